@@ -1,19 +1,28 @@
 //! HTTP API handlers
 
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::info;
 
+use crate::clipboard::{restore_entry, ClipboardContentType, ClipboardEntry, ClipboardFormat};
 use crate::dedup::EventKey;
-use crate::redact::redact_command;
-use crate::storage::{EditorEventData, EventSource, ShellEventData};
+use crate::export::{self, ExportFormat};
+use crate::focus::{self, FocusSession};
+use crate::jobs::{JobOutcome, RunError};
+use crate::notes::{annotate, normalize_tags, NoteData, MAX_NOTE_LENGTH};
+use crate::redact::Redactor;
+use crate::reports::{Report, ReportGenerator, ReportPeriod};
+use crate::storage::{ClipboardFilter, EditorEventData, EventSource, ShellEventData};
+use crate::summary::MAX_SUMMARY_EVENTS;
+use crate::tasks::TaskClusterer;
 use crate::watcher::{FileEventData, FileWatcher, WatcherConfig};
 use crate::AppState;
 
@@ -52,7 +61,7 @@ pub async fn ingest_shell_event(
     Json(payload): Json<ShellEventRequest>,
 ) -> impl IntoResponse {
     // Redact sensitive information from command
-    let redaction_result = redact_command(&payload.command);
+    let redaction_result = state.redactor.redact_command(&payload.command);
 
     // If command should be skipped entirely (e.g., password manager commands), return success without storing
     let redacted_command = match redaction_result.command {
@@ -67,9 +76,14 @@ pub async fn ingest_shell_event(
     };
 
     if redaction_result.was_redacted {
+        let kinds: Vec<&str> = redaction_result
+            .findings
+            .iter()
+            .map(|f| f.kind.as_str())
+            .collect();
         info!(
-            "Redacted {} sensitive pattern(s) from command",
-            redaction_result.redaction_count
+            "Redacted {} sensitive pattern(s) from command {:?}",
+            redaction_result.redaction_count, kinds
         );
     }
 
@@ -425,6 +439,110 @@ pub async fn get_session_info(State(state): State<Arc<AppState>>) -> impl IntoRe
     Json(response)
 }
 
+/// Query parameters for session history
+#[derive(Debug, Deserialize)]
+pub struct SessionsQuery {
+    /// Start of the range (default: 7 days ago)
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default: now)
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// List completed work sessions overlapping a time range
+pub async fn get_sessions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SessionsQuery>,
+) -> impl IntoResponse {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(7));
+    if from >= to {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "'from' must be before 'to'" })),
+        );
+    }
+
+    let store = state.store.lock().unwrap();
+    match store.get_sessions(from, to) {
+        Ok(sessions) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "from": from,
+                "to": to,
+                "count": sessions.len(),
+                "sessions": sessions
+            })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Get a work session with its events, projects and summary.
+/// The live session can be fetched by id too.
+pub async fn get_session_detail(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    use crate::summary::{SummaryConfig, SummaryGenerator};
+
+    let live = state
+        .idle_detector
+        .lock()
+        .unwrap()
+        .get_session()
+        .filter(|s| s.session_id == id);
+
+    let store = state.store.lock().unwrap();
+    let session = match live {
+        Some(session) => session,
+        None => match store.get_session(&id) {
+            Ok(Some(session)) => session,
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({ "error": format!("No session with id '{}'", id) })),
+                )
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": e.to_string() })),
+                )
+            }
+        },
+    };
+
+    let end = session.ended_at.unwrap_or_else(Utc::now);
+    let events = store
+        .get_events_between(session.started_at, end, None)
+        .unwrap_or_default();
+    let config = SummaryConfig {
+        min_session_duration: 0,
+        ..state.summary_config.clone()
+    };
+    let summary = SummaryGenerator::with_categories(config, Arc::clone(&state.categories))
+        .generate_summary(&store, session.started_at, end);
+    let projects = summary
+        .as_ref()
+        .map(|s| s.projects.clone())
+        .unwrap_or_default();
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "session": session,
+            "projects": projects,
+            "summary": summary,
+            "events": events
+        })),
+    )
+}
+
 /// Query parameters for events endpoint
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -529,6 +647,15 @@ pub async fn cleanup_events(
                 deleted, payload.retention_days
             );
 
+            // Clipboard images go with the events that reference them
+            let max_age = std::time::Duration::from_secs(payload.retention_days as u64 * 86400);
+            if let Err(e) = state
+                .blobs
+                .cleanup(max_age, state.clipboard_config.max_blob_storage_bytes)
+            {
+                tracing::warn!("Failed to clean up clipboard blobs: {}", e);
+            }
+
             let vacuumed = if payload.vacuum {
                 store.vacuum().is_ok()
             } else {
@@ -694,10 +821,13 @@ pub async fn get_session_summary(
     State(state): State<Arc<AppState>>,
     Query(query): Query<SummaryQuery>,
 ) -> impl IntoResponse {
-    use crate::summary::{SummaryConfig, SummaryGenerator};
+    use crate::summary::SummaryGenerator;
 
     let store = state.store.lock().unwrap();
-    let generator = SummaryGenerator::new(SummaryConfig::default());
+    let generator = SummaryGenerator::with_categories(
+        state.summary_config.clone(),
+        Arc::clone(&state.categories),
+    );
 
     match generator.generate_recent_summary(&store, query.hours) {
         Some(summary) => (
@@ -714,6 +844,300 @@ pub async fn get_session_summary(
     }
 }
 
+/// Query parameters for a daily report
+#[derive(Debug, Deserialize)]
+pub struct DailyReportQuery {
+    /// Local date, `YYYY-MM-DD` (default: today)
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+}
+
+/// Query parameters for a weekly report
+#[derive(Debug, Deserialize)]
+pub struct WeeklyReportQuery {
+    /// ISO week such as `2026-W42`, or a date in the week (default: this week)
+    #[serde(default)]
+    pub week: Option<String>,
+}
+
+/// Report for a local calendar day, compared with the day before
+pub async fn get_daily_report(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DailyReportQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    match load_report(&state, ReportPeriod::Day(date)) {
+        Ok(report) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "report": report })),
+        ),
+        Err(e) => e,
+    }
+}
+
+/// Report for an ISO week (Monday to Sunday), compared with the week before
+pub async fn get_weekly_report(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WeeklyReportQuery>,
+) -> impl IntoResponse {
+    match weekly_period(&query).and_then(|period| load_report(&state, period)) {
+        Ok(report) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "report": report })),
+        ),
+        Err(e) => e,
+    }
+}
+
+fn weekly_period(
+    query: &WeeklyReportQuery,
+) -> Result<ReportPeriod, (StatusCode, Json<serde_json::Value>)> {
+    match &query.week {
+        Some(week) => ReportPeriod::parse_week(week).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": "'week' must be an ISO week like 2026-W42 or a date"
+                })),
+            )
+        }),
+        None => Ok(ReportPeriod::week_of(Local::now().date_naive())),
+    }
+}
+
+fn load_report(
+    state: &AppState,
+    period: ReportPeriod,
+) -> Result<Report, (StatusCode, Json<serde_json::Value>)> {
+    let now = Utc::now();
+    if period.bounds(&Local).0 > now {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": format!("{} hasn't started yet", period.label()) })),
+        ));
+    }
+
+    let store = state.store.lock().unwrap();
+    let generator = ReportGenerator::new(
+        state.summary_config.clone(),
+        Arc::clone(&state.categories),
+        state.retention_days,
+    );
+    generator.report(&store, period, &Local, now).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+    })
+}
+
+/// Query parameters for exports
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// `markdown` (default) or `json`
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Markdown as text, JSON as it is
+fn export_response(
+    format: ExportFormat,
+    markdown: impl FnOnce() -> String,
+    json: impl FnOnce() -> serde_json::Value,
+) -> Response {
+    match format {
+        ExportFormat::Markdown => (
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            markdown(),
+        )
+            .into_response(),
+        ExportFormat::Json => (StatusCode::OK, Json(json())).into_response(),
+    }
+}
+
+/// Export the summary of the last N hours
+pub async fn export_summary(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SummaryQuery>,
+    Query(export): Query<ExportQuery>,
+) -> Response {
+    use crate::summary::SummaryGenerator;
+
+    let store = state.store.lock().unwrap();
+    let generator = SummaryGenerator::with_categories(
+        state.summary_config.clone(),
+        Arc::clone(&state.categories),
+    );
+
+    match generator.generate_recent_summary(&store, query.hours) {
+        Some(summary) => export_response(
+            export.format,
+            || export::summary_markdown(&summary, &Local),
+            || export::summary_json(&summary),
+        ),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Not enough data for summary" })),
+        )
+            .into_response(),
+    }
+}
+
+/// Export the report for a local calendar day
+pub async fn export_daily_report(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DailyReportQuery>,
+    Query(export): Query<ExportQuery>,
+) -> Response {
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    match load_report(&state, ReportPeriod::Day(date)) {
+        Ok(report) => export_response(
+            export.format,
+            || export::report_markdown(&report, &Local),
+            || export::report_json(&report),
+        ),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Export the report for an ISO week
+pub async fn export_weekly_report(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WeeklyReportQuery>,
+    Query(export): Query<ExportQuery>,
+) -> Response {
+    match weekly_period(&query).and_then(|period| load_report(&state, period)) {
+        Ok(report) => export_response(
+            export.format,
+            || export::report_markdown(&report, &Local),
+            || export::report_json(&report),
+        ),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Write the day's report into its Obsidian daily note as a `## Siphon` block
+pub async fn export_obsidian(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DailyReportQuery>,
+) -> impl IntoResponse {
+    if state.obsidian_config.vault.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "No Obsidian vault is configured" })),
+        );
+    }
+
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    let report = match load_report(&state, ReportPeriod::Day(date)) {
+        Ok(report) => report,
+        Err(e) => return e,
+    };
+    let block = export::daily_note_block(&report, &Local);
+    match export::write_daily_note(&state.obsidian_config, date, &block) {
+        Ok((path, changed)) => {
+            if changed {
+                info!("Updated daily note {:?}", path);
+            }
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "path": path, "changed": changed })),
+            )
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Scheduled jobs with their schedules and last runs
+pub async fn get_jobs(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(serde_json::json!({ "jobs": state.jobs.statuses() }))
+}
+
+/// Run a job now, returning it with the run's outcome
+pub async fn run_job(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match tokio::task::block_in_place(|| state.jobs.run_now(&state, &name)) {
+        Ok(job) => {
+            info!("Job '{}' run from the API", name);
+            let failed = job
+                .last_run
+                .as_ref()
+                .is_some_and(|run| run.outcome == JobOutcome::Failed);
+            let status = if failed {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::OK
+            };
+            (status, Json(serde_json::json!({ "job": job })))
+        }
+        Err(e) => {
+            let status = match e {
+                RunError::Unknown(_) => StatusCode::NOT_FOUND,
+                RunError::AlreadyRunning(_) => StatusCode::CONFLICT,
+            };
+            (status, Json(serde_json::json!({ "error": e.to_string() })))
+        }
+    }
+}
+
+/// Query parameters for the task timeline
+#[derive(Debug, Deserialize)]
+pub struct TasksQuery {
+    /// Start of the range (default: 24 hours ago)
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default: now)
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Activity in a time range clustered into labelled task segments, oldest first
+pub async fn get_tasks(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TasksQuery>,
+) -> impl IntoResponse {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::hours(24));
+    if from >= to {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "'from' must be before 'to'" })),
+        );
+    }
+
+    // Clustering is quadratic in the worst case, so cap the events and let go
+    // of the store before it starts
+    let (events, spans) = {
+        let store = state.store.lock().unwrap();
+        let events = match store.get_events_between(from, to, Some(MAX_SUMMARY_EVENTS)) {
+            Ok(events) => events,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": e.to_string() })),
+                )
+            }
+        };
+        (events, store.get_focus_spans(from, to).unwrap_or_default())
+    };
+    let tasks = TaskClusterer::default().cluster(&events, &spans);
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "from": from,
+            "to": to,
+            "count": tasks.len(),
+            "tasks": tasks
+        })),
+    )
+}
+
 /// Get current meeting state
 pub async fn get_meeting_state(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let detector = state.meeting_detector.lock().unwrap();
@@ -726,3 +1150,602 @@ pub async fn get_meeting_state(State(state): State<Arc<AppState>>) -> impl IntoR
         "title": state.title
     }))
 }
+
+/// Query parameters for meeting history
+#[derive(Debug, Deserialize)]
+pub struct MeetingsQuery {
+    /// Start of the range (default: 7 days ago)
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default: now)
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// List meetings overlapping a time range, each with the activity around it
+pub async fn get_meetings(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<MeetingsQuery>,
+) -> impl IntoResponse {
+    use crate::summary::SummaryGenerator;
+
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(7));
+    if from >= to {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "'from' must be before 'to'" })),
+        );
+    }
+
+    let store = state.store.lock().unwrap();
+    let meetings = match store.get_meeting_spans(from, to) {
+        Ok(meetings) => meetings,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+        }
+    };
+
+    let generator = SummaryGenerator::with_categories(
+        state.summary_config.clone(),
+        Arc::clone(&state.categories),
+    );
+    let now = Utc::now();
+    let meetings: Vec<_> = meetings
+        .iter()
+        .map(|meeting| {
+            let context = generator.meeting_context(&store, meeting, now);
+            serde_json::json!({
+                "id": meeting.id,
+                "platform": meeting.platform,
+                "platform_name": meeting.platform.to_string(),
+                "title": meeting.title,
+                "started_at": meeting.started_at,
+                "ended_at": meeting.ended_at,
+                "duration_minutes": meeting.duration_minutes(now),
+                "in_progress": meeting.ended_at.is_none(),
+                "missed": meeting.missed,
+                "confidence": meeting.confidence,
+                "calendar": meeting.calendar_uid.as_ref().map(|uid| serde_json::json!({
+                    "uid": uid,
+                    "attendee_count": meeting.attendee_count
+                })),
+                "before": context.before,
+                "after": context.after
+            })
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "from": from,
+            "to": to,
+            "count": meetings.len(),
+            "meetings": meetings
+        })),
+    )
+}
+
+/// Meeting platform patterns in use, configured ones first
+pub async fn get_meeting_platforms(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let detector = state.meeting_detector.lock().unwrap();
+    let platforms: Vec<_> = detector
+        .patterns()
+        .iter()
+        .map(|pattern| {
+            serde_json::json!({
+                "platform": pattern.platform,
+                "name": pattern.platform.to_string(),
+                "source": if pattern.custom { "config" } else { "builtin" },
+                "apps": pattern.apps,
+                "titles": pattern.titles.iter().map(|re| re.as_str()).collect::<Vec<_>>(),
+                "domains": pattern.domains,
+                "join_paths": pattern.join_paths.iter().map(|re| re.as_str()).collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    Json(serde_json::json!({ "platforms": platforms }))
+}
+
+/// Configured hotkeys and whether each one is active
+pub async fn get_hotkeys(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let manager = state.hotkey_manager.lock().unwrap();
+    match manager.as_ref() {
+        Some(manager) => Json(serde_json::json!({
+            "enabled": true,
+            "bindings": manager.bindings()
+        })),
+        None => Json(serde_json::json!({
+            "enabled": false,
+            "bindings": []
+        })),
+    }
+}
+
+/// Focus session start request body
+#[derive(Debug, Default, Deserialize)]
+pub struct StartFocusRequest {
+    /// What the session is for
+    #[serde(default)]
+    pub goal: Option<String>,
+    /// Planned length in minutes; the session ends on its own once reached
+    #[serde(default)]
+    pub target_minutes: Option<u32>,
+}
+
+/// Query parameters for focus session history
+#[derive(Debug, Deserialize)]
+pub struct FocusSessionsQuery {
+    #[serde(default = "default_focus_sessions_limit")]
+    pub limit: u32,
+}
+
+fn default_focus_sessions_limit() -> u32 {
+    20
+}
+
+fn focus_session_json(session: &FocusSession) -> serde_json::Value {
+    let now = Utc::now();
+    let mut value = serde_json::to_value(session).unwrap_or_default();
+    value["duration_minutes"] = serde_json::json!(session.duration_minutes(now));
+    value["target_reached"] = serde_json::json!(session.target_reached(now));
+    value
+}
+
+/// Get the running focus session, if any
+pub async fn get_focus(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let focus = state.focus.lock().unwrap();
+    Json(serde_json::json!({
+        "active": focus.active().is_some(),
+        "session": focus.active().map(focus_session_json)
+    }))
+}
+
+/// Start a focus session
+pub async fn start_focus(
+    State(state): State<Arc<AppState>>,
+    payload: Option<Json<StartFocusRequest>>,
+) -> impl IntoResponse {
+    let request = payload.map(|Json(p)| p).unwrap_or_default();
+    let mut focus = state.focus.lock().unwrap();
+    match focus.start(request.goal, request.target_minutes, Utc::now()) {
+        Ok(session) => {
+            info!("Focus session {} started", session.id);
+            (StatusCode::CREATED, Json(focus_session_json(session)))
+        }
+        Err(e) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Stop the running focus session, returning it with its focus score
+pub async fn stop_focus(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    use crate::summary::SummaryGenerator;
+
+    let stopped = state.focus.lock().unwrap().stop(Utc::now());
+    let Some(session) = stopped else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "No focus session is active" })),
+        );
+    };
+
+    let store = state.store.lock().unwrap();
+    let generator = SummaryGenerator::with_categories(
+        state.summary_config.clone(),
+        Arc::clone(&state.categories),
+    );
+    match focus::complete_session(session, &store, &generator) {
+        Ok(session) => {
+            info!(
+                "Focus session {} ended with score {}",
+                session.id,
+                session.focus_score.unwrap_or_default()
+            );
+            (StatusCode::OK, Json(focus_session_json(&session)))
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+/// List completed focus sessions, newest first
+pub async fn get_focus_sessions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<FocusSessionsQuery>,
+) -> impl IntoResponse {
+    let store = state.store.lock().unwrap();
+    match store.get_focus_sessions(params.limit.min(500)) {
+        Ok(sessions) => {
+            let sessions: Vec<_> = sessions.iter().map(focus_session_json).collect();
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "count": sessions.len(),
+                    "sessions": sessions
+                })),
+            )
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Query parameters for clipboard history
+#[derive(Debug, Deserialize)]
+pub struct ClipboardQuery {
+    #[serde(default = "default_clipboard_limit")]
+    pub limit: u32,
+    #[serde(default)]
+    pub offset: u32,
+    /// Content type (text, code, url, path, command)
+    #[serde(default, rename = "type")]
+    pub content_type: Option<ClipboardContentType>,
+    /// Source app name
+    #[serde(default)]
+    pub app: Option<String>,
+    /// Text search
+    #[serde(default)]
+    pub q: Option<String>,
+}
+
+fn default_clipboard_limit() -> u32 {
+    50
+}
+
+/// List clipboard history, newest first
+pub async fn get_clipboard_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ClipboardQuery>,
+) -> impl IntoResponse {
+    let filter = ClipboardFilter {
+        content_type: query.content_type.map(|t| t.as_str().to_string()),
+        source_app: query.app,
+        search: query.q.filter(|q| !q.is_empty()),
+    };
+    let limit = query.limit.clamp(1, 500);
+
+    let store = state.store.lock().unwrap();
+    match store.get_clipboard_events(&filter, limit, query.offset) {
+        Ok((events, total)) => {
+            let entries: Vec<_> = events
+                .iter()
+                .filter_map(ClipboardEntry::from_event)
+                .collect();
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "entries": entries,
+                    "total": total,
+                    "limit": limit,
+                    "offset": query.offset
+                })),
+            )
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Look up one clipboard entry by id or unique id prefix
+fn find_clipboard_entry(
+    state: &AppState,
+    id: &str,
+) -> Result<ClipboardEntry, (StatusCode, Json<serde_json::Value>)> {
+    if id.len() < 4 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Id must be at least 4 characters" })),
+        ));
+    }
+
+    let events = state
+        .store
+        .lock()
+        .unwrap()
+        .find_clipboard_events(id)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+        })?;
+
+    match events.as_slice() {
+        [event] => ClipboardEntry::from_event(event).ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Stored clipboard entry is unreadable" })),
+            )
+        }),
+        [] => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Clipboard entry not found" })),
+        )),
+        _ => Err((
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "Id prefix matches more than one entry" })),
+        )),
+    }
+}
+
+/// Get a single clipboard entry
+pub async fn get_clipboard_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match find_clipboard_entry(&state, &id) {
+        Ok(entry) => (StatusCode::OK, Json(serde_json::json!({ "entry": entry }))),
+        Err(e) => e,
+    }
+}
+
+/// Put a clipboard entry back on the system clipboard
+pub async fn restore_clipboard_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let entry = match find_clipboard_entry(&state, &id) {
+        Ok(entry) => entry,
+        Err(e) => return e,
+    };
+
+    // Redacted or truncated text isn't what was copied; don't hand it back as if it were
+    if !entry.is_restorable() {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Entry was redacted or truncated; the original text was not stored",
+                "id": entry.id
+            })),
+        );
+    }
+
+    let result = match &state.clipboard {
+        Some(clipboard) => clipboard.restore(&entry),
+        None => restore_entry(&entry, &state.blobs),
+    };
+
+    match result {
+        Ok(()) => {
+            info!("Restored clipboard entry {}", entry.id);
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "restored": true, "id": entry.id })),
+            )
+        }
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": format!("Failed to set clipboard: {}", e) })),
+        ),
+    }
+}
+
+/// Note creation request body
+#[derive(Debug, Deserialize)]
+pub struct CreateNoteRequest {
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When the note applies; defaults to the anchored event's time, then now
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Defaults to the anchored event's project
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Event the note is about
+    #[serde(default)]
+    pub event_id: Option<String>,
+}
+
+/// Note update request body; omitted fields are left unchanged
+#[derive(Debug, Deserialize)]
+pub struct UpdateNoteRequest {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+fn note_error(
+    status: StatusCode,
+    message: impl Into<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "error": message.into() })))
+}
+
+/// Check note text and strip any secrets pasted into it, as the redaction mode says
+fn clean_note_text(
+    redactor: &Redactor,
+    text: &str,
+) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let text = text.trim();
+    if text.len() > MAX_NOTE_LENGTH {
+        return Err(note_error(
+            StatusCode::BAD_REQUEST,
+            format!("Note is longer than {} bytes", MAX_NOTE_LENGTH),
+        ));
+    }
+    Ok(redactor.redact_text(text).0)
+}
+
+/// Create a freeform note, optionally anchored to an event
+pub async fn create_note(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateNoteRequest>,
+) -> impl IntoResponse {
+    let text = match clean_note_text(&state.redactor, &payload.text) {
+        Ok(text) if !text.is_empty() => text,
+        Ok(_) => return note_error(StatusCode::BAD_REQUEST, "Note text is empty"),
+        Err(e) => return e,
+    };
+
+    let store = state.store.lock().unwrap();
+    let anchor = match payload.event_id.as_deref() {
+        Some(event_id) => match store.get_event(event_id) {
+            Ok(Some(event)) => Some(event),
+            Ok(None) => {
+                return note_error(
+                    StatusCode::NOT_FOUND,
+                    format!("No event with id '{}'", event_id),
+                )
+            }
+            Err(e) => return note_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        },
+        None => None,
+    };
+
+    let timestamp = payload
+        .timestamp
+        .or(anchor.as_ref().map(|e| e.timestamp))
+        .unwrap_or_else(Utc::now);
+    let project = payload
+        .project
+        .or_else(|| anchor.as_ref().and_then(|e| e.project.clone()));
+    let note = NoteData {
+        text,
+        tags: normalize_tags(&payload.tags),
+        event_id: payload.event_id,
+    };
+
+    let event_json = serde_json::to_string(&note).unwrap_or_default();
+    match store.insert_event_at(
+        EventSource::Note,
+        "note",
+        &event_json,
+        project.as_deref(),
+        timestamp,
+    ) {
+        Ok(id) => {
+            drop(store);
+            let mut idle = state.idle_detector.lock().unwrap();
+            idle.record_activity("note");
+            drop(idle);
+            info!("Recorded note {}", id);
+            (
+                StatusCode::CREATED,
+                Json(serde_json::json!({
+                    "id": id,
+                    "timestamp": timestamp,
+                    "project": project,
+                    "note": note
+                })),
+            )
+        }
+        Err(e) => note_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// Edit a note, or annotate a hotkey mark/trigger with text and tags
+pub async fn update_note(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateNoteRequest>,
+) -> impl IntoResponse {
+    if payload.text.is_none() && payload.tags.is_none() {
+        return note_error(
+            StatusCode::BAD_REQUEST,
+            "Nothing to update: give text or tags",
+        );
+    }
+    let text = match payload
+        .text
+        .as_deref()
+        .map(|text| clean_note_text(&state.redactor, text))
+        .transpose()
+    {
+        Ok(text) => text,
+        Err(e) => return e,
+    };
+
+    let store = state.store.lock().unwrap();
+    let event = match store.get_event(&id) {
+        Ok(Some(event)) => event,
+        Ok(None) => return note_error(StatusCode::NOT_FOUND, format!("No event with id '{}'", id)),
+        Err(e) => return note_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    if event.source == "note" && text.as_deref() == Some("") {
+        return note_error(StatusCode::BAD_REQUEST, "Note text is empty");
+    }
+
+    let event_data = match annotate(&event, text.as_deref(), payload.tags.as_deref()) {
+        Ok(data) => data,
+        Err(e) => return note_error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+    };
+    match store.update_event_data(&id, &event_data) {
+        Ok(_) => {
+            info!("Annotated {} event {}", event.source, id);
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "id": id,
+                    "source": event.source,
+                    "event_type": event.event_type,
+                    "timestamp": event.timestamp,
+                    "data": serde_json::from_str::<serde_json::Value>(&event_data)
+                        .unwrap_or_default()
+                })),
+            )
+        }
+        Err(e) => note_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// Query parameters for clipboard images
+#[derive(Debug, Deserialize)]
+pub struct ClipboardImageQuery {
+    /// Return the thumbnail instead of the full image
+    #[serde(default)]
+    pub thumbnail: bool,
+}
+
+/// Get the PNG for an image clipboard entry
+pub async fn get_clipboard_image(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<ClipboardImageQuery>,
+) -> Response {
+    let entry = match find_clipboard_entry(&state, &id) {
+        Ok(entry) => entry,
+        Err(e) => return e.into_response(),
+    };
+
+    let blob = match (entry.format, entry.image) {
+        (ClipboardFormat::Image, Some(blob)) => blob,
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Entry has no stored image" })),
+            )
+                .into_response()
+        }
+    };
+
+    let path = if query.thumbnail {
+        state.blobs.thumbnail_path(&blob.hash)
+    } else {
+        state.blobs.blob_path(&blob.hash)
+    };
+
+    match std::fs::read(&path) {
+        Ok(png) => ([(header::CONTENT_TYPE, "image/png")], png).into_response(),
+        Err(_) => (
+            StatusCode::GONE,
+            Json(serde_json::json!({ "error": "Image blob was removed by retention cleanup" })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,271 @@
+//! Content-addressed blob storage
+//!
+//! Binary captures (clipboard images) are stored outside SQLite under
+//! `~/.siphon/blobs`, named by the SHA-256 of their contents so the same
+//! image copied twice is stored once. Each image gets a small PNG thumbnail
+//! next to it for the dashboard and API.
+
+use image::{imageops::FilterType, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info};
+
+/// Longest edge of generated thumbnails, in pixels
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// A stored image blob
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageBlob {
+    /// SHA-256 of the PNG-encoded image, hex
+    pub hash: String,
+    pub width: u32,
+    pub height: u32,
+    /// Size of the stored PNG in bytes
+    pub size_bytes: u64,
+}
+
+/// Blob directory with content-addressed files
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    /// Blob store at `~/.siphon/blobs`
+    pub fn new() -> Self {
+        Self::open(
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".siphon")
+                .join("blobs"),
+        )
+    }
+
+    /// Blob store at a specific directory
+    pub fn open(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Path of a blob by hash (`<root>/ab/abcdef….png`)
+    pub fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root
+            .join(hash.get(..2).unwrap_or("00"))
+            .join(format!("{}.png", hash))
+    }
+
+    /// Path of a blob's thumbnail
+    pub fn thumbnail_path(&self, hash: &str) -> PathBuf {
+        self.root
+            .join(hash.get(..2).unwrap_or("00"))
+            .join(format!("{}.thumb.png", hash))
+    }
+
+    /// Store raw RGBA pixels as a PNG plus thumbnail, returning the blob reference
+    pub fn put_rgba(&self, width: u32, height: u32, rgba: Vec<u8>) -> io::Result<ImageBlob> {
+        let image = RgbaImage::from_raw(width, height, rgba).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "pixel buffer doesn't match size",
+            )
+        })?;
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut io::Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(io::Error::other)?;
+        let hash = hex(&Sha256::digest(&png));
+
+        let path = self.blob_path(&hash);
+        if path.exists() {
+            // Already stored; refresh mtime so retention counts from the latest copy
+            std::fs::File::options()
+                .append(true)
+                .open(&path)?
+                .set_modified(SystemTime::now())?;
+            debug!("Blob {} already stored", hash);
+        } else {
+            std::fs::create_dir_all(path.parent().unwrap_or(&self.root))?;
+            write_atomic(&path, &png)?;
+
+            let (tw, th) = thumbnail_dimensions(width, height);
+            let thumbnail = image::imageops::resize(&image, tw, th, FilterType::Triangle);
+            let mut thumb_png = Vec::new();
+            thumbnail
+                .write_to(&mut io::Cursor::new(&mut thumb_png), ImageFormat::Png)
+                .map_err(io::Error::other)?;
+            write_atomic(&self.thumbnail_path(&hash), &thumb_png)?;
+        }
+
+        Ok(ImageBlob {
+            hash,
+            width,
+            height,
+            size_bytes: png.len() as u64,
+        })
+    }
+
+    /// Read a blob back as raw RGBA pixels
+    pub fn get_rgba(&self, hash: &str) -> io::Result<(u32, u32, Vec<u8>)> {
+        let image = image::open(self.blob_path(hash))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            .into_rgba8();
+        Ok((image.width(), image.height(), image.into_raw()))
+    }
+
+    /// Delete blobs older than `max_age`, then the oldest ones until the total fits `max_total_bytes`
+    pub fn cleanup(&self, max_age: Duration, max_total_bytes: u64) -> io::Result<usize> {
+        let mut blobs = Vec::new();
+        for shard in read_dir_paths(&self.root)? {
+            for path in read_dir_paths(&shard)? {
+                let is_blob = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.ends_with(".png") && !n.ends_with(".thumb.png"));
+                if is_blob {
+                    let meta = std::fs::metadata(&path)?;
+                    blobs.push((meta.modified()?, meta.len(), path));
+                }
+            }
+        }
+
+        // Oldest first
+        blobs.sort_by_key(|(modified, _, _)| *modified);
+        let now = SystemTime::now();
+        let mut total: u64 = blobs.iter().map(|(_, len, _)| len).sum();
+        let mut removed = 0;
+
+        for (modified, len, path) in blobs {
+            let expired = now.duration_since(modified).unwrap_or_default() > max_age;
+            if !expired && total <= max_total_bytes {
+                break;
+            }
+            std::fs::remove_file(&path)?;
+            let _ = std::fs::remove_file(path.with_extension("thumb.png"));
+            total = total.saturating_sub(len);
+            removed += 1;
+        }
+
+        if removed > 0 {
+            info!("Removed {} clipboard blob(s)", removed);
+        }
+        Ok(removed)
+    }
+}
+
+impl Default for BlobStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_dir_paths(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => Ok(entries.filter_map(|e| e.ok()).map(|e| e.path()).collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write via a temp file so a crash never leaves a half-written blob under its final name
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Scale so the longest edge is at most [`THUMBNAIL_SIZE`], keeping aspect ratio
+fn thumbnail_dimensions(width: u32, height: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= THUMBNAIL_SIZE {
+        return (width.max(1), height.max(1));
+    }
+    let scale = THUMBNAIL_SIZE as f64 / longest as f64;
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempStore(BlobStore, PathBuf);
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.1);
+        }
+    }
+
+    fn temp_store() -> TempStore {
+        let root = std::env::temp_dir().join(format!("siphon-blobs-{}", uuid::Uuid::new_v4()));
+        TempStore(BlobStore::open(root.clone()), root)
+    }
+
+    fn pixels(width: u32, height: u32, shade: u8) -> Vec<u8> {
+        (0..width * height)
+            .flat_map(|i| [shade, (i % 256) as u8, 0, 255])
+            .collect()
+    }
+
+    #[test]
+    fn test_put_is_content_addressed_with_thumbnail() {
+        let store = temp_store();
+        let a = store.0.put_rgba(600, 300, pixels(600, 300, 10)).unwrap();
+        let again = store.0.put_rgba(600, 300, pixels(600, 300, 10)).unwrap();
+        let b = store.0.put_rgba(600, 300, pixels(600, 300, 20)).unwrap();
+
+        assert_eq!(a, again);
+        assert_ne!(a.hash, b.hash);
+        assert_eq!((a.width, a.height), (600, 300));
+        assert!(store.0.blob_path(&a.hash).exists());
+
+        let thumb = image::open(store.0.thumbnail_path(&a.hash)).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (256, 128));
+
+        let (w, h, rgba) = store.0.get_rgba(&a.hash).unwrap();
+        assert_eq!((w, h), (600, 300));
+        assert_eq!(rgba, pixels(600, 300, 10));
+    }
+
+    #[test]
+    fn test_put_rejects_mismatched_buffer() {
+        let store = temp_store();
+        assert!(store.0.put_rgba(10, 10, vec![0; 12]).is_err());
+    }
+
+    #[test]
+    fn test_cleanup_by_size_removes_oldest_first() {
+        let store = temp_store();
+        let old = store.0.put_rgba(64, 64, pixels(64, 64, 1)).unwrap();
+        let old_path = store.0.blob_path(&old.hash);
+        std::fs::File::options()
+            .append(true)
+            .open(&old_path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+        let new = store.0.put_rgba(64, 64, pixels(64, 64, 2)).unwrap();
+
+        // Nothing expired and everything fits
+        let week = Duration::from_secs(7 * 86400);
+        assert_eq!(store.0.cleanup(week, u64::MAX).unwrap(), 0);
+
+        // Only room for one blob: the older one goes, with its thumbnail
+        assert_eq!(store.0.cleanup(week, new.size_bytes).unwrap(), 1);
+        assert!(!old_path.exists());
+        assert!(!store.0.thumbnail_path(&old.hash).exists());
+        assert!(store.0.blob_path(&new.hash).exists());
+
+        // Age limit
+        assert_eq!(store.0.cleanup(Duration::ZERO, u64::MAX).unwrap(), 1);
+    }
+}
@@ -0,0 +1,683 @@
+//! Browser History Collector
+//!
+//! Incrementally reads Chromium-family and Firefox history databases and
+//! turns new visits into browser events. Browsers keep their history files
+//! locked (and recent writes in a `-wal` file), so each poll copies the
+//! database to a temp directory and reads the copy.
+//!
+//! Read positions are tracked per profile by visit id, so each visit is
+//! reported once even across daemon restarts (see [`BrowserHistoryCollector::cursors`]).
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+use crate::categories::{host_matches, url_host};
+use crate::secrets::SecretDetector;
+
+/// Microseconds between 1601-01-01 (Chromium/WebKit epoch) and 1970-01-01
+const WEBKIT_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+/// Maximum visits read from one profile per poll
+const MAX_VISITS_PER_POLL: i64 = 500;
+
+/// Chromium-family browsers: (name, user data dir relative to the config dir)
+#[cfg(target_os = "macos")]
+const CHROMIUM_BROWSERS: &[(&str, &str)] = &[
+    ("chrome", "Google/Chrome"),
+    ("chromium", "Chromium"),
+    ("brave", "BraveSoftware/Brave-Browser"),
+    ("edge", "Microsoft Edge"),
+    ("vivaldi", "Vivaldi"),
+    ("arc", "Arc/User Data"),
+];
+
+#[cfg(not(target_os = "macos"))]
+const CHROMIUM_BROWSERS: &[(&str, &str)] = &[
+    ("chrome", "google-chrome"),
+    ("chromium", "chromium"),
+    ("brave", "BraveSoftware/Brave-Browser"),
+    ("edge", "microsoft-edge"),
+    ("vivaldi", "vivaldi"),
+];
+
+/// History database format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryFormat {
+    /// `History` file used by Chrome, Chromium, Brave, Edge, Vivaldi, Arc
+    Chromium,
+    /// `places.sqlite` used by Firefox and its forks
+    Firefox,
+}
+
+impl HistoryFormat {
+    /// Guess the format from the database file name
+    pub fn from_path(path: &Path) -> Self {
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("places.sqlite") => HistoryFormat::Firefox,
+            _ => HistoryFormat::Chromium,
+        }
+    }
+}
+
+/// A browser profile's history database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryProfile {
+    /// Browser name, e.g. "chrome" or "firefox"
+    pub browser: String,
+    /// Profile directory name, e.g. "Default"
+    pub profile: String,
+    pub format: HistoryFormat,
+    pub path: PathBuf,
+}
+
+impl HistoryProfile {
+    /// Create a profile for a history file, inferring format and profile name
+    pub fn new(browser: &str, path: PathBuf) -> Self {
+        let profile = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self {
+            browser: browser.to_string(),
+            profile,
+            format: HistoryFormat::from_path(&path),
+            path,
+        }
+    }
+
+    /// Name of this profile's read cursor
+    pub fn cursor_key(&self) -> String {
+        format!("browser:{}", self.path.display())
+    }
+}
+
+/// A history file configured by hand
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryProfileConfig {
+    pub browser: String,
+    pub path: PathBuf,
+}
+
+/// The `[browser]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BrowserConfig {
+    /// Enable the history collector (off by default)
+    pub enabled: bool,
+    /// Seconds between history polls
+    pub poll_interval_secs: u64,
+    /// How far back to read the first time a profile is seen
+    pub backfill_hours: u32,
+    /// Only record these domains (and subdomains); empty means all
+    pub allow_domains: Vec<String>,
+    /// Never record these domains (and subdomains); wins over the allow list
+    pub deny_domains: Vec<String>,
+    /// Extra history files to read in addition to the auto-detected ones
+    pub profiles: Vec<HistoryProfileConfig>,
+}
+
+impl Default for BrowserConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: 60,
+            backfill_hours: 24,
+            allow_domains: Vec::new(),
+            deny_domains: Vec::new(),
+            profiles: Vec::new(),
+        }
+    }
+}
+
+/// A single page visit read from browser history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrowserVisit {
+    pub browser: String,
+    pub profile: String,
+    pub url: String,
+    pub title: String,
+    pub domain: String,
+    pub visited_at: DateTime<Utc>,
+    /// Time the page stayed open (Chromium only; Firefox doesn't record it)
+    pub visit_duration_ms: Option<u64>,
+    /// How the visit happened: link, typed, bookmark, reload, form_submit, ...
+    pub transition: String,
+}
+
+/// Reads new visits from browser history databases
+pub struct BrowserHistoryCollector {
+    config: BrowserConfig,
+    profiles: Vec<HistoryProfile>,
+    /// Last visit id read, keyed by [`HistoryProfile::cursor_key`]
+    cursors: HashMap<String, i64>,
+}
+
+impl BrowserHistoryCollector {
+    /// Create a collector for the auto-detected and configured profiles
+    pub fn new(config: BrowserConfig) -> Self {
+        let mut profiles = discover_profiles();
+        for extra in &config.profiles {
+            profiles.push(HistoryProfile::new(&extra.browser, extra.path.clone()));
+        }
+        Self::with_profiles(config, profiles)
+    }
+
+    /// Create a collector for an explicit list of profiles
+    pub fn with_profiles(config: BrowserConfig, profiles: Vec<HistoryProfile>) -> Self {
+        for profile in &profiles {
+            info!(
+                "Browser history: {} ({}) at {:?}",
+                profile.browser, profile.profile, profile.path
+            );
+        }
+        Self {
+            config,
+            profiles,
+            cursors: HashMap::new(),
+        }
+    }
+
+    /// Profiles being read
+    pub fn profiles(&self) -> &[HistoryProfile] {
+        &self.profiles
+    }
+
+    /// Current read positions, to persist between runs
+    pub fn cursors(&self) -> &HashMap<String, i64> {
+        &self.cursors
+    }
+
+    /// Restore a read position saved by a previous run
+    pub fn set_cursor(&mut self, key: &str, last_visit_id: i64) {
+        self.cursors.insert(key.to_string(), last_visit_id);
+    }
+
+    /// Read visits added since the last poll from every profile
+    pub fn poll(&mut self) -> Vec<BrowserVisit> {
+        let backfill_since = Utc::now() - Duration::hours(self.config.backfill_hours as i64);
+        let mut visits = Vec::new();
+
+        for profile in &self.profiles {
+            if !profile.path.exists() {
+                continue;
+            }
+            let key = profile.cursor_key();
+            let after_id = self.cursors.get(&key).copied();
+
+            match read_history_copy(profile, after_id, backfill_since) {
+                Ok((rows, last_id)) => {
+                    if let Some(last_id) = last_id {
+                        self.cursors.insert(key, last_id);
+                    }
+                    visits.extend(rows.into_iter().filter(|v| self.is_allowed(&v.domain)));
+                }
+                Err(e) => warn!("Failed to read {} history: {}", profile.browser, e),
+            }
+        }
+
+        if !visits.is_empty() {
+            debug!("Read {} new browser visits", visits.len());
+        }
+        visits.sort_by_key(|v| v.visited_at);
+        visits
+    }
+
+    /// Apply the allow/deny domain lists
+    fn is_allowed(&self, domain: &str) -> bool {
+        let listed = |list: &[String]| {
+            list.iter()
+                .any(|d| host_matches(domain, d.trim_start_matches("www.")))
+        };
+        if listed(&self.config.deny_domains) {
+            return false;
+        }
+        self.config.allow_domains.is_empty() || listed(&self.config.allow_domains)
+    }
+}
+
+/// Find history databases for installed browsers
+pub fn discover_profiles() -> Vec<HistoryProfile> {
+    let mut profiles = Vec::new();
+
+    if let Some(config_dir) = dirs::config_dir() {
+        for (browser, rel) in CHROMIUM_BROWSERS {
+            let user_data = config_dir.join(rel);
+            for dir in subdirs(&user_data) {
+                let name = dir.file_name().map(|n| n.to_string_lossy().to_string());
+                let is_profile = name
+                    .as_deref()
+                    .is_some_and(|n| n == "Default" || n.starts_with("Profile "));
+                let history = dir.join("History");
+                if is_profile && history.is_file() {
+                    profiles.push(HistoryProfile::new(browser, history));
+                }
+            }
+        }
+    }
+
+    if let Some(firefox_dir) = firefox_profiles_dir() {
+        for dir in subdirs(&firefox_dir) {
+            let places = dir.join("places.sqlite");
+            if places.is_file() {
+                profiles.push(HistoryProfile::new("firefox", places));
+            }
+        }
+    }
+
+    profiles
+}
+
+#[cfg(target_os = "macos")]
+fn firefox_profiles_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("Firefox").join("Profiles"))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn firefox_profiles_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|d| d.join(".mozilla").join("firefox"))
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<_> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// Copy a history database (with its WAL) to a temp dir and read new visits from it
+fn read_history_copy(
+    profile: &HistoryProfile,
+    after_id: Option<i64>,
+    backfill_since: DateTime<Utc>,
+) -> Result<(Vec<BrowserVisit>, Option<i64>), Box<dyn std::error::Error>> {
+    let tmp_dir = std::env::temp_dir().join(format!("siphon-history-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&tmp_dir)?;
+
+    let result = (|| {
+        let file_name = profile
+            .path
+            .file_name()
+            .ok_or("history path has no file name")?;
+        let copy = tmp_dir.join(file_name);
+        std::fs::copy(&profile.path, &copy)?;
+
+        // Recent visits may only be in the write-ahead log
+        let mut wal = profile.path.clone().into_os_string();
+        wal.push("-wal");
+        let wal = PathBuf::from(wal);
+        if wal.is_file() {
+            let mut wal_copy = copy.clone().into_os_string();
+            wal_copy.push("-wal");
+            std::fs::copy(&wal, PathBuf::from(wal_copy))?;
+        }
+
+        let conn = Connection::open(&copy)?;
+        Ok(read_visits(&conn, profile, after_id, backfill_since)?)
+    })();
+
+    let _ = std::fs::remove_dir_all(&tmp_dir);
+    result
+}
+
+/// Read visits with id greater than `after_id`, or since `backfill_since` the first time
+fn read_visits(
+    conn: &Connection,
+    profile: &HistoryProfile,
+    after_id: Option<i64>,
+    backfill_since: DateTime<Utc>,
+) -> rusqlite::Result<(Vec<BrowserVisit>, Option<i64>)> {
+    let since_micros = backfill_since.timestamp_micros();
+    let (sql, min_time) = match profile.format {
+        HistoryFormat::Chromium => (
+            "SELECT v.id, u.url, u.title, v.visit_time, v.visit_duration, v.transition
+             FROM visits v JOIN urls u ON u.id = v.url
+             WHERE v.id > ?1 AND v.visit_time >= ?2
+             ORDER BY v.id LIMIT ?3",
+            since_micros + WEBKIT_EPOCH_OFFSET_MICROS,
+        ),
+        HistoryFormat::Firefox => (
+            "SELECT v.id, p.url, p.title, v.visit_date, NULL, v.visit_type
+             FROM moz_historyvisits v JOIN moz_places p ON p.id = v.place_id
+             WHERE v.id > ?1 AND v.visit_date >= ?2
+             ORDER BY v.id LIMIT ?3",
+            since_micros,
+        ),
+    };
+    // Once we have a cursor the id alone decides what's new
+    let min_time = if after_id.is_some() {
+        i64::MIN
+    } else {
+        min_time
+    };
+
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(
+        params![after_id.unwrap_or(0), min_time, MAX_VISITS_PER_POLL],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        },
+    )?;
+
+    // Basic-auth URLs and tokens in query strings end up in history too
+    let detector = SecretDetector::default();
+    let mut visits = Vec::new();
+    let mut last_id = None;
+    for row in rows {
+        let (id, url, title, time, duration, transition) = row?;
+        last_id = Some(id);
+
+        let (visited_at, transition) = match profile.format {
+            HistoryFormat::Chromium => (
+                micros_to_datetime(time - WEBKIT_EPOCH_OFFSET_MICROS),
+                chromium_transition(transition),
+            ),
+            HistoryFormat::Firefox => (micros_to_datetime(time), firefox_transition(transition)),
+        };
+        // Embedded frames aren't pages the user looked at
+        let Some(transition) = transition else {
+            continue;
+        };
+        let Some(visited_at) = visited_at else {
+            continue;
+        };
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            continue;
+        }
+        let Some(domain) = url_host(&url).map(|h| h.to_lowercase()) else {
+            continue;
+        };
+
+        visits.push(BrowserVisit {
+            browser: profile.browser.clone(),
+            profile: profile.profile.clone(),
+            title: detector.redact(&title.unwrap_or_default()).0,
+            domain,
+            url: detector.redact(&url).0,
+            visited_at,
+            visit_duration_ms: duration.filter(|d| *d > 0).map(|d| (d / 1000) as u64),
+            transition: transition.to_string(),
+        });
+    }
+
+    Ok((visits, last_id))
+}
+
+fn micros_to_datetime(micros: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_micros(micros).single()
+}
+
+/// Chromium's core page transition (low byte of `visits.transition`)
+fn chromium_transition(transition: i64) -> Option<&'static str> {
+    match transition & 0xFF {
+        0 => Some("link"),
+        1 => Some("typed"),
+        2 => Some("bookmark"),
+        3 | 4 => None, // auto/manual subframe
+        5 => Some("generated"),
+        6 => Some("start_page"),
+        7 => Some("form_submit"),
+        8 => Some("reload"),
+        9 | 10 => Some("keyword"),
+        _ => Some("other"),
+    }
+}
+
+/// Firefox `moz_historyvisits.visit_type`
+fn firefox_transition(visit_type: i64) -> Option<&'static str> {
+    match visit_type {
+        1 => Some("link"),
+        2 => Some("typed"),
+        3 => Some("bookmark"),
+        4 => None, // embed
+        5 | 6 => Some("redirect"),
+        7 => Some("download"),
+        8 => Some("link"), // framed link
+        9 => Some("reload"),
+        _ => Some("other"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        dir: PathBuf,
+        conn: Connection,
+        profile: HistoryProfile,
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn fixture(browser: &str, file_name: &str, schema: &str) -> Fixture {
+        let dir = std::env::temp_dir()
+            .join(format!("siphon-history-test-{}", uuid::Uuid::new_v4()))
+            .join("Default");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(file_name);
+        let conn = Connection::open(&path).unwrap();
+        // Browsers keep history in WAL mode; new rows stay in the -wal file while open
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        conn.execute_batch(schema).unwrap();
+        Fixture {
+            dir: dir.parent().unwrap().to_path_buf(),
+            conn,
+            profile: HistoryProfile::new(browser, path),
+        }
+    }
+
+    fn chromium_fixture() -> Fixture {
+        fixture(
+            "chrome",
+            "History",
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT, title TEXT);
+             CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER,
+                                  transition INTEGER, visit_duration INTEGER);",
+        )
+    }
+
+    fn firefox_fixture() -> Fixture {
+        fixture(
+            "firefox",
+            "places.sqlite",
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT);
+             CREATE TABLE moz_historyvisits (id INTEGER PRIMARY KEY, place_id INTEGER,
+                                             visit_date INTEGER, visit_type INTEGER);",
+        )
+    }
+
+    fn add_chromium_visit(
+        f: &Fixture,
+        url: &str,
+        title: &str,
+        at: DateTime<Utc>,
+        transition: i64,
+        duration_ms: i64,
+    ) {
+        f.conn
+            .execute(
+                "INSERT INTO urls (url, title) VALUES (?1, ?2)",
+                params![url, title],
+            )
+            .unwrap();
+        let url_id = f.conn.last_insert_rowid();
+        f.conn
+            .execute(
+                "INSERT INTO visits (url, visit_time, transition, visit_duration)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    url_id,
+                    at.timestamp_micros() + WEBKIT_EPOCH_OFFSET_MICROS,
+                    transition,
+                    duration_ms * 1000
+                ],
+            )
+            .unwrap();
+    }
+
+    fn add_firefox_visit(f: &Fixture, url: &str, title: &str, at: DateTime<Utc>, visit_type: i64) {
+        f.conn
+            .execute(
+                "INSERT INTO moz_places (url, title) VALUES (?1, ?2)",
+                params![url, title],
+            )
+            .unwrap();
+        let place_id = f.conn.last_insert_rowid();
+        f.conn
+            .execute(
+                "INSERT INTO moz_historyvisits (place_id, visit_date, visit_type)
+                 VALUES (?1, ?2, ?3)",
+                params![place_id, at.timestamp_micros(), visit_type],
+            )
+            .unwrap();
+    }
+
+    fn collector(config: BrowserConfig, fixtures: &[&Fixture]) -> BrowserHistoryCollector {
+        BrowserHistoryCollector::with_profiles(
+            config,
+            fixtures.iter().map(|f| f.profile.clone()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_chromium_visits_are_read_incrementally() {
+        let f = chromium_fixture();
+        let now = Utc::now();
+        add_chromium_visit(
+            &f,
+            "https://docs.rs/serde",
+            "serde - Rust",
+            now,
+            0x3000_0001,
+            42_000,
+        );
+        add_chromium_visit(&f, "https://example.com/ad", "", now, 3, 0);
+
+        let mut collector = collector(BrowserConfig::default(), &[&f]);
+        let visits = collector.poll();
+        assert_eq!(visits.len(), 1, "subframe visit should be skipped");
+        let visit = &visits[0];
+        assert_eq!(visit.browser, "chrome");
+        assert_eq!(visit.profile, "Default");
+        assert_eq!(visit.domain, "docs.rs");
+        assert_eq!(visit.title, "serde - Rust");
+        assert_eq!(visit.transition, "typed");
+        assert_eq!(visit.visit_duration_ms, Some(42_000));
+        assert_eq!(visit.visited_at.timestamp_micros(), now.timestamp_micros());
+
+        // Nothing new until another visit is added
+        assert!(collector.poll().is_empty());
+        add_chromium_visit(&f, "https://github.com/", "GitHub", now, 0, 0);
+        let visits = collector.poll();
+        assert_eq!(visits.len(), 1);
+        assert_eq!(visits[0].domain, "github.com");
+    }
+
+    #[test]
+    fn test_first_poll_only_backfills_recent_history() {
+        let f = chromium_fixture();
+        let now = Utc::now();
+        add_chromium_visit(
+            &f,
+            "https://old.example.com/",
+            "Old",
+            now - Duration::days(3),
+            0,
+            0,
+        );
+        add_chromium_visit(&f, "https://new.example.com/", "New", now, 0, 0);
+
+        let visits = collector(BrowserConfig::default(), &[&f]).poll();
+        assert_eq!(visits.len(), 1);
+        assert_eq!(visits[0].domain, "new.example.com");
+    }
+
+    #[test]
+    fn test_saved_cursor_resumes_after_restart() {
+        let f = firefox_fixture();
+        let now = Utc::now();
+        add_firefox_visit(&f, "https://one.example.com/", "One", now, 1);
+
+        let mut first = collector(BrowserConfig::default(), &[&f]);
+        assert_eq!(first.poll().len(), 1);
+        let (key, cursor) = first.cursors().iter().next().unwrap();
+
+        add_firefox_visit(&f, "https://two.example.com/", "Two", now, 2);
+        let mut second = collector(BrowserConfig::default(), &[&f]);
+        second.set_cursor(key, *cursor);
+        let visits = second.poll();
+        assert_eq!(visits.len(), 1);
+        assert_eq!(visits[0].domain, "two.example.com");
+        assert_eq!(visits[0].transition, "typed");
+        assert_eq!(visits[0].visit_duration_ms, None);
+    }
+
+    #[test]
+    fn test_firefox_skips_embeds_and_non_web_urls() {
+        let f = firefox_fixture();
+        let now = Utc::now();
+        add_firefox_visit(&f, "https://developer.mozilla.org/", "MDN", now, 1);
+        add_firefox_visit(&f, "https://tracker.example.com/pixel", "", now, 4);
+        add_firefox_visit(&f, "about:preferences", "Settings", now, 2);
+        add_firefox_visit(&f, "file:///home/me/notes.html", "notes", now, 2);
+
+        let visits = collector(BrowserConfig::default(), &[&f]).poll();
+        assert_eq!(visits.len(), 1);
+        assert_eq!(visits[0].domain, "developer.mozilla.org");
+    }
+
+    #[test]
+    fn test_domain_allow_and_deny_lists() {
+        let chrome = chromium_fixture();
+        let firefox = firefox_fixture();
+        let now = Utc::now();
+        add_chromium_visit(&chrome, "https://github.com/a", "a", now, 0, 0);
+        add_chromium_visit(&chrome, "https://gist.github.com/b", "b", now, 0, 0);
+        add_chromium_visit(&chrome, "https://mybank.com/", "bank", now, 0, 0);
+        add_firefox_visit(&firefox, "https://www.github.com/c", "c", now, 1);
+        add_firefox_visit(&firefox, "https://notgithub.com/", "d", now, 1);
+
+        let config = BrowserConfig {
+            allow_domains: vec!["github.com".to_string(), "mybank.com".to_string()],
+            deny_domains: vec!["gist.github.com".to_string(), "mybank.com".to_string()],
+            ..Default::default()
+        };
+        let visits = collector(config, &[&chrome, &firefox]).poll();
+        let domains: Vec<_> = visits.iter().map(|v| v.domain.as_str()).collect();
+        assert_eq!(domains.len(), 2);
+        assert!(domains.contains(&"github.com"));
+        assert!(domains.contains(&"www.github.com"));
+    }
+
+    #[test]
+    fn test_missing_profile_is_ignored() {
+        let profile = HistoryProfile::new("chrome", PathBuf::from("/nonexistent/Default/History"));
+        let mut collector =
+            BrowserHistoryCollector::with_profiles(BrowserConfig::default(), vec![profile]);
+        assert!(collector.poll().is_empty());
+    }
+}
@@ -0,0 +1,750 @@
+//! Calendar entries from local ICS files
+//!
+//! Reads `.ics` exports (or directories of them) listed under `[calendar]` so
+//! detected meetings can be matched to what was scheduled. Only timed events
+//! are kept: all-day, cancelled and free (`TRANSP:TRANSPARENT`) events aren't
+//! meetings. Recurring events are expanded a few days either side of now.
+//!
+//! Times with a `TZID` use the IANA zone of that name; floating times and
+//! zones chrono-tz doesn't know (such as Outlook's Windows zone names) are
+//! taken as local time. A recurring event whose rule can't be expanded keeps
+//! only its first occurrence.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+use tracing::{debug, info, warn};
+
+use crate::config::expand_home;
+use crate::meeting::MeetingPlatform;
+
+/// How often to check the files for changes
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// Recurring events are expanded this far into the past...
+const EXPAND_BEHIND: Duration = Duration::days(1);
+/// ...and this far into the future
+const EXPAND_AHEAD: Duration = Duration::days(7);
+/// Upper bound on occurrences of a single recurring event in the window
+const MAX_OCCURRENCES: u16 = 500;
+
+/// The `[calendar]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// `.ics` files, or directories containing them
+    pub paths: Vec<PathBuf>,
+    /// How early or late a detected meeting can start and still match an entry
+    pub match_slack_minutes: u32,
+    /// Record scheduled meetings that passed without a detected meeting
+    pub record_missed: bool,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            match_slack_minutes: 10,
+            record_missed: true,
+        }
+    }
+}
+
+/// A single (occurrence of a) scheduled event
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEntry {
+    pub uid: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub attendee_count: u32,
+    /// Location, which often holds the call link
+    pub location: Option<String>,
+    /// Conference link from `URL`, `LOCATION` or `DESCRIPTION`
+    pub url: Option<String>,
+}
+
+impl CalendarEntry {
+    /// Scheduled length in minutes
+    pub fn duration_minutes(&self) -> u32 {
+        (self.end - self.start).num_minutes().max(0) as u32
+    }
+
+    /// Whether `[start, end)` overlaps this entry
+    pub fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        start < self.end && end > self.start
+    }
+
+    /// Meeting platform, guessed from the conference link
+    pub fn platform(&self) -> MeetingPlatform {
+        self.url
+            .as_deref()
+            .and_then(MeetingPlatform::from_url)
+            .unwrap_or(MeetingPlatform::Unknown)
+    }
+}
+
+/// Entries read from the configured ICS files, re-read when they change
+pub struct Calendar {
+    config: CalendarConfig,
+    entries: Vec<CalendarEntry>,
+    /// Files and modification times the entries were read from
+    sources: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Centre of the window recurring events were expanded around
+    expanded_at: DateTime<Utc>,
+    last_check: Option<Instant>,
+}
+
+impl Calendar {
+    /// Load the configured files, or None when no paths are configured
+    pub fn from_config(config: &CalendarConfig) -> Option<Self> {
+        if config.paths.is_empty() {
+            return None;
+        }
+        let mut calendar = Self {
+            config: config.clone(),
+            entries: Vec::new(),
+            sources: Vec::new(),
+            expanded_at: Utc::now(),
+            last_check: None,
+        };
+        calendar.reload(Utc::now());
+        info!(
+            "Calendar loaded: {} entries from {} file(s)",
+            calendar.entries.len(),
+            calendar.sources.len()
+        );
+        Some(calendar)
+    }
+
+    /// Build a calendar from already-parsed entries
+    pub fn from_entries(config: CalendarConfig, entries: Vec<CalendarEntry>) -> Self {
+        Self {
+            config,
+            entries,
+            sources: Vec::new(),
+            expanded_at: Utc::now(),
+            last_check: Some(Instant::now()),
+        }
+    }
+
+    pub fn config(&self) -> &CalendarConfig {
+        &self.config
+    }
+
+    pub fn entries(&self) -> &[CalendarEntry] {
+        &self.entries
+    }
+
+    /// Re-read the files if any changed, or if recurring events need
+    /// expanding around a later time. Checks at most once a minute.
+    pub fn refresh(&mut self, now: DateTime<Utc>) {
+        if self
+            .last_check
+            .is_some_and(|checked| checked.elapsed() < REFRESH_INTERVAL)
+        {
+            return;
+        }
+        self.last_check = Some(Instant::now());
+
+        let changed = find_ics_files(&self.config.paths)
+            .into_iter()
+            .map(|path| {
+                let modified = modified_time(&path);
+                (path, modified)
+            })
+            .collect::<Vec<_>>()
+            != self.sources;
+        if changed || now - self.expanded_at > Duration::hours(12) {
+            self.reload(now);
+            debug!("Calendar reloaded: {} entries", self.entries.len());
+        }
+    }
+
+    fn reload(&mut self, now: DateTime<Utc>) {
+        let from = now - EXPAND_BEHIND;
+        let to = now + EXPAND_AHEAD;
+        let mut entries = Vec::new();
+        let mut sources = Vec::new();
+        for path in find_ics_files(&self.config.paths) {
+            let modified = modified_time(&path);
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => entries.extend(parse_ics(&contents, from, to)),
+                Err(e) => warn!("Failed to read calendar {:?}: {}", path, e),
+            }
+            sources.push((path, modified));
+        }
+        entries.sort_by_key(|e| e.start);
+        entries.dedup_by(|a, b| a.uid == b.uid && a.start == b.start);
+
+        self.entries = entries;
+        self.sources = sources;
+        self.expanded_at = now;
+    }
+
+    /// The entry a meeting detected at `at` belongs to, if any
+    ///
+    /// Among overlapping entries the one scheduled to start closest to `at` wins.
+    pub fn find_match(&self, at: DateTime<Utc>) -> Option<&CalendarEntry> {
+        let slack = Duration::minutes(self.config.match_slack_minutes as i64);
+        self.entries
+            .iter()
+            .filter(|e| e.start - slack <= at && at < e.end.max(e.start + slack))
+            .min_by_key(|e| (e.start - at).num_seconds().abs())
+    }
+
+    /// Entries that ended in `(from, to]`
+    pub fn ended_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> impl Iterator<Item = &CalendarEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.end > from && e.end <= to)
+    }
+}
+
+/// `.ics` files at the given paths, looking one level into directories
+fn find_ics_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = expand_home(path);
+        if path.is_dir() {
+            let Ok(dir) = std::fs::read_dir(&path) else {
+                continue;
+            };
+            let mut found: Vec<_> = dir
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+                })
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else if path.is_file() {
+            files.push(path);
+        } else {
+            debug!("Calendar path {:?} not found", path);
+        }
+    }
+    files
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// A content line: `NAME;PARAM=value:VALUE`
+#[derive(Debug, Clone)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+    /// The whole unfolded line, handed to the recurrence parser as is
+    raw: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first ':' outside a quoted parameter
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(i),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+        let mut parts = split_unquoted(head, ';').into_iter();
+        let name = parts.next()?.to_ascii_uppercase();
+        let params = parts
+            .filter_map(|p| {
+                let (key, value) = p.split_once('=')?;
+                Some((
+                    key.to_ascii_uppercase(),
+                    value.trim_matches('"').to_string(),
+                ))
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            params,
+            value: value.to_string(),
+            raw: line.to_string(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn text(&self) -> String {
+        unescape_text(&self.value)
+    }
+}
+
+fn split_unquoted(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == separator && !in_quotes {
+            parts.push(&s[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// A VEVENT's properties, before expansion
+#[derive(Debug, Default)]
+struct RawEvent {
+    uid: Option<String>,
+    summary: Option<String>,
+    dtstart: Option<Property>,
+    dtend: Option<Property>,
+    duration: Option<String>,
+    recurrence: Vec<Property>,
+    recurrence_id: Option<Property>,
+    status: Option<String>,
+    transparent: bool,
+    attendees: u32,
+    location: Option<String>,
+    description: Option<String>,
+    url: Option<String>,
+}
+
+/// Parse an ICS document into entries overlapping `[from, to)`
+pub fn parse_ics(contents: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<CalendarEntry> {
+    let events = read_events(contents);
+
+    // Instances moved or edited by a RECURRENCE-ID override replace the
+    // generated occurrence at that time
+    let overridden: HashSet<(String, DateTime<Utc>)> = events
+        .iter()
+        .filter_map(|e| {
+            let id = e.recurrence_id.as_ref()?;
+            Some((e.uid.clone()?, parse_datetime(id)?))
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    for event in &events {
+        if event.status.as_deref() == Some("CANCELLED") || event.transparent {
+            continue;
+        }
+        let Some(dtstart) = &event.dtstart else {
+            continue;
+        };
+        // All-day events have no time and aren't meetings
+        let Some(start) = parse_datetime(dtstart) else {
+            continue;
+        };
+        let length = event_length(event, start);
+        let uid = event.uid.clone().unwrap_or_default();
+
+        let starts = if event.recurrence.is_empty() || event.recurrence_id.is_some() {
+            vec![start]
+        } else {
+            expand_recurrence(dtstart, &event.recurrence, from - length, to).unwrap_or_else(|e| {
+                debug!("Can't expand recurring event {:?}: {}", event.summary, e);
+                vec![start]
+            })
+        };
+
+        for start in starts {
+            let end = start + length;
+            if end < from || start >= to {
+                continue;
+            }
+            if event.recurrence_id.is_none() && overridden.contains(&(uid.clone(), start)) {
+                continue;
+            }
+            entries.push(CalendarEntry {
+                uid: uid.clone(),
+                title: event.summary.clone().unwrap_or_default(),
+                start,
+                end,
+                attendee_count: event.attendees,
+                location: event.location.clone(),
+                url: conference_url(event),
+            });
+        }
+    }
+    entries.sort_by_key(|e| e.start);
+    entries
+}
+
+fn read_events(contents: &str) -> Vec<RawEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    // Depth of components nested inside the VEVENT (VALARM)
+    let mut nested = 0;
+
+    for line in unfold(contents) {
+        let Some(prop) = Property::parse(&line) else {
+            continue;
+        };
+        let value = prop.value.trim().to_ascii_uppercase();
+        match prop.name.as_str() {
+            "BEGIN" if value == "VEVENT" => {
+                current = Some(RawEvent::default());
+                nested = 0;
+            }
+            "END" if value == "VEVENT" => events.extend(current.take()),
+            "BEGIN" if current.is_some() => nested += 1,
+            "END" if current.is_some() => nested -= 1,
+            _ => {
+                let Some(event) = current.as_mut() else {
+                    continue;
+                };
+                if nested > 0 {
+                    continue;
+                }
+                match prop.name.as_str() {
+                    "UID" => event.uid = Some(prop.value.trim().to_string()),
+                    "SUMMARY" => event.summary = Some(prop.text()),
+                    "DTSTART" => event.dtstart = Some(prop),
+                    "DTEND" => event.dtend = Some(prop),
+                    "DURATION" => event.duration = Some(prop.value.trim().to_string()),
+                    "RRULE" | "RDATE" | "EXDATE" => event.recurrence.push(prop),
+                    "RECURRENCE-ID" => event.recurrence_id = Some(prop),
+                    "STATUS" => event.status = Some(value),
+                    "TRANSP" => event.transparent = value == "TRANSPARENT",
+                    "ATTENDEE" => event.attendees += 1,
+                    "LOCATION" => event.location = Some(prop.text()),
+                    "DESCRIPTION" => event.description = Some(prop.text()),
+                    "URL" => event.url = Some(prop.value.trim().to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+    events
+}
+
+/// Length from DTEND or DURATION; events with neither take no time
+fn event_length(event: &RawEvent, start: DateTime<Utc>) -> Duration {
+    if let Some(end) = event.dtend.as_ref().and_then(parse_datetime) {
+        return (end - start).max(Duration::zero());
+    }
+    event
+        .duration
+        .as_deref()
+        .and_then(parse_duration)
+        .unwrap_or_else(Duration::zero)
+}
+
+/// Start times of a recurring event's occurrences in `[from, to)`
+fn expand_recurrence(
+    dtstart: &Property,
+    recurrence: &[Property],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<DateTime<Utc>>, rrule::RRuleError> {
+    let mut spec = dtstart.raw.clone();
+    for prop in recurrence {
+        spec.push('\n');
+        spec.push_str(&prop.raw);
+    }
+    let set: rrule::RRuleSet = spec.parse()?;
+    let result = set
+        .after(from.with_timezone(&rrule::Tz::UTC))
+        .before(to.with_timezone(&rrule::Tz::UTC))
+        .all(MAX_OCCURRENCES);
+    Ok(result
+        .dates
+        .into_iter()
+        .map(|d| d.with_timezone(&Utc))
+        .collect())
+}
+
+/// Parse a DATE-TIME property; None for DATE values (all-day)
+fn parse_datetime(prop: &Property) -> Option<DateTime<Utc>> {
+    if prop.param("VALUE") == Some("DATE") {
+        return None;
+    }
+    let value = prop.value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(naive.and_utc());
+    }
+    if NaiveDate::parse_from_str(value, "%Y%m%d").is_ok() {
+        return None;
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = prop
+        .param("TZID")
+        .and_then(|tzid| tzid.parse::<chrono_tz::Tz>().ok());
+    match zone {
+        Some(zone) => zone
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+/// Parse an ISO 8601 duration such as `PT30M`, `PT1H30M` or `P1DT2H`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let rest = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match c {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    _ => Duration::seconds(n),
+                };
+            }
+            _ => return None,
+        }
+    }
+    number.is_empty().then_some(total)
+}
+
+/// First link that looks like a call, from URL, LOCATION or DESCRIPTION
+fn conference_url(event: &RawEvent) -> Option<String> {
+    [&event.url, &event.location, &event.description]
+        .into_iter()
+        .flatten()
+        .flat_map(|text| text.split_whitespace())
+        .map(|word| word.trim_matches(|c: char| matches!(c, '<' | '>' | '(' | ')' | '"' | ',')))
+        .find(|word| word.starts_with("https://") && MeetingPlatform::from_url(word).is_some())
+        .map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDUP: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:standup@example.com\r
+SUMMARY:Team standup\r
+DTSTART;TZID=Europe/Berlin:20261012T093000\r
+DTEND;TZID=Europe/Berlin:20261012T094500\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r
+EXDATE;TZID=Europe/Berlin:20261014T093000\r
+ATTENDEE;CN=Ana:mailto:ana@example.com\r
+ATTENDEE;CN=\"Bo, Jr\":mailto:bo@example.com\r
+ATTENDEE:mailto:cy@example.com\r
+LOCATION:https://meet.google.com/abc-defg-hij\r
+BEGIN:VALARM\r
+ACTION:DISPLAY\r
+DESCRIPTION:Reminder\r
+TRIGGER:-PT10M\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup@example.com\r
+RECURRENCE-ID;TZID=Europe/Berlin:20261016T093000\r
+SUMMARY:Team standup (moved)\r
+DTSTART;TZID=Europe/Berlin:20261016T110000\r
+DTEND;TZID=Europe/Berlin:20261016T111500\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:review@example.com\r
+SUMMARY:Design review\\, Q4\r
+DESCRIPTION:Join: https://us02web.zoom.us/j/123456\\nAgenda in doc\r
+DTSTART:20261013T140000Z\r
+DURATION:PT1H\r
+ATTENDEE:mailto:ana@example.com\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:cancelled@example.com\r
+SUMMARY:Cancelled sync\r
+STATUS:CANCELLED\r
+DTSTART:20261013T150000Z\r
+DTEND:20261013T153000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday@example.com\r
+SUMMARY:Holiday\r
+DTSTART;VALUE=DATE:20261015\r
+DTEND;VALUE=DATE:20261016\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:lunch@example.com\r
+SUMMARY:Lunch\r
+TRANSP:TRANSPARENT\r
+DTSTART:20261013T110000Z\r
+DTEND:20261013T120000Z\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn week() -> Vec<CalendarEntry> {
+        parse_ics(
+            STANDUP,
+            utc("2026-10-12T00:00:00Z"),
+            utc("2026-10-19T00:00:00Z"),
+        )
+    }
+
+    #[test]
+    fn test_parse_single_events() {
+        let entries = week();
+        let review = entries
+            .iter()
+            .find(|e| e.uid == "review@example.com")
+            .unwrap();
+        assert_eq!(review.title, "Design review, Q4");
+        assert_eq!(review.start, utc("2026-10-13T14:00:00Z"));
+        assert_eq!(review.duration_minutes(), 60);
+        assert_eq!(review.attendee_count, 1);
+        assert_eq!(
+            review.url.as_deref(),
+            Some("https://us02web.zoom.us/j/123456")
+        );
+        assert_eq!(review.platform(), MeetingPlatform::Zoom);
+
+        // Cancelled, all-day and free events are dropped
+        assert!(!entries
+            .iter()
+            .any(|e| ["Cancelled sync", "Holiday", "Lunch"].contains(&e.title.as_str())));
+    }
+
+    #[test]
+    fn test_expand_recurring_event() {
+        let standups: Vec<_> = week()
+            .into_iter()
+            .filter(|e| e.uid == "standup@example.com")
+            .collect();
+
+        // Monday, Wednesday excluded, Friday moved by an override
+        let starts: Vec<_> = standups.iter().map(|e| e.start).collect();
+        assert_eq!(
+            starts,
+            vec![utc("2026-10-12T07:30:00Z"), utc("2026-10-16T09:00:00Z")]
+        );
+        assert_eq!(standups[0].title, "Team standup");
+        assert_eq!(standups[0].attendee_count, 3);
+        assert_eq!(standups[0].duration_minutes(), 15);
+        assert_eq!(standups[0].platform(), MeetingPlatform::GoogleMeet);
+        assert_eq!(standups[1].title, "Team standup (moved)");
+
+        // Past the DST change the wall-clock time stays 09:30 in Berlin
+        let later = parse_ics(
+            STANDUP,
+            utc("2026-10-26T00:00:00Z"),
+            utc("2026-10-27T00:00:00Z"),
+        );
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].start, utc("2026-10-26T08:30:00Z"));
+    }
+
+    #[test]
+    fn test_find_match_and_ended_between() {
+        let calendar = Calendar::from_entries(CalendarConfig::default(), week());
+
+        // Joining a few minutes early or late still matches
+        let early = calendar.find_match(utc("2026-10-12T07:25:00Z")).unwrap();
+        assert_eq!(early.title, "Team standup");
+        let late = calendar.find_match(utc("2026-10-13T14:20:00Z")).unwrap();
+        assert_eq!(late.uid, "review@example.com");
+        assert!(calendar.find_match(utc("2026-10-13T16:00:00Z")).is_none());
+
+        let ended: Vec<_> = calendar
+            .ended_between(utc("2026-10-12T00:00:00Z"), utc("2026-10-13T23:00:00Z"))
+            .map(|e| e.uid.as_str())
+            .collect();
+        assert_eq!(ended, vec!["standup@example.com", "review@example.com"]);
+    }
+
+    #[test]
+    fn test_parse_helpers() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("1H"), None);
+
+        assert_eq!(
+            unfold("SUMMARY:Long\r\n  title\r\nUID:x"),
+            vec!["SUMMARY:Long title", "UID:x"]
+        );
+
+        let prop = Property::parse("ATTENDEE;CN=\"Doe: Jane\";ROLE=CHAIR:mailto:jane@example.com")
+            .unwrap();
+        assert_eq!(prop.param("CN"), Some("Doe: Jane"));
+        assert_eq!(prop.value, "mailto:jane@example.com");
+    }
+
+    #[test]
+    fn test_load_directory() {
+        let dir = std::env::temp_dir().join(format!("siphon-calendar-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("work.ics"), STANDUP).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a calendar").unwrap();
+
+        let config = CalendarConfig {
+            paths: vec![dir.clone()],
+            ..Default::default()
+        };
+        let calendar = Calendar::from_config(&config).unwrap();
+        assert_eq!(calendar.sources.len(), 1);
+        assert!(Calendar::from_config(&CalendarConfig::default()).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
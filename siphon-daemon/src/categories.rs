@@ -0,0 +1,609 @@
+//! Activity categorisation rules
+//!
+//! Maps an app (name, bundle id, window title, URL) to an [`ActivityCategory`].
+//! Rules are checked in order and the first match wins: user rules from the
+//! `[categories]` config section come first, then the built-in rules below.
+//!
+//! App names are matched as whole words, so "Arc" does not match
+//! "Archive Utility" and "Code" does not match "Xcode Helper".
+
+use regex::Regex;
+use serde::Deserialize;
+use std::sync::LazyLock;
+use tracing::warn;
+
+use crate::idle::ActivityCategory;
+use crate::window::{FocusSpan, WindowInfo};
+
+/// Built-in rules, shared by everything that doesn't have a configured rule set
+static BUILTIN_RULES: LazyLock<CategoryRules> = LazyLock::new(CategoryRules::builtin);
+
+/// Browsers, used to scope title-based site rules
+const BROWSER_APPS: &[&str] = &[
+    "Google Chrome",
+    "Chrome",
+    "Chromium",
+    "Safari",
+    "Firefox",
+    "Arc",
+    "Microsoft Edge",
+    "Brave Browser",
+    "Opera",
+    "Vivaldi",
+];
+
+/// A single categorisation rule as written in the config file
+///
+/// ```toml
+/// [[categories.rules]]
+/// category = "writing"
+/// app = "Obsidian"
+///
+/// [[categories.rules]]
+/// category = "entertainment"
+/// domain = "news.ycombinator.com"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct CategoryRuleConfig {
+    /// Category to assign (built-in name or any custom name)
+    pub category: String,
+    /// App name, matched as whole words, case-insensitive
+    #[serde(default)]
+    pub app: Option<String>,
+    /// Exact bundle identifier (macOS)
+    #[serde(default)]
+    pub bundle_id: Option<String>,
+    /// Regex matched against the window title
+    #[serde(default)]
+    pub title: Option<String>,
+    /// URL host; also matches subdomains
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+/// The `[categories]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CategoriesConfig {
+    /// Extra rules, checked before the built-in ones
+    pub rules: Vec<CategoryRuleConfig>,
+    /// Categories that count as productive time for focus scoring
+    pub productive: Vec<String>,
+}
+
+impl Default for CategoriesConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            productive: vec![
+                "coding".to_string(),
+                "terminal".to_string(),
+                "creative".to_string(),
+                "research".to_string(),
+            ],
+        }
+    }
+}
+
+/// What we know about an activity when categorising it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActivityContext<'a> {
+    pub app_name: &'a str,
+    pub bundle_id: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub url: Option<&'a str>,
+}
+
+impl<'a> From<&'a WindowInfo> for ActivityContext<'a> {
+    fn from(window: &'a WindowInfo) -> Self {
+        Self {
+            app_name: &window.app_name,
+            bundle_id: window.bundle_id.as_deref(),
+            title: Some(&window.title),
+            url: window.url.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a FocusSpan> for ActivityContext<'a> {
+    fn from(span: &'a FocusSpan) -> Self {
+        Self {
+            app_name: &span.app_name,
+            bundle_id: span.bundle_id.as_deref(),
+            title: Some(&span.title),
+            url: span.url.as_deref(),
+        }
+    }
+}
+
+/// A compiled rule; every matcher that is set must match
+#[derive(Debug, Clone)]
+pub struct CategoryRule {
+    category: ActivityCategory,
+    apps: Vec<Regex>,
+    bundle_ids: Vec<String>,
+    title: Option<Regex>,
+    domains: Vec<String>,
+}
+
+impl CategoryRule {
+    fn new(category: ActivityCategory) -> Self {
+        Self {
+            category,
+            apps: Vec::new(),
+            bundle_ids: Vec::new(),
+            title: None,
+            domains: Vec::new(),
+        }
+    }
+
+    fn apps(mut self, names: &[&str]) -> Self {
+        self.apps = names.iter().map(|n| app_regex(n)).collect();
+        self
+    }
+
+    fn title(mut self, pattern: &str) -> Self {
+        self.title = Some(Regex::new(pattern).expect("Invalid built-in title pattern"));
+        self
+    }
+
+    fn domains(mut self, domains: &[&str]) -> Self {
+        self.domains = domains.iter().map(|d| d.to_lowercase()).collect();
+        self
+    }
+
+    /// Compile a rule from config, rejecting empty rules and bad regexes
+    fn from_config(config: &CategoryRuleConfig) -> Result<Self, String> {
+        let mut rule = Self::new(ActivityCategory::from(config.category.clone()));
+
+        if let Some(ref app) = config.app {
+            rule.apps.push(app_regex(app));
+        }
+        if let Some(ref bundle_id) = config.bundle_id {
+            rule.bundle_ids.push(bundle_id.clone());
+        }
+        if let Some(ref title) = config.title {
+            rule.title = Some(
+                Regex::new(title).map_err(|e| format!("invalid title regex '{}': {}", title, e))?,
+            );
+        }
+        if let Some(ref domain) = config.domain {
+            rule.domains
+                .push(domain.trim_start_matches("www.").to_lowercase());
+        }
+
+        if rule.apps.is_empty()
+            && rule.bundle_ids.is_empty()
+            && rule.title.is_none()
+            && rule.domains.is_empty()
+        {
+            return Err(format!(
+                "rule for '{}' needs at least one of app, bundle_id, title or domain",
+                config.category
+            ));
+        }
+
+        Ok(rule)
+    }
+
+    fn matches(&self, ctx: &ActivityContext) -> bool {
+        if !self.apps.is_empty() && !self.apps.iter().any(|re| re.is_match(ctx.app_name)) {
+            return false;
+        }
+        if !self.bundle_ids.is_empty()
+            && !ctx
+                .bundle_id
+                .is_some_and(|id| self.bundle_ids.iter().any(|b| b == id))
+        {
+            return false;
+        }
+        if let Some(ref title) = self.title {
+            if !ctx.title.is_some_and(|t| title.is_match(t)) {
+                return false;
+            }
+        }
+        if !self.domains.is_empty() {
+            let host = match ctx.url.and_then(url_host) {
+                Some(h) => h.to_lowercase(),
+                None => return false,
+            };
+            if !self.domains.iter().any(|d| host_matches(&host, d)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Whole-word, case-insensitive app name matcher
+pub(crate) fn app_regex(name: &str) -> Regex {
+    Regex::new(&format!(r"(?i)(?:^|\W){}(?:$|\W)", regex::escape(name)))
+        .expect("Escaped app name is always a valid regex")
+}
+
+/// Extract the host from a URL like `https://user@www.example.com:8080/path`
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = host_port.split(':').next()?;
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains (both lowercase)
+pub fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// An ordered set of categorisation rules
+#[derive(Debug, Clone)]
+pub struct CategoryRules {
+    rules: Vec<CategoryRule>,
+    productive: Vec<ActivityCategory>,
+}
+
+impl Default for CategoryRules {
+    fn default() -> Self {
+        BUILTIN_RULES.clone()
+    }
+}
+
+impl CategoryRules {
+    /// Build user rules from config followed by the built-in rules
+    pub fn from_config(config: &CategoriesConfig) -> Self {
+        let mut rules = Vec::with_capacity(config.rules.len());
+        for rule_config in &config.rules {
+            match CategoryRule::from_config(rule_config) {
+                Ok(rule) => rules.push(rule),
+                Err(e) => warn!("Ignoring category rule: {}", e),
+            }
+        }
+        rules.extend(BUILTIN_RULES.rules.iter().cloned());
+
+        Self {
+            rules,
+            productive: config
+                .productive
+                .iter()
+                .map(|c| ActivityCategory::from(c.clone()))
+                .collect(),
+        }
+    }
+
+    /// The built-in rules
+    pub fn builtin() -> Self {
+        use ActivityCategory::*;
+
+        let rules = vec![
+            // Sites, checked first so a browser on YouTube isn't "research"
+            CategoryRule::new(Meeting).domains(&[
+                "meet.google.com",
+                "zoom.us",
+                "teams.microsoft.com",
+                "whereby.com",
+            ]),
+            CategoryRule::new(Communication).domains(&[
+                "mail.google.com",
+                "outlook.office.com",
+                "outlook.live.com",
+                "app.slack.com",
+                "discord.com",
+            ]),
+            CategoryRule::new(Entertainment).domains(&[
+                "youtube.com",
+                "netflix.com",
+                "twitch.tv",
+                "reddit.com",
+                "twitter.com",
+                "x.com",
+                "instagram.com",
+                "facebook.com",
+                "tiktok.com",
+            ]),
+            CategoryRule::new(Coding).domains(&[
+                "github.com",
+                "gitlab.com",
+                "bitbucket.org",
+                "stackoverflow.com",
+                "docs.rs",
+                "crates.io",
+            ]),
+            CategoryRule::new(Creative).domains(&["figma.com", "canva.com"]),
+            // Browsers don't expose URLs everywhere (e.g. Linux), so fall back to titles
+            CategoryRule::new(Meeting)
+                .apps(BROWSER_APPS)
+                .title(r"(?i)(^Meet - |Google Meet|Zoom Meeting)"),
+            CategoryRule::new(Entertainment)
+                .apps(BROWSER_APPS)
+                .title(r"(?i)(\bYouTube\b|\bNetflix\b|\bTwitch\b|\breddit\b)"),
+            // Native apps
+            CategoryRule::new(Meeting).apps(&[
+                "zoom.us",
+                "Zoom",
+                "FaceTime",
+                "Webex",
+                "Cisco Webex Meetings",
+                "Around",
+            ]),
+            CategoryRule::new(Communication).apps(&[
+                "Slack",
+                "Microsoft Teams",
+                "Teams",
+                "Mail",
+                "Microsoft Outlook",
+                "Outlook",
+                "Thunderbird",
+                "Messages",
+                "Discord",
+                "Telegram",
+                "Signal",
+                "WhatsApp",
+            ]),
+            CategoryRule::new(Coding).apps(&[
+                "Code",
+                "Visual Studio Code",
+                "VSCodium",
+                "Cursor",
+                "Zed",
+                "Xcode",
+                "IntelliJ IDEA",
+                "PyCharm",
+                "WebStorm",
+                "GoLand",
+                "RustRover",
+                "CLion",
+                "Android Studio",
+                "Sublime Text",
+                "Atom",
+                "Nova",
+                "Vim",
+                "MacVim",
+                "Neovim",
+                "neovide",
+                "Emacs",
+            ]),
+            CategoryRule::new(Terminal).apps(&[
+                "Terminal",
+                "iTerm",
+                "iTerm2",
+                "Warp",
+                "Alacritty",
+                "kitty",
+                "WezTerm",
+                "Ghostty",
+                "foot",
+                "Konsole",
+                "gnome-terminal",
+                "gnome-terminal-server",
+                "Hyper",
+                "Tabby",
+            ]),
+            CategoryRule::new(Creative).apps(&[
+                "Photoshop",
+                "Illustrator",
+                "Figma",
+                "Sketch",
+                "Blender",
+                "Premiere",
+                "Final Cut Pro",
+                "DaVinci Resolve",
+                "Affinity Designer",
+                "Affinity Photo",
+                "GIMP",
+                "Inkscape",
+                "Krita",
+            ]),
+            CategoryRule::new(Research).apps(BROWSER_APPS),
+        ];
+
+        Self {
+            rules,
+            productive: CategoriesConfig::default()
+                .productive
+                .into_iter()
+                .map(ActivityCategory::from)
+                .collect(),
+        }
+    }
+
+    /// Shared built-in rules (no config)
+    pub fn builtin_ref() -> &'static CategoryRules {
+        &BUILTIN_RULES
+    }
+
+    /// Categorise an activity; unmatched activity is `Other`
+    pub fn categorize(&self, ctx: &ActivityContext) -> ActivityCategory {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(ctx))
+            .map(|rule| rule.category.clone())
+            .unwrap_or(ActivityCategory::Other)
+    }
+
+    /// Categorise from the app name alone
+    pub fn categorize_app(&self, app_name: &str) -> ActivityCategory {
+        self.categorize(&ActivityContext {
+            app_name,
+            ..Default::default()
+        })
+    }
+
+    /// Whether time in this category counts as productive
+    pub fn is_productive(&self, category: &ActivityCategory) -> bool {
+        self.productive.contains(category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx<'a>(app_name: &'a str, title: &'a str, url: Option<&'a str>) -> ActivityContext<'a> {
+        ActivityContext {
+            app_name,
+            bundle_id: None,
+            title: Some(title),
+            url,
+        }
+    }
+
+    #[test]
+    fn test_app_names_match_whole_words() {
+        let rules = CategoryRules::builtin();
+        assert_eq!(rules.categorize_app("Arc"), ActivityCategory::Research);
+        assert_eq!(
+            rules.categorize_app("Archive Utility"),
+            ActivityCategory::Other
+        );
+        assert_eq!(
+            rules.categorize_app("Visual Studio Code"),
+            ActivityCategory::Coding
+        );
+        assert_eq!(rules.categorize_app("Codecademy"), ActivityCategory::Other);
+        assert_eq!(rules.categorize_app("Mailspring"), ActivityCategory::Other);
+    }
+
+    #[test]
+    fn test_browser_on_video_site_is_not_research() {
+        let rules = CategoryRules::builtin();
+        assert_eq!(
+            rules.categorize(&ctx(
+                "Google Chrome",
+                "Lo-fi beats",
+                Some("https://www.youtube.com/watch?v=abc")
+            )),
+            ActivityCategory::Entertainment
+        );
+        assert_eq!(
+            rules.categorize(&ctx(
+                "Firefox",
+                "Cat videos - YouTube — Mozilla Firefox",
+                None
+            )),
+            ActivityCategory::Entertainment
+        );
+        assert_eq!(
+            rules.categorize(&ctx(
+                "Safari",
+                "siphon",
+                Some("https://github.com/siphon-dev/siphon")
+            )),
+            ActivityCategory::Coding
+        );
+        assert_eq!(
+            rules.categorize(&ctx(
+                "Safari",
+                "MDN Web Docs",
+                Some("https://developer.mozilla.org")
+            )),
+            ActivityCategory::Research
+        );
+    }
+
+    #[test]
+    fn test_config_rules_take_precedence_and_allow_custom_categories() {
+        let config = CategoriesConfig {
+            rules: vec![
+                CategoryRuleConfig {
+                    category: "writing".to_string(),
+                    app: Some("Obsidian".to_string()),
+                    bundle_id: None,
+                    title: None,
+                    domain: None,
+                },
+                CategoryRuleConfig {
+                    category: "research".to_string(),
+                    app: None,
+                    bundle_id: None,
+                    title: None,
+                    domain: Some("www.youtube.com".to_string()),
+                },
+            ],
+            productive: vec!["writing".to_string()],
+        };
+        let rules = CategoryRules::from_config(&config);
+
+        let writing = rules.categorize_app("Obsidian");
+        assert_eq!(writing, ActivityCategory::Custom("writing".to_string()));
+        assert!(rules.is_productive(&writing));
+        assert!(!rules.is_productive(&ActivityCategory::Coding));
+
+        assert_eq!(
+            rules.categorize(&ctx("Arc", "Talk", Some("https://youtube.com/watch?v=1"))),
+            ActivityCategory::Research
+        );
+        // Built-ins still apply after user rules
+        assert_eq!(
+            rules.categorize_app("Slack"),
+            ActivityCategory::Communication
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_rules_are_skipped() {
+        let config = CategoriesConfig {
+            rules: vec![
+                CategoryRuleConfig {
+                    category: "broken".to_string(),
+                    app: None,
+                    bundle_id: None,
+                    title: Some("(unclosed".to_string()),
+                    domain: None,
+                },
+                CategoryRuleConfig {
+                    category: "empty".to_string(),
+                    app: None,
+                    bundle_id: None,
+                    title: None,
+                    domain: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let rules = CategoryRules::from_config(&config);
+        assert_eq!(rules.rules.len(), CategoryRules::builtin().rules.len());
+    }
+
+    #[test]
+    fn test_bundle_id_rule() {
+        let config = CategoriesConfig {
+            rules: vec![CategoryRuleConfig {
+                category: "notes".to_string(),
+                app: None,
+                bundle_id: Some("com.apple.Notes".to_string()),
+                title: None,
+                domain: None,
+            }],
+            ..Default::default()
+        };
+        let rules = CategoryRules::from_config(&config);
+        let notes = ActivityContext {
+            app_name: "Notes",
+            bundle_id: Some("com.apple.Notes"),
+            title: None,
+            url: None,
+        };
+        assert_eq!(
+            rules.categorize(&notes),
+            ActivityCategory::Custom("notes".to_string())
+        );
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://www.example.com/path"),
+            Some("www.example.com")
+        );
+        assert_eq!(url_host("http://user:pw@host.io:8080/x"), Some("host.io"));
+        assert_eq!(url_host("example.com?q=1"), Some("example.com"));
+        assert_eq!(url_host("https:///nohost"), None);
+    }
+}
@@ -1,33 +1,114 @@
 //! Clipboard tracking module
 //!
-//! Monitors the system clipboard for changes and captures text content.
+//! Monitors the system clipboard for changes and captures text, HTML
+//! (kept as plain text), file lists and images. Images are stored as blobs
+//! under `~/.siphon/blobs` (see [`crate::blobs`]); events only reference them.
 //! Implements redaction for sensitive data like passwords, API keys, etc.
+//!
+//! Copies made while a password manager (or any app in `[clipboard]
+//! exclude_apps`) is focused are never stored, and neither is content the
+//! source app marked as concealed (`org.nspasteboard.ConcealedType` on
+//! macOS, `x-kde-passwordManagerHint` on Linux).
+//!
+//! Reading the clipboard can wait on the app that owns it, so
+//! [`BackgroundClipboard`] polls on its own thread and the daemon's ticker
+//! only picks up the changes.
 
-use arboard::Clipboard;
+use arboard::{Clipboard, ImageData};
 use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::sync::LazyLock;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::{Duration, Instant};
 use tracing::{debug, warn};
 
+use crate::blobs::{BlobStore, ImageBlob};
+use crate::categories::app_regex;
+use crate::secrets::{SecretDetector, SecretKind};
+use crate::storage::Event;
+use crate::window::WindowInfo;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 /// Maximum text length to store (prevent huge clipboard entries)
 const MAX_TEXT_LENGTH: usize = 10_000;
 
 /// Minimum text length to track (skip tiny snippets)
 const MIN_TEXT_LENGTH: usize = 2;
 
-/// Configuration for clipboard tracking
-#[derive(Debug, Clone)]
+/// Largest raw (RGBA) image to store: a 4K screenshot is ~33 MB
+const MAX_IMAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// How often an image still on the clipboard is re-read when there's no
+/// cheap way to tell whether it changed
+const IMAGE_RECHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Most file paths recorded from one copied selection
+const MAX_FILES: usize = 200;
+
+/// Total size cap for stored clipboard images
+const MAX_BLOB_STORAGE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Password managers whose copies are never stored (app names and bundle ids)
+const BUILTIN_EXCLUDED_APPS: &[&str] = &[
+    "1Password",
+    "Bitwarden",
+    "KeePassXC",
+    "KeePass",
+    "KeePassX",
+    "LastPass",
+    "Dashlane",
+    "Enpass",
+    "Keeper",
+    "Proton Pass",
+    "NordPass",
+    "Keychain Access",
+    "Passwords",
+    "Seahorse",
+    "gnome-keyring",
+    "KWalletManager",
+    "com.1password.1password",
+    "com.agilebits.onepassword7",
+    "com.bitwarden.desktop",
+    "org.keepassxc.keepassxc",
+    "com.apple.keychainaccess",
+    "com.apple.Passwords",
+];
+
+/// Clipboard flavours an app sets to say "don't record this"
+const CONCEALED_MARKERS: &[&str] = &[
+    "org.nspasteboard.ConcealedType",
+    "org.nspasteboard.TransientType",
+    "x-kde-passwordManagerHint",
+];
+
+/// Configuration for clipboard tracking (`[clipboard]` in the config file)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct ClipboardConfig {
     /// Minimum time between clipboard checks
+    #[serde(skip)]
     pub poll_interval: Duration,
     /// Whether to redact sensitive content
     pub redact_sensitive: bool,
     /// Maximum text length to store
     pub max_text_length: usize,
+    /// Largest raw image (width × height × 4 bytes) to store as a blob
+    pub max_image_bytes: usize,
+    /// Most paths to record from a copied file selection
+    pub max_files: usize,
+    /// Total size cap for image blobs; oldest are removed first
+    pub max_blob_storage_bytes: u64,
+    /// Apps whose copies are never stored (whole-word app name or exact bundle id)
+    pub exclude_apps: Vec<String>,
+    /// Also exclude well-known password managers
+    pub exclude_password_managers: bool,
+    /// Skip content marked concealed/transient by the source app
+    pub respect_concealed: bool,
 }
 
 impl Default for ClipboardConfig {
@@ -36,8 +117,64 @@ impl Default for ClipboardConfig {
             poll_interval: Duration::from_secs(1),
             redact_sensitive: true,
             max_text_length: MAX_TEXT_LENGTH,
+            max_image_bytes: MAX_IMAGE_BYTES,
+            max_files: MAX_FILES,
+            max_blob_storage_bytes: MAX_BLOB_STORAGE_BYTES,
+            exclude_apps: Vec::new(),
+            exclude_password_managers: true,
+            respect_concealed: true,
+        }
+    }
+}
+
+/// Compiled app denylist
+#[derive(Debug, Clone)]
+struct ExcludedApps {
+    names: Vec<Regex>,
+    ids: Vec<String>,
+}
+
+impl ExcludedApps {
+    fn from_config(config: &ClipboardConfig) -> Self {
+        let mut entries = config.exclude_apps.clone();
+        if config.exclude_password_managers {
+            entries.extend(BUILTIN_EXCLUDED_APPS.iter().map(|s| s.to_string()));
+        }
+        Self {
+            names: entries.iter().map(|name| app_regex(name)).collect(),
+            ids: entries.iter().map(|id| id.to_lowercase()).collect(),
         }
     }
+
+    /// Whether copies made while `window` is focused must be dropped
+    fn matches(&self, window: &WindowInfo) -> bool {
+        let bundle_match = window
+            .bundle_id
+            .as_deref()
+            .is_some_and(|id| self.ids.contains(&id.to_lowercase()));
+        bundle_match || self.names.iter().any(|re| re.is_match(&window.app_name))
+    }
+}
+
+/// Whether any of the clipboard's advertised flavours is a concealment marker
+fn has_concealed_marker<S: AsRef<str>>(targets: &[S]) -> bool {
+    targets.iter().any(|target| {
+        CONCEALED_MARKERS
+            .iter()
+            .any(|marker| target.as_ref().eq_ignore_ascii_case(marker))
+    })
+}
+
+/// Clipboard flavour the content was read from
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardFormat {
+    #[default]
+    Text,
+    /// Rich text; `text` holds the plain-text rendering
+    Html,
+    Image,
+    Files,
 }
 
 /// Types of clipboard content
@@ -49,6 +186,23 @@ pub enum ClipboardContentType {
     Url,
     Path,
     Command,
+    Image,
+    Files,
+}
+
+impl ClipboardContentType {
+    /// The snake_case name stored in event data
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClipboardContentType::Text => "text",
+            ClipboardContentType::Code => "code",
+            ClipboardContentType::Url => "url",
+            ClipboardContentType::Path => "path",
+            ClipboardContentType::Command => "command",
+            ClipboardContentType::Image => "image",
+            ClipboardContentType::Files => "files",
+        }
+    }
 }
 
 /// Information about clipboard content
@@ -62,6 +216,21 @@ pub struct ClipboardContent {
     pub original_length: usize,
     /// Whether any redaction was applied
     pub was_redacted: bool,
+    /// Kinds of secrets the detector removed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<SecretKind>,
+    /// Whether the text (or file list) was cut to the configured maximum
+    #[serde(default)]
+    pub truncated: bool,
+    /// Clipboard flavour the content came from
+    #[serde(default)]
+    pub format: ClipboardFormat,
+    /// Stored image, for image copies small enough to keep
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageBlob>,
+    /// Copied file paths, for file selections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
     /// Hash of the original content (for deduplication)
     pub content_hash: u64,
 }
@@ -78,12 +247,142 @@ pub struct ClipboardChangeEvent {
     pub source_app: Option<String>,
 }
 
+/// A stored clipboard change, as returned by the clipboard history API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardEntry {
+    /// Event id
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+    pub content_type: ClipboardContentType,
+    pub source_app: Option<String>,
+    pub original_length: usize,
+    pub was_redacted: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<SecretKind>,
+    pub truncated: bool,
+    pub format: ClipboardFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageBlob>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+impl ClipboardEntry {
+    /// Build an entry from a stored `clipboard_change` event
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let change: ClipboardChangeEvent = serde_json::from_str(&event.event_data).ok()?;
+        Some(Self {
+            id: event.id.clone(),
+            timestamp: event.timestamp,
+            text: change.content.text,
+            content_type: change.content.content_type,
+            source_app: change.source_app,
+            original_length: change.content.original_length,
+            was_redacted: change.content.was_redacted,
+            secrets: change.content.secrets,
+            truncated: change.content.truncated,
+            format: change.content.format,
+            image: change.content.image,
+            files: change.content.files,
+        })
+    }
+
+    /// Whether the stored content is the full original and can be put back on the clipboard
+    pub fn is_restorable(&self) -> bool {
+        let has_payload = match self.format {
+            ClipboardFormat::Image => self.image.is_some(),
+            ClipboardFormat::Files => !self.files.is_empty(),
+            ClipboardFormat::Text | ClipboardFormat::Html => true,
+        };
+        has_payload && !self.was_redacted && !self.truncated
+    }
+}
+
+/// Raw clipboard content, before processing
+enum Capture {
+    Files(Vec<PathBuf>),
+    Html(String),
+    Text(String),
+    Image(ImageData<'static>),
+}
+
+impl Capture {
+    /// Read the richest flavour available: files, then HTML, then text, then
+    /// image, unless `read_image` says the image isn't worth reading again
+    fn read(clipboard: &mut Clipboard, read_image: impl FnOnce() -> bool) -> Option<Self> {
+        if let Ok(files) = clipboard.get().file_list() {
+            if !files.is_empty() {
+                return Some(Capture::Files(files));
+            }
+        }
+        if let Ok(html) = clipboard.get().html() {
+            if !html.trim().is_empty() {
+                return Some(Capture::Html(html));
+            }
+        }
+        if let Ok(text) = clipboard.get_text() {
+            return Some(Capture::Text(text));
+        }
+        if !read_image() {
+            return None;
+        }
+        clipboard.get_image().ok().map(Capture::Image)
+    }
+
+    /// Hash for change detection
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match self {
+            Capture::Files(files) => files.hash(&mut hasher),
+            Capture::Html(html) => html.hash(&mut hasher),
+            Capture::Text(text) => return ClipboardTracker::hash_content(text),
+            Capture::Image(image) => {
+                (image.width, image.height).hash(&mut hasher);
+                image.bytes.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+}
+
+/// The image last read from the clipboard
+///
+/// Reading one copies and hashes every pixel, so while it's still there it
+/// is only read again once the clipboard's stamp changes.
+struct HeldImage {
+    /// [`ClipboardProbe::stamp`] when it was read
+    stamp: Option<u64>,
+    read_at: Instant,
+}
+
+impl HeldImage {
+    fn new(stamp: Option<u64>) -> Self {
+        Self {
+            stamp,
+            read_at: Instant::now(),
+        }
+    }
+
+    /// Whether the clipboard may hold something else, given its stamp now
+    fn may_have_changed(&self, stamp: Option<u64>) -> bool {
+        match (self.stamp, stamp) {
+            (Some(then), Some(now)) => then != now,
+            _ => self.read_at.elapsed() >= IMAGE_RECHECK_INTERVAL,
+        }
+    }
+}
+
 /// Tracks clipboard content and emits events on changes
 pub struct ClipboardTracker {
     config: ClipboardConfig,
+    excluded_apps: ExcludedApps,
+    blobs: BlobStore,
     clipboard: Option<Clipboard>,
+    probe: ClipboardProbe,
     last_content_hash: Option<u64>,
     last_check_time: Instant,
+    held_image: Option<HeldImage>,
 }
 
 impl ClipboardTracker {
@@ -98,15 +397,22 @@ impl ClipboardTracker {
         };
 
         Self {
+            excluded_apps: ExcludedApps::from_config(&config),
             config,
+            blobs: BlobStore::new(),
             clipboard,
+            probe: ClipboardProbe::new(),
             last_content_hash: None,
             last_check_time: Instant::now(),
+            held_image: None,
         }
     }
 
     /// Check the clipboard and return an event if content changed
-    pub fn check_clipboard(&mut self, source_app: Option<String>) -> Option<ClipboardChangeEvent> {
+    ///
+    /// `source` is the focused window, used both as the source hint and to
+    /// drop copies made in excluded apps.
+    pub fn check_clipboard(&mut self, source: Option<&WindowInfo>) -> Option<ClipboardChangeEvent> {
         // Respect poll interval
         if self.last_check_time.elapsed() < self.config.poll_interval {
             return None;
@@ -114,68 +420,197 @@ impl ClipboardTracker {
         self.last_check_time = Instant::now();
 
         let clipboard = self.clipboard.as_mut()?;
-
-        // Try to get text content
-        let text = match clipboard.get_text() {
-            Ok(t) => t,
-            Err(_) => return None, // No text content or error
+        // Taken before reading, so a copy made meanwhile moves it again
+        let stamp = self.probe.stamp();
+        let held_image = &self.held_image;
+        let capture = Capture::read(clipboard, || {
+            held_image
+                .as_ref()
+                .is_none_or(|held| held.may_have_changed(stamp))
+        });
+        self.held_image = match capture {
+            Some(Capture::Image(_)) => Some(HeldImage::new(stamp)),
+            // Unchanged image, or nothing readable
+            None => self.held_image.take(),
+            Some(_) => None,
         };
+        let capture = capture?;
 
-        // Skip empty or too short text
-        if text.len() < MIN_TEXT_LENGTH {
+        // Check if content changed
+        let content_hash = capture.hash();
+        if self.last_content_hash == Some(content_hash) {
             return None;
         }
+        // Remembered even when skipped, so switching away from a password
+        // manager doesn't pick up the same secret from the next app
+        self.last_content_hash = Some(content_hash);
 
-        // Calculate hash of content
-        let content_hash = Self::hash_content(&text);
-
-        // Check if content changed
-        if self.last_content_hash == Some(content_hash) {
+        if let Some(window) = source.filter(|w| self.excluded_apps.matches(w)) {
+            debug!(
+                "Skipping clipboard change from excluded app {}",
+                window.app_name
+            );
+            return None;
+        }
+        if self.config.respect_concealed
+            && self
+                .probe
+                .targets()
+                .is_some_and(|targets| has_concealed_marker(&targets))
+        {
+            debug!("Skipping clipboard change marked as concealed");
             return None;
         }
 
-        self.last_content_hash = Some(content_hash);
+        let content = match capture {
+            Capture::Text(text) => self.text_content(text, ClipboardFormat::Text, content_hash)?,
+            Capture::Html(html) => {
+                self.text_content(html_to_text(&html), ClipboardFormat::Html, content_hash)?
+            }
+            Capture::Files(files) => self.files_content(files, content_hash),
+            Capture::Image(image) => self.image_content(image, content_hash),
+        };
+
+        debug!(
+            "Clipboard changed: {:?}, {} chars, type: {:?}, redacted: {}",
+            content.format, content.original_length, content.content_type, content.was_redacted
+        );
+
+        Some(ClipboardChangeEvent {
+            content,
+            timestamp: Utc::now(),
+            source_app: source.map(|w| w.app_name.clone()),
+        })
+    }
+
+    /// Redact, classify and truncate copied text
+    fn text_content(
+        &self,
+        text: String,
+        format: ClipboardFormat,
+        content_hash: u64,
+    ) -> Option<ClipboardContent> {
+        // Skip empty or too short text
+        if text.len() < MIN_TEXT_LENGTH {
+            return None;
+        }
 
         // Detect content type
         let content_type = Self::detect_content_type(&text);
 
+        // Cut to the length cap first, so detection never scans more than we keep
+        let original_length = text.len();
+        let truncated = original_length > self.config.max_text_length;
+        let kept = if truncated {
+            let mut cut = self.config.max_text_length;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            &text[..cut]
+        } else {
+            text.as_str()
+        };
+
         // Apply redaction if needed
-        let (processed_text, was_redacted) = if self.config.redact_sensitive {
-            redact_sensitive_content(&text)
+        let (processed_text, was_redacted, secrets) = if self.config.redact_sensitive {
+            let (detected, findings) = SecretDetector::default().redact(kept);
+            let (redacted, matched_pattern) = redact_sensitive_content(&detected);
+            let secrets: Vec<SecretKind> = findings.iter().map(|f| f.kind).collect();
+            (redacted, matched_pattern || !secrets.is_empty(), secrets)
         } else {
-            (text.clone(), false)
+            (kept.to_string(), false, Vec::new())
         };
 
-        // Truncate if too long
-        let original_length = text.len();
-        let final_text = if processed_text.len() > self.config.max_text_length {
+        let final_text = if truncated {
             format!(
                 "{}... [truncated, {} chars total]",
-                &processed_text[..self.config.max_text_length],
-                original_length
+                processed_text, original_length
             )
         } else {
             processed_text
         };
 
-        debug!(
-            "Clipboard changed: {} chars, type: {:?}, redacted: {}",
-            original_length, content_type, was_redacted
-        );
-
-        Some(ClipboardChangeEvent {
-            content: ClipboardContent {
-                text: final_text,
-                content_type,
-                original_length,
-                was_redacted,
-                content_hash,
-            },
-            timestamp: Utc::now(),
-            source_app,
+        Some(ClipboardContent {
+            text: final_text,
+            content_type,
+            original_length,
+            was_redacted,
+            secrets,
+            truncated,
+            format,
+            image: None,
+            files: Vec::new(),
+            content_hash,
         })
     }
 
+    /// Record copied file paths (capped at `max_files`)
+    fn files_content(&self, files: Vec<PathBuf>, content_hash: u64) -> ClipboardContent {
+        let original_length = files.len();
+        let truncated = files.len() > self.config.max_files;
+        let files: Vec<String> = files
+            .into_iter()
+            .take(self.config.max_files)
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+
+        ClipboardContent {
+            text: files.join("\n"),
+            content_type: ClipboardContentType::Files,
+            original_length,
+            was_redacted: false,
+            secrets: Vec::new(),
+            truncated,
+            format: ClipboardFormat::Files,
+            image: None,
+            files,
+            content_hash,
+        }
+    }
+
+    /// Store a copied image as a blob; oversized images are recorded without pixels
+    fn image_content(&self, image: ImageData<'static>, content_hash: u64) -> ClipboardContent {
+        let (width, height) = (image.width, image.height);
+        let raw_size = image.bytes.len();
+
+        let blob = if raw_size > self.config.max_image_bytes {
+            debug!(
+                "Clipboard image {}x{} over size cap, not stored",
+                width, height
+            );
+            None
+        } else {
+            match self
+                .blobs
+                .put_rgba(width as u32, height as u32, image.bytes.into_owned())
+            {
+                Ok(blob) => Some(blob),
+                Err(e) => {
+                    warn!("Failed to store clipboard image: {}", e);
+                    None
+                }
+            }
+        };
+
+        let text = match blob {
+            Some(_) => format!("[image {}x{}]", width, height),
+            None => format!("[image {}x{}, not stored]", width, height),
+        };
+
+        ClipboardContent {
+            text,
+            content_type: ClipboardContentType::Image,
+            original_length: raw_size,
+            was_redacted: false,
+            secrets: Vec::new(),
+            truncated: false,
+            format: ClipboardFormat::Image,
+            image: blob,
+            files: Vec::new(),
+            content_hash,
+        }
+    }
+
     /// Hash content for change detection
     fn hash_content(text: &str) -> u64 {
         let mut hasher = DefaultHasher::new();
@@ -244,6 +679,326 @@ impl ClipboardTracker {
     pub fn is_available(&self) -> bool {
         self.clipboard.is_some()
     }
+
+    /// Put an entry back on the clipboard without recording it as a new copy
+    pub fn restore(&mut self, entry: &ClipboardEntry) -> Result<(), String> {
+        let clipboard = self
+            .clipboard
+            .as_mut()
+            .ok_or_else(|| "clipboard unavailable".to_string())?;
+        let capture = write_entry(clipboard, entry, &self.blobs)?;
+        self.last_content_hash = Some(capture.hash());
+        self.held_image = None;
+        Ok(())
+    }
+}
+
+/// Polls a [`ClipboardTracker`] on its own thread
+///
+/// The ticker hands over the focused window, which copies are attributed
+/// to, and picks up the changes seen since it last looked.
+pub struct BackgroundClipboard {
+    tracker: Arc<Mutex<ClipboardTracker>>,
+    source: Arc<Mutex<Option<WindowInfo>>>,
+    changes: Arc<Mutex<Vec<ClipboardChangeEvent>>>,
+}
+
+impl BackgroundClipboard {
+    /// Poll `tracker` at its configured interval until this is dropped
+    pub fn spawn(tracker: ClipboardTracker) -> Self {
+        let interval = tracker.config.poll_interval;
+        let tracker = Arc::new(Mutex::new(tracker));
+        let source = Arc::new(Mutex::new(None));
+        let changes = Arc::new(Mutex::new(Vec::new()));
+
+        let shared_tracker = Arc::clone(&tracker);
+        let shared_source = Arc::clone(&source);
+        let shared_changes = Arc::clone(&changes);
+        let spawned = std::thread::Builder::new()
+            .name("clipboard".to_string())
+            .spawn(move || {
+                while Arc::strong_count(&shared_changes) > 1 {
+                    std::thread::sleep(interval);
+                    let source = match shared_source.lock() {
+                        Ok(source) => source.clone(),
+                        Err(_) => return,
+                    };
+                    let change = match shared_tracker.lock() {
+                        Ok(mut tracker) => tracker.check_clipboard(source.as_ref()),
+                        Err(_) => return,
+                    };
+                    if let Some(change) = change {
+                        match shared_changes.lock() {
+                            Ok(mut changes) => changes.push(change),
+                            Err(_) => return,
+                        }
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Couldn't start clipboard polling: {}", e);
+        }
+
+        Self {
+            tracker,
+            source,
+            changes,
+        }
+    }
+
+    /// Attribute the next copies to `window`
+    pub fn set_source(&self, window: Option<&WindowInfo>) {
+        if let Ok(mut source) = self.source.lock() {
+            *source = window.cloned();
+        }
+    }
+
+    /// Changes seen since the last call
+    pub fn drain(&self) -> Vec<ClipboardChangeEvent> {
+        self.changes
+            .lock()
+            .map(|mut changes| std::mem::take(&mut *changes))
+            .unwrap_or_default()
+    }
+
+    /// Put an entry back on the clipboard without recording it as a new copy
+    pub fn restore(&self, entry: &ClipboardEntry) -> Result<(), String> {
+        self.tracker
+            .lock()
+            .map_err(|_| "clipboard tracker panicked".to_string())?
+            .restore(entry)
+    }
+}
+
+/// Put an entry on the clipboard when no tracker is running
+///
+/// On X11 the content is only served while a `Clipboard` is alive, so prefer
+/// [`BackgroundClipboard::restore`] when tracking is enabled.
+pub fn restore_entry(entry: &ClipboardEntry, blobs: &BlobStore) -> Result<(), String> {
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    write_entry(&mut clipboard, entry, blobs).map(|_| ())
+}
+
+/// Write an entry to the clipboard, returning what was written for change detection
+fn write_entry(
+    clipboard: &mut Clipboard,
+    entry: &ClipboardEntry,
+    blobs: &BlobStore,
+) -> Result<Capture, String> {
+    match entry.format {
+        ClipboardFormat::Image => {
+            let blob = entry.image.as_ref().ok_or("image was not stored")?;
+            let (width, height, rgba) = blobs.get_rgba(&blob.hash).map_err(|e| e.to_string())?;
+            let image = ImageData {
+                width: width as usize,
+                height: height as usize,
+                bytes: rgba.into(),
+            };
+            clipboard
+                .set_image(image.clone())
+                .map_err(|e| e.to_string())?;
+            Ok(Capture::Image(image))
+        }
+        ClipboardFormat::Files => {
+            let files: Vec<PathBuf> = entry.files.iter().map(PathBuf::from).collect();
+            clipboard
+                .set()
+                .file_list(&files)
+                .map_err(|e| e.to_string())?;
+            Ok(Capture::Files(files))
+        }
+        // Only the plain-text rendering of HTML is kept
+        ClipboardFormat::Text | ClipboardFormat::Html => {
+            clipboard.set_text(&entry.text).map_err(|e| e.to_string())?;
+            Ok(Capture::Text(entry.text.clone()))
+        }
+    }
+}
+
+/// What can be learnt about the clipboard without reading it
+struct ClipboardProbe {
+    /// Owner change notifications, outside Wayland sessions
+    #[cfg(target_os = "linux")]
+    x11: Option<linux::X11Clipboard>,
+}
+
+impl ClipboardProbe {
+    fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            x11: if std::env::var_os("WAYLAND_DISPLAY").is_none()
+                && std::env::var_os("DISPLAY").is_some()
+            {
+                linux::X11Clipboard::connect()
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Something cheap to read that changes whenever anything is copied
+    ///
+    /// The pasteboard's change count on macOS and the number of `CLIPBOARD`
+    /// owner changes on X11. Wayland has no such thing.
+    fn stamp(&mut self) -> Option<u64> {
+        #[cfg(target_os = "linux")]
+        {
+            self.x11.as_mut().and_then(|x11| x11.changes())
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            with_general_pasteboard(|pasteboard| {
+                let count: isize = unsafe { objc2::msg_send![pasteboard, changeCount] };
+                Some(count as u64)
+            })
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            None
+        }
+    }
+
+    /// Flavours (MIME types / atom names / pasteboard types) the clipboard offers
+    ///
+    /// Read via `wl-paste --list-types` on Wayland, a `TARGETS` request on
+    /// X11 and `NSPasteboard` on macOS. Elsewhere we can't see concealment
+    /// markers.
+    fn targets(&mut self) -> Option<Vec<String>> {
+        #[cfg(target_os = "linux")]
+        {
+            if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                return command_output("wl-paste", &["--list-types"])
+                    .map(|types| type_list(&types));
+            }
+            self.x11.as_mut().and_then(|x11| x11.targets())
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            use objc2::rc::Retained;
+            use objc2_foundation::{NSArray, NSString};
+
+            with_general_pasteboard(|pasteboard| {
+                let types: Option<Retained<NSArray<NSString>>> =
+                    unsafe { objc2::msg_send![pasteboard, types] };
+                Some(
+                    types
+                        .map(|types| types.iter().map(|t| t.to_string()).collect())
+                        .unwrap_or_default(),
+                )
+            })
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            None
+        }
+    }
+}
+
+/// Run `f` on the general pasteboard, which can be missing when the daemon
+/// runs under launchd
+#[cfg(target_os = "macos")]
+fn with_general_pasteboard<T>(
+    f: impl FnOnce(&objc2_app_kit::NSPasteboard) -> Option<T>,
+) -> Option<T> {
+    use objc2::rc::{autoreleasepool, Retained};
+    use objc2::ClassType;
+    use objc2_app_kit::NSPasteboard;
+
+    // Our polling thread has no autorelease pool of its own
+    autoreleasepool(|_| {
+        let pasteboard: Option<Retained<NSPasteboard>> =
+            unsafe { objc2::msg_send![NSPasteboard::class(), generalPasteboard] };
+        f(&pasteboard?)
+    })
+}
+
+/// Stdout of a successful run of `program`
+#[cfg(target_os = "linux")]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+}
+
+/// One flavour per line, as `wl-paste --list-types` prints them
+#[cfg(target_os = "linux")]
+fn type_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+static HTML_DROP_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<!--.*?-->|<(script|style|head)\b[^>]*>.*?</(script|style|head)\s*>").unwrap()
+});
+
+static HTML_BREAK_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<br\s*/?>|</(p|div|li|tr|h[1-6]|pre|blockquote|table|ul|ol)\s*>").unwrap()
+});
+
+static HTML_LIST_ITEM_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<li\b[^>]*>").unwrap());
+
+static HTML_CELL_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)</t[dh]\s*>").unwrap());
+
+static HTML_TAG_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+static HTML_ENTITY_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+
+/// Render HTML as plain text: block elements become line breaks, tags are dropped, entities decoded
+pub fn html_to_text(html: &str) -> String {
+    let text = HTML_DROP_PATTERN.replace_all(html, "");
+    let text = HTML_BREAK_PATTERN.replace_all(&text, "\n");
+    let text = HTML_LIST_ITEM_PATTERN.replace_all(&text, "- ");
+    let text = HTML_CELL_PATTERN.replace_all(&text, "\t");
+    let text = HTML_TAG_PATTERN.replace_all(&text, "");
+    let text = HTML_ENTITY_PATTERN.replace_all(&text, |caps: &regex::Captures| {
+        let entity = &caps[1];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        decoded
+            .map(String::from)
+            .unwrap_or_else(|| caps[0].to_string())
+    });
+
+    // Collapse whitespace within lines and runs of blank lines
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
 }
 
 // Regex patterns for sensitive content
@@ -409,6 +1164,215 @@ mod tests {
         assert!(result.contains("[REDACTED"));
     }
 
+    #[test]
+    fn test_entry_from_event() {
+        let change = ClipboardChangeEvent {
+            content: ClipboardContent {
+                text: "cargo build".to_string(),
+                content_type: ClipboardContentType::Command,
+                original_length: 11,
+                was_redacted: false,
+                secrets: Vec::new(),
+                truncated: false,
+                format: ClipboardFormat::Text,
+                image: None,
+                files: Vec::new(),
+                content_hash: 1,
+            },
+            timestamp: Utc::now(),
+            source_app: Some("Terminal".to_string()),
+        };
+        let event = Event {
+            id: "abc".to_string(),
+            timestamp: change.timestamp,
+            source: "clipboard".to_string(),
+            event_type: "clipboard_change".to_string(),
+            event_data: serde_json::to_string(&change).unwrap(),
+            project: None,
+        };
+
+        let entry = ClipboardEntry::from_event(&event).unwrap();
+        assert_eq!(entry.id, "abc");
+        assert_eq!(entry.content_type, ClipboardContentType::Command);
+        assert_eq!(entry.source_app.as_deref(), Some("Terminal"));
+        assert!(entry.is_restorable());
+
+        // Events stored before `truncated` existed still parse
+        let mut old: serde_json::Value = serde_json::from_str(&event.event_data).unwrap();
+        old["content"].as_object_mut().unwrap().remove("truncated");
+        let old_event = Event {
+            event_data: old.to_string(),
+            ..event
+        };
+        assert!(!ClipboardEntry::from_event(&old_event).unwrap().truncated);
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<html><head><style>p { color: red }</style></head><body>
+            <h1>Release&nbsp;notes</h1>
+            <p>Fixed <b>crash</b> when &lt;Enter&gt; &amp; <a href="x">Esc</a> were pressed.</p>
+            <!-- comment -->
+            <ul><li>one</li><li>two &#8212; &#x2713;</li></ul>
+            <script>alert('x')</script>
+            <table><tr><td>a</td><td>b</td></tr></table>
+        </body></html>"#;
+
+        assert_eq!(
+            html_to_text(html),
+            "Release notes\n\nFixed crash when <Enter> & Esc were pressed.\n\n- one\n- two \u{2014} \u{2713}\n\na b"
+        );
+        assert_eq!(html_to_text("plain &bogus; text"), "plain &bogus; text");
+    }
+
+    #[test]
+    fn test_image_and_file_entries_restorability() {
+        let entry = |format, image, files: Vec<String>| ClipboardEntry {
+            id: "id".to_string(),
+            timestamp: Utc::now(),
+            text: String::new(),
+            content_type: ClipboardContentType::Image,
+            source_app: None,
+            original_length: 0,
+            was_redacted: false,
+            secrets: Vec::new(),
+            truncated: false,
+            format,
+            image,
+            files,
+        };
+        let blob = ImageBlob {
+            hash: "ab".repeat(32),
+            width: 10,
+            height: 10,
+            size_bytes: 100,
+        };
+
+        assert!(entry(ClipboardFormat::Image, Some(blob), vec![]).is_restorable());
+        // Oversized images are recorded without pixels
+        assert!(!entry(ClipboardFormat::Image, None, vec![]).is_restorable());
+        assert!(entry(ClipboardFormat::Files, None, vec!["/tmp/a".to_string()]).is_restorable());
+    }
+
+    #[test]
+    fn test_file_list_is_capped() {
+        let tracker = ClipboardTracker {
+            config: ClipboardConfig {
+                max_files: 2,
+                ..Default::default()
+            },
+            excluded_apps: ExcludedApps::from_config(&ClipboardConfig::default()),
+            blobs: BlobStore::open(std::env::temp_dir().join("siphon-unused-blobs")),
+            clipboard: None,
+            probe: ClipboardProbe::new(),
+            last_content_hash: None,
+            last_check_time: Instant::now(),
+            held_image: None,
+        };
+        let files = ["/a", "/b", "/c"].iter().map(PathBuf::from).collect();
+        let content = tracker.files_content(files, 0);
+        assert_eq!(content.files, vec!["/a", "/b"]);
+        assert_eq!(content.text, "/a\n/b");
+        assert_eq!(content.original_length, 3);
+        assert!(content.truncated);
+        assert_eq!(content.content_type, ClipboardContentType::Files);
+    }
+
+    fn window(app_name: &str, bundle_id: Option<&str>) -> WindowInfo {
+        WindowInfo {
+            app_name: app_name.to_string(),
+            title: String::new(),
+            process_id: 1,
+            bundle_id: bundle_id.map(String::from),
+            url: None,
+            bounds: None,
+        }
+    }
+
+    #[test]
+    fn test_excluded_apps() {
+        let config = ClipboardConfig {
+            exclude_apps: vec!["Banking".to_string(), "com.example.Wallet".to_string()],
+            ..Default::default()
+        };
+        let excluded = ExcludedApps::from_config(&config);
+
+        assert!(excluded.matches(&window("KeePassXC", None)));
+        assert!(excluded.matches(&window("1Password 7", None)));
+        assert!(excluded.matches(&window("My Banking", None)));
+        assert!(excluded.matches(&window("Wallet", Some("com.example.wallet"))));
+        assert!(!excluded.matches(&window("Terminal", None)));
+        // Whole words only
+        assert!(!excluded.matches(&window("Passwordsafe-notes", Some("org.notes"))));
+        assert!(!excluded.matches(&window("Onlinebanking", None)));
+
+        let config = ClipboardConfig {
+            exclude_password_managers: false,
+            ..Default::default()
+        };
+        assert!(!ExcludedApps::from_config(&config).matches(&window("Bitwarden", None)));
+    }
+
+    #[test]
+    fn test_held_image_rechecks() {
+        let held = HeldImage::new(Some(7));
+        assert!(!held.may_have_changed(Some(7)));
+        assert!(held.may_have_changed(Some(8)));
+
+        // Without a stamp, only once in a while
+        let held = HeldImage::new(None);
+        assert!(!held.may_have_changed(None));
+        let stale = HeldImage {
+            stamp: None,
+            read_at: Instant::now() - IMAGE_RECHECK_INTERVAL,
+        };
+        assert!(stale.may_have_changed(None));
+    }
+
+    #[test]
+    fn test_concealed_markers() {
+        assert!(has_concealed_marker(&[
+            "text/plain;charset=utf-8",
+            "x-kde-passwordManagerHint",
+        ]));
+        assert!(has_concealed_marker(&["org.nspasteboard.ConcealedType"]));
+        assert!(has_concealed_marker(&["org.nspasteboard.TransientType"]));
+        assert!(!has_concealed_marker(&[
+            "TARGETS",
+            "UTF8_STRING",
+            "text/html"
+        ]));
+        assert!(!has_concealed_marker::<&str>(&[]));
+
+        let listed = type_list("public.utf8-plain-text\norg.nspasteboard.ConcealedType\n\n");
+        assert_eq!(listed.len(), 2);
+        assert!(has_concealed_marker(&listed));
+    }
+
+    #[test]
+    fn test_text_content_records_detected_secrets() {
+        let tracker = ClipboardTracker {
+            config: ClipboardConfig::default(),
+            excluded_apps: ExcludedApps::from_config(&ClipboardConfig::default()),
+            blobs: BlobStore::open(std::env::temp_dir().join("siphon-unused-blobs")),
+            clipboard: None,
+            probe: ClipboardProbe::new(),
+            last_content_hash: None,
+            last_check_time: Instant::now(),
+            held_image: None,
+        };
+        let content = tracker
+            .text_content(
+                "DATABASE_URL=postgres://app:S3cr3tP4ss@db/app".to_string(),
+                ClipboardFormat::Text,
+                0,
+            )
+            .unwrap();
+        assert!(content.was_redacted);
+        assert!(!content.text.contains("S3cr3tP4ss"));
+        assert_eq!(content.secrets, vec![SecretKind::UrlPassword]);
+    }
+
     #[test]
     fn test_hash_consistency() {
         let hash1 = ClipboardTracker::hash_content("hello");
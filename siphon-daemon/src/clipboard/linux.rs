@@ -0,0 +1,233 @@
+//! Clipboard change notifications and targets on X11
+//!
+//! One connection stays open for the daemon's lifetime. XFixes tells it
+//! whenever the `CLIPBOARD` selection changes hands, which happens on every
+//! copy, so noticing a change costs no round trip to the X server. The same
+//! connection asks the owner for its `TARGETS` after a change.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use x11rb::connection::Connection;
+use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, CreateWindowAux, WindowClass};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+/// How long the owner gets to answer a `TARGETS` request
+const TARGETS_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The `CLIPBOARD` selection on the X server named by `DISPLAY`
+pub struct X11Clipboard {
+    conn: RustConnection,
+    /// Receives the notifications and the owner's answers
+    window: u32,
+    atoms: ClipboardAtoms,
+    /// Owner changes seen since connecting
+    changes: u64,
+    /// Target atoms are few and never change meaning
+    atom_names: HashMap<u32, String>,
+}
+
+struct ClipboardAtoms {
+    clipboard: u32,
+    targets: u32,
+    /// Where the owner puts its answer
+    property: u32,
+}
+
+impl X11Clipboard {
+    /// Connect and ask for owner change notifications
+    pub fn connect() -> Option<Self> {
+        match Self::try_connect() {
+            Ok(x11) => Some(x11),
+            Err(e) => {
+                debug!("X11 clipboard notifications unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    fn try_connect() -> Result<Self, Box<dyn std::error::Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn
+            .setup()
+            .roots
+            .get(screen_num)
+            .ok_or("no such screen")?
+            .root;
+        let intern = |name: &[u8]| -> Result<u32, Box<dyn std::error::Error>> {
+            Ok(conn.intern_atom(false, name)?.reply()?.atom)
+        };
+        let atoms = ClipboardAtoms {
+            clipboard: intern(b"CLIPBOARD")?,
+            targets: intern(b"TARGETS")?,
+            property: intern(b"SIPHON_TARGETS")?,
+        };
+        // XFixes needs to know which version we speak before anything else
+        conn.xfixes_query_version(5, 0)?.reply()?;
+
+        let window = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )?;
+        conn.xfixes_select_selection_input(
+            window,
+            atoms.clipboard,
+            SelectionEventMask::SET_SELECTION_OWNER
+                | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+        )?;
+        conn.flush()?;
+
+        Ok(Self {
+            conn,
+            window,
+            atoms,
+            changes: 0,
+            atom_names: HashMap::new(),
+        })
+    }
+
+    /// How many times `CLIPBOARD` has changed hands; None once the
+    /// connection is gone
+    pub fn changes(&mut self) -> Option<u64> {
+        loop {
+            match self.conn.poll_for_event() {
+                Ok(Some(event)) => self.handle(&event),
+                Ok(None) => return Some(self.changes),
+                Err(e) => {
+                    debug!("X11 clipboard connection failed: {}", e);
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Ask the current owner which targets it offers
+    pub fn targets(&mut self) -> Option<Vec<String>> {
+        match self.try_targets() {
+            Ok(targets) => targets,
+            Err(e) => {
+                debug!("Failed to read X11 clipboard targets: {}", e);
+                None
+            }
+        }
+    }
+
+    fn try_targets(&mut self) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>> {
+        self.conn.convert_selection(
+            self.window,
+            self.atoms.clipboard,
+            self.atoms.targets,
+            self.atoms.property,
+            x11rb::CURRENT_TIME,
+        )?;
+        self.conn.flush()?;
+
+        // The owner answers with SelectionNotify, or never if it's hung
+        let deadline = Instant::now() + TARGETS_TIMEOUT;
+        let answered = loop {
+            match self.conn.poll_for_event()? {
+                Some(Event::SelectionNotify(e)) if e.requestor == self.window => {
+                    break e.property != x11rb::NONE
+                }
+                Some(event) => self.handle(&event),
+                None if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(5)),
+                None => break false,
+            }
+        };
+        if !answered {
+            return Ok(None);
+        }
+
+        let atoms: Vec<u32> = self
+            .conn
+            .get_property(
+                true,
+                self.window,
+                self.atoms.property,
+                AtomEnum::ANY,
+                0,
+                1024,
+            )?
+            .reply()?
+            .value32()
+            .map(|values| values.collect())
+            .unwrap_or_default();
+        atoms
+            .into_iter()
+            .map(|atom| self.atom_name(atom))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    fn atom_name(&mut self, atom: u32) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(name) = self.atom_names.get(&atom) {
+            return Ok(name.clone());
+        }
+        let reply = self.conn.get_atom_name(atom)?.reply()?;
+        let name = String::from_utf8_lossy(&reply.name).to_string();
+        self.atom_names.insert(atom, name.clone());
+        Ok(name)
+    }
+
+    fn handle(&mut self, event: &Event) {
+        if let Event::XfixesSelectionNotify(e) = event {
+            if e.selection == self.atoms.clipboard {
+                self.changes += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run with `xvfb-run cargo test -- --ignored` to exercise the XFixes path
+    #[test]
+    #[ignore]
+    fn test_x11_clipboard_counts_owner_changes() {
+        let mut x11 = X11Clipboard::connect().expect("DISPLAY must point at an X server");
+        let before = x11.changes().unwrap();
+
+        // Take the selection from a second client, as a copy in another app would
+        let (conn, screen_num) = x11rb::connect(None).unwrap();
+        let root = conn.setup().roots[screen_num].root;
+        let window = conn.generate_id().unwrap();
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+        conn.set_selection_owner(window, x11.atoms.clipboard, x11rb::CURRENT_TIME)
+            .unwrap();
+        conn.flush().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while x11.changes().unwrap() == before && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(x11.changes().unwrap() > before);
+    }
+}
@@ -0,0 +1,244 @@
+//! Daemon configuration file
+//!
+//! Read once at startup from `~/.siphon/config.toml` (or the path in
+//! `SIPHON_CONFIG`). Every section is optional and falls back to defaults,
+//! and a missing file is the same as an empty one.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::browser::BrowserConfig;
+use crate::calendar::CalendarConfig;
+use crate::categories::CategoriesConfig;
+use crate::clipboard::ClipboardConfig;
+use crate::export::ObsidianConfig;
+use crate::hotkey::HotkeyConfig;
+use crate::jobs::JobConfig;
+use crate::meeting::MeetingConfig;
+use crate::redact::RedactionConfig;
+use crate::scoring::FocusScoreConfig;
+
+/// Top-level config file contents
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SiphonConfig {
+    /// App/URL categorisation rules
+    pub categories: CategoriesConfig,
+    /// Browser history collector
+    pub browser: BrowserConfig,
+    /// Clipboard capture limits and app exclusions
+    pub clipboard: ClipboardConfig,
+    /// How secrets in commands are replaced
+    pub redaction: RedactionConfig,
+    /// Global hotkey bindings
+    pub hotkeys: HotkeyConfig,
+    /// Local calendar files for meeting detection
+    pub calendar: CalendarConfig,
+    /// Meeting detection timing and extra platforms
+    pub meeting: MeetingConfig,
+    /// Focus score weights and thresholds
+    pub focus_score: FocusScoreConfig,
+    /// Obsidian vault for daily note export
+    pub obsidian: ObsidianConfig,
+    /// Schedule overrides per job name
+    pub jobs: HashMap<String, JobConfig>,
+}
+
+impl SiphonConfig {
+    /// Default config location, overridable with `SIPHON_CONFIG`
+    pub fn default_path() -> PathBuf {
+        if let Ok(path) = std::env::var("SIPHON_CONFIG") {
+            return PathBuf::from(path);
+        }
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".siphon")
+            .join("config.toml")
+    }
+
+    /// Load config from the default location, falling back to defaults on error
+    pub fn load() -> Self {
+        Self::load_from(&Self::default_path())
+    }
+
+    /// Load config from a path, falling back to defaults on error
+    pub fn load_from(path: &Path) -> Self {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read config {:?}: {}", path, e);
+                return Self::default();
+            }
+        };
+
+        match Self::parse(&contents) {
+            Ok(config) => {
+                info!("Loaded config from {:?}", path);
+                config
+            }
+            Err(e) => {
+                warn!("Invalid config {:?}, using defaults: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Parse config from TOML
+    pub fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+}
+
+/// Replace a leading `~` with the home directory
+pub fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| path.to_path_buf()),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = SiphonConfig::parse("").unwrap();
+        assert!(config.categories.rules.is_empty());
+        assert!(config.categories.productive.contains(&"coding".to_string()));
+    }
+
+    #[test]
+    fn test_parse_category_rules() {
+        let config = SiphonConfig::parse(
+            r#"
+            [categories]
+            productive = ["coding", "writing"]
+
+            [[categories.rules]]
+            category = "writing"
+            app = "Obsidian"
+
+            [[categories.rules]]
+            category = "entertainment"
+            domain = "news.ycombinator.com"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.categories.rules.len(), 2);
+        assert_eq!(config.categories.rules[0].app.as_deref(), Some("Obsidian"));
+        assert_eq!(
+            config.categories.rules[1].domain.as_deref(),
+            Some("news.ycombinator.com")
+        );
+        assert_eq!(config.categories.productive, vec!["coding", "writing"]);
+    }
+
+    #[test]
+    fn test_parse_clipboard_section() {
+        let config = SiphonConfig::parse(
+            r#"
+            [clipboard]
+            exclude_apps = ["Banking", "com.example.wallet"]
+            max_text_length = 500
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.clipboard.exclude_apps.len(), 2);
+        assert_eq!(config.clipboard.max_text_length, 500);
+        // Unset keys keep their defaults
+        assert!(config.clipboard.exclude_password_managers);
+        assert!(config.clipboard.respect_concealed);
+        assert_eq!(config.clipboard.poll_interval, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_parse_hotkeys_section() {
+        let config = SiphonConfig::parse(
+            r#"
+            [hotkeys]
+            quick_note = "ctrl+alt+n"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.hotkeys.enabled);
+        // Unset actions keep their defaults
+        assert!(config.hotkeys.mark_moment.is_some());
+        assert_eq!(config.hotkeys.quick_note.as_deref(), Some("ctrl+alt+n"));
+        assert!(config.hotkeys.toggle_focus.is_none());
+    }
+
+    #[test]
+    fn test_parse_calendar_section() {
+        let config = SiphonConfig::parse(
+            r#"
+            [calendar]
+            paths = ["~/Calendars/work.ics", "~/Calendars/exports"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.calendar.paths.len(), 2);
+        assert_eq!(config.calendar.match_slack_minutes, 10);
+        assert!(config.calendar.record_missed);
+        assert!(SiphonConfig::parse("").unwrap().calendar.paths.is_empty());
+    }
+
+    #[test]
+    fn test_parse_meeting_platforms() {
+        let config = SiphonConfig::parse(
+            r#"
+            [[meeting.platforms]]
+            name = "Jitsi"
+            apps = ["Jitsi Meet"]
+            titles = ["\\| Jitsi Meet$"]
+            domains = ["meet.jit.si"]
+
+            [[meeting.platforms]]
+            name = "CorpVideo"
+            domains = ["video.corp.example.com"]
+            "#,
+        )
+        .unwrap();
+
+        let platforms = &config.meeting.platforms;
+        assert_eq!(platforms.len(), 2);
+        assert_eq!(platforms[0].titles, vec![r"\| Jitsi Meet$"]);
+        assert!(platforms[1].apps.is_empty());
+        // Unset keys keep their defaults
+        assert_eq!(config.meeting.grace_period_secs, 60);
+    }
+
+    #[test]
+    fn test_parse_focus_score() {
+        let config = SiphonConfig::parse(
+            r#"
+            [focus_score]
+            deep_work_min_minutes = 45
+            switching_weight = 30
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.focus_score.deep_work_min_minutes, 45);
+        assert_eq!(config.focus_score.switching_weight, 30.0);
+        assert_eq!(config.focus_score.base, 60.0);
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let path =
+            std::env::temp_dir().join(format!("siphon-missing-{}.toml", uuid::Uuid::new_v4()));
+        let config = SiphonConfig::load_from(&path);
+        assert!(config.categories.rules.is_empty());
+    }
+}
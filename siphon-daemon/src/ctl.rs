@@ -1,8 +1,15 @@
 //! siphon-ctl - Control CLI for the Siphon daemon
 
+// The secret vault is read straight from disk, never through the daemon's API
+#[allow(dead_code)]
+#[path = "vault.rs"]
+mod vault;
+
 use clap::{Parser, Subcommand};
 use serde::Deserialize;
 
+use crate::vault::SecretVault;
+
 const DEFAULT_API_URL: &str = "http://127.0.0.1:9847";
 
 #[derive(Parser)]
@@ -43,6 +50,68 @@ enum Commands {
         #[arg(short = 'H', long, default_value = "4")]
         hours: u32,
     },
+
+    /// Browse and restore clipboard history
+    Clipboard {
+        #[command(subcommand)]
+        command: ClipboardCommands,
+    },
+
+    /// Inspect secrets kept by tokenised redaction (reads the local vault)
+    Secrets {
+        #[command(subcommand)]
+        command: SecretsCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretsCommands {
+    /// List stored placeholders (without values)
+    List,
+
+    /// Print the original value behind a placeholder
+    Reveal {
+        /// Placeholder, e.g. "[SECRET:gh_3fa1b2]" or "gh_3fa1b2"
+        placeholder: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ClipboardCommands {
+    /// List recent clipboard entries
+    List {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: u32,
+
+        /// Skip this many entries (for paging)
+        #[arg(long, default_value = "0")]
+        offset: u32,
+
+        /// Filter by content type (text, code, url, path, command, image, files)
+        #[arg(short = 't', long = "type")]
+        content_type: Option<String>,
+
+        /// Filter by source app
+        #[arg(short, long)]
+        app: Option<String>,
+
+        /// Search the copied text
+        #[arg(short, long)]
+        search: Option<String>,
+    },
+
+    /// Show a clipboard entry in full
+    Get {
+        /// Entry id (or a unique prefix of at least 4 characters)
+        id: String,
+    },
+
+    /// Put a clipboard entry back on the clipboard
+    Restore {
+        /// Entry id (or a unique prefix of at least 4 characters)
+        id: String,
+    },
 }
 
 #[derive(Deserialize)]
@@ -73,6 +142,38 @@ struct Event {
     project: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct ClipboardListResponse {
+    entries: Vec<ClipboardEntry>,
+    total: i64,
+}
+
+#[derive(Deserialize)]
+struct ClipboardEntryResponse {
+    entry: ClipboardEntry,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct ClipboardEntry {
+    id: String,
+    timestamp: String,
+    text: String,
+    content_type: String,
+    source_app: Option<String>,
+    was_redacted: bool,
+    truncated: bool,
+    #[serde(default)]
+    image: Option<ClipboardImage>,
+}
+
+#[derive(Deserialize)]
+struct ClipboardImage {
+    width: u32,
+    height: u32,
+    size_bytes: u64,
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -81,6 +182,21 @@ fn main() {
         Commands::Stats => cmd_stats(&cli.api_url),
         Commands::Events { hours, source } => cmd_events(&cli.api_url, hours, source),
         Commands::Ideas { hours } => cmd_ideas(&cli.api_url, hours),
+        Commands::Clipboard { command } => match command {
+            ClipboardCommands::List {
+                limit,
+                offset,
+                content_type,
+                app,
+                search,
+            } => cmd_clipboard_list(&cli.api_url, limit, offset, content_type, app, search),
+            ClipboardCommands::Get { id } => cmd_clipboard_get(&cli.api_url, &id),
+            ClipboardCommands::Restore { id } => cmd_clipboard_restore(&cli.api_url, &id),
+        },
+        Commands::Secrets { command } => match command {
+            SecretsCommands::List => cmd_secrets_list(),
+            SecretsCommands::Reveal { placeholder } => cmd_secrets_reveal(&placeholder),
+        },
     }
 }
 
@@ -281,7 +397,7 @@ fn cmd_ideas(api_url: &str, hours: u32) {
 
                 // Top tools
                 let mut topics_vec: Vec<_> = topics.into_iter().collect();
-                topics_vec.sort_by(|a, b| b.1.cmp(&a.1));
+                topics_vec.sort_by_key(|t| std::cmp::Reverse(t.1));
 
                 println!("Top tools used:");
                 for (tool, count) in topics_vec.iter().take(5) {
@@ -304,3 +420,210 @@ fn cmd_ideas(api_url: &str, hours: u32) {
         }
     }
 }
+
+fn cmd_clipboard_list(
+    api_url: &str,
+    limit: u32,
+    offset: u32,
+    content_type: Option<String>,
+    app: Option<String>,
+    search: Option<String>,
+) {
+    let mut query = vec![("limit", limit.to_string()), ("offset", offset.to_string())];
+    if let Some(t) = content_type {
+        query.push(("type", t));
+    }
+    if let Some(a) = app {
+        query.push(("app", a));
+    }
+    if let Some(q) = search {
+        query.push(("q", q));
+    }
+
+    let client = reqwest::blocking::Client::new();
+    match client
+        .get(format!("{}/clipboard", api_url))
+        .query(&query)
+        .send()
+    {
+        Ok(resp) => {
+            if resp.status().is_success() {
+                let list: ClipboardListResponse = resp.json().unwrap();
+                if list.entries.is_empty() {
+                    println!("No clipboard entries");
+                    return;
+                }
+
+                for entry in &list.entries {
+                    let time = entry.timestamp.get(..19).unwrap_or(&entry.timestamp);
+                    let preview: String = entry
+                        .text
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .chars()
+                        .take(60)
+                        .collect();
+                    println!(
+                        "{}  {}  {:8} {:12} {}",
+                        &entry.id[..8],
+                        time.replace('T', " "),
+                        entry.content_type,
+                        entry.source_app.as_deref().unwrap_or("-"),
+                        preview
+                    );
+                }
+
+                let shown = offset as i64 + list.entries.len() as i64;
+                if shown < list.total {
+                    println!("... {} more (use --offset {})", list.total - shown, shown);
+                }
+            } else {
+                eprintln!("Error: {}", resp.status());
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to connect to daemon: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn cmd_clipboard_get(api_url: &str, id: &str) {
+    let url = format!("{}/clipboard/{}", api_url, id);
+
+    match reqwest::blocking::get(&url) {
+        Ok(resp) => {
+            if resp.status().is_success() {
+                let entry = resp.json::<ClipboardEntryResponse>().unwrap().entry;
+                println!("Id:     {}", entry.id);
+                println!("Time:   {}", entry.timestamp);
+                println!("Type:   {}", entry.content_type);
+                println!("App:    {}", entry.source_app.as_deref().unwrap_or("-"));
+                if entry.was_redacted || entry.truncated {
+                    println!(
+                        "Note:   stored text was {}",
+                        if entry.was_redacted {
+                            "redacted"
+                        } else {
+                            "truncated"
+                        }
+                    );
+                }
+                println!();
+                match entry.image {
+                    Some(image) => {
+                        println!(
+                            "Image {}x{} ({} KB)",
+                            image.width,
+                            image.height,
+                            image.size_bytes / 1024
+                        );
+                        println!("  {}/clipboard/{}/image", api_url, entry.id);
+                    }
+                    None => println!("{}", entry.text),
+                }
+            } else {
+                print_api_error(resp);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to connect to daemon: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn cmd_clipboard_restore(api_url: &str, id: &str) {
+    let url = format!("{}/clipboard/{}/restore", api_url, id);
+
+    match reqwest::blocking::Client::new().post(&url).send() {
+        Ok(resp) => {
+            if resp.status().is_success() {
+                println!("Restored to clipboard");
+            } else {
+                print_api_error(resp);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to connect to daemon: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Open the local vault, exiting if tokenised redaction never created one
+fn open_vault() -> SecretVault {
+    let db_path = SecretVault::default_db_path();
+    if !db_path.exists() {
+        eprintln!("No secret vault at {:?}", db_path);
+        eprintln!("Enable it with `mode = \"tokenize\"` and `vault = true` under [redaction] in config.toml");
+        std::process::exit(1);
+    }
+    match SecretVault::open(&db_path, &SecretVault::default_key_path()) {
+        Ok(vault) => vault,
+        Err(e) => {
+            eprintln!("Failed to open secret vault: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn cmd_secrets_list() {
+    let entries = match open_vault().list() {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Failed to read secret vault: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if entries.is_empty() {
+        println!("No secrets stored");
+        return;
+    }
+
+    println!("Stored secrets ({}):", entries.len());
+    println!();
+    for entry in entries {
+        println!(
+            "  {:<22} {:<16} {:>4}x  last seen {}",
+            entry.placeholder,
+            entry.kind,
+            entry.uses,
+            entry.last_seen.format("%Y-%m-%d %H:%M")
+        );
+    }
+}
+
+fn cmd_secrets_reveal(placeholder: &str) {
+    let placeholder = if placeholder.starts_with("[SECRET:") {
+        placeholder.to_string()
+    } else {
+        format!("[SECRET:{}]", placeholder)
+    };
+
+    match open_vault().reveal(&placeholder) {
+        Ok(Some(secret)) => println!("{}", secret),
+        Ok(None) => {
+            eprintln!("Error: {} is not in the vault", placeholder);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Print the `error` field of a failed API response and exit
+fn print_api_error(resp: reqwest::blocking::Response) {
+    let status = resp.status();
+    let message = resp
+        .json::<serde_json::Value>()
+        .ok()
+        .and_then(|v| v["error"].as_str().map(String::from))
+        .unwrap_or_else(|| status.to_string());
+    eprintln!("Error: {}", message);
+    std::process::exit(1);
+}
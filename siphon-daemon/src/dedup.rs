@@ -158,7 +158,7 @@ impl Deduplicator {
             .collect();
 
         // Sort by timestamp (oldest first)
-        entries.sort_by(|a, b| a.1.cmp(&b.1));
+        entries.sort_by_key(|e| e.1);
 
         // Remove oldest entries
         for (key, _) in entries.into_iter().take(count) {
@@ -0,0 +1,674 @@
+//! Summary and report export
+//!
+//! Renders summaries and reports as Markdown, with sections for projects,
+//! apps, meetings, key activities and notes, or as versioned JSON with keys in
+//! a fixed order. A daily report can also be written into an Obsidian daily
+//! note as a `## Siphon` block, which is replaced on each export so running
+//! it again doesn't add a second copy.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Deserializer};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::config::expand_home;
+use crate::reports::{Report, ReportKind, ReportTotals};
+use crate::summary::SessionSummary;
+
+/// Version of the exported JSON layout, bumped on incompatible changes
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Heading of the block written into daily notes
+pub const DAILY_NOTE_HEADING: &str = "## Siphon";
+
+/// Most apps listed in an export
+const MAX_EXPORTED_APPS: usize = 10;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+/// Obsidian vault to write daily notes into (the `[obsidian]` config section)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ObsidianConfig {
+    /// Vault directory; daily note export is off without one
+    pub vault: Option<PathBuf>,
+    /// Folder in the vault daily notes live in
+    pub daily_folder: PathBuf,
+    /// Daily note file name, as a chrono format string (without `.md`)
+    #[serde(deserialize_with = "strftime_format")]
+    pub filename_format: String,
+}
+
+impl Default for ObsidianConfig {
+    fn default() -> Self {
+        Self {
+            vault: None,
+            daily_folder: PathBuf::new(),
+            filename_format: "%Y-%m-%d".to_string(),
+        }
+    }
+}
+
+/// A chrono format string, rejected up front if it has a specifier chrono
+/// doesn't know, since formatting with one panics
+fn strftime_format<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let format = String::deserialize(deserializer)?;
+    if StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+        return Err(serde::de::Error::custom(format!(
+            "invalid date format {:?}",
+            format
+        )));
+    }
+    Ok(format)
+}
+
+impl ObsidianConfig {
+    /// Path of the daily note for `date`, None without a vault
+    pub fn daily_note_path(&self, date: NaiveDate) -> Option<PathBuf> {
+        let vault = expand_home(self.vault.as_ref()?);
+        let name = format!("{}.md", date.format(&self.filename_format));
+        Some(vault.join(&self.daily_folder).join(name))
+    }
+}
+
+/// A summary as JSON, with a version and keys in a fixed order
+pub fn summary_json(summary: &SessionSummary) -> serde_json::Value {
+    serde_json::json!({
+        "version": EXPORT_VERSION,
+        "kind": "summary",
+        "summary": sorted(summary),
+    })
+}
+
+/// A report as JSON, with a version and keys in a fixed order
+pub fn report_json(report: &Report) -> serde_json::Value {
+    serde_json::json!({
+        "version": EXPORT_VERSION,
+        "kind": report.kind,
+        "report": sorted(report),
+    })
+}
+
+/// Serialize through `Value`, whose maps keep their keys sorted
+fn sorted<T: serde::Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// A summary as a Markdown document
+pub fn summary_markdown<Tz: TimeZone>(summary: &SessionSummary, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let start = summary.start_time.with_timezone(tz);
+    let end = summary.end_time.with_timezone(tz);
+    let mut out = format!(
+        "# Siphon summary: {} – {}\n\n",
+        start.format("%Y-%m-%d %H:%M"),
+        if start.date_naive() == end.date_naive() {
+            end.format("%H:%M").to_string()
+        } else {
+            end.format("%Y-%m-%d %H:%M").to_string()
+        }
+    );
+
+    let focused: u64 = summary.time_by_category.values().sum();
+    let _ = writeln!(
+        out,
+        "- **Duration:** {}",
+        duration(summary.duration_minutes as u64 * 60)
+    );
+    let _ = writeln!(out, "- **Focused time:** {}", duration(focused));
+    let _ = writeln!(out, "- **Focus score:** {}/100", summary.focus_score);
+    let _ = writeln!(out, "- **Events:** {}", summary.total_events);
+    out.push('\n');
+
+    write_sections(&mut out, summary, tz, 2);
+    finish(out)
+}
+
+/// A daily or weekly report as a Markdown document
+pub fn report_markdown<Tz: TimeZone>(report: &Report, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let title = match report.kind {
+        ReportKind::Daily => "Daily report",
+        ReportKind::Weekly => "Weekly report",
+    };
+    let mut out = format!("# {}: {}\n\n", title, report.period);
+    write_report_body(&mut out, report, tz, 2);
+    finish(out)
+}
+
+/// The `## Siphon` block for a daily note
+pub fn daily_note_block<Tz: TimeZone>(report: &Report, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let mut out = format!("{}\n\n", DAILY_NOTE_HEADING);
+    write_report_body(&mut out, report, tz, 3);
+    finish(out)
+}
+
+/// Replace the `## Siphon` block in a note, or append one
+///
+/// The block runs from its heading to the next heading of level 1 or 2, or
+/// the end of the note. Text around it is left as it is.
+pub fn upsert_block(note: &str, block: &str) -> String {
+    let lines: Vec<&str> = note.lines().collect();
+    let block = block.trim_end();
+
+    let Some(start) = lines
+        .iter()
+        .position(|line| line.trim_end() == DAILY_NOTE_HEADING)
+    else {
+        let mut out = note.trim_end().to_string();
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(block);
+        out.push('\n');
+        return out;
+    };
+
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.starts_with("# ") || line.starts_with("## "))
+        .map(|i| start + 1 + i)
+        .unwrap_or(lines.len());
+
+    let before = lines[..start].join("\n");
+    let after = lines[end..].join("\n");
+    let mut out = before.trim_end().to_string();
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(block);
+    out.push('\n');
+    if !after.trim().is_empty() {
+        out.push('\n');
+        out.push_str(after.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Write `block` into the daily note for `date`, creating the note if needed
+///
+/// Returns the note's path and whether it changed.
+pub fn write_daily_note(
+    config: &ObsidianConfig,
+    date: NaiveDate,
+    block: &str,
+) -> std::io::Result<(PathBuf, bool)> {
+    let path = config.daily_note_path(date).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "no Obsidian vault configured")
+    })?;
+    let current = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    let updated = upsert_block(&current, block);
+    if updated == current {
+        return Ok((path, false));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, updated)?;
+    Ok((path, true))
+}
+
+/// Totals, the change from the previous period, per-day totals and the summary sections
+fn write_report_body<Tz: TimeZone>(out: &mut String, report: &Report, tz: &Tz, level: usize)
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let totals = &report.totals;
+    let changes = &report.changes;
+    let _ = writeln!(
+        out,
+        "- **Focused time:** {} ({})",
+        duration(totals.focused_seconds),
+        signed_duration(changes.focused_seconds)
+    );
+    let _ = writeln!(
+        out,
+        "- **Focus score:** {}/100 ({:+})",
+        totals.focus_score, changes.focus_score
+    );
+    let _ = writeln!(
+        out,
+        "- **Events:** {} ({:+})",
+        totals.event_count, changes.event_count
+    );
+    let _ = writeln!(
+        out,
+        "- **Meetings:** {}, {} ({})",
+        totals.meeting_count,
+        duration(totals.meeting_minutes as u64 * 60),
+        signed_duration(changes.meeting_minutes * 60)
+    );
+    if !changes.new_projects.is_empty() {
+        let _ = writeln!(
+            out,
+            "- **New projects:** {}",
+            changes.new_projects.join(", ")
+        );
+    }
+    if !changes.dropped_projects.is_empty() {
+        let _ = writeln!(
+            out,
+            "- **Dropped projects:** {}",
+            changes.dropped_projects.join(", ")
+        );
+    }
+    let _ = writeln!(
+        out,
+        "\nChanges are from {}.{}\n",
+        report.previous.period,
+        if report.complete {
+            ""
+        } else {
+            " The period isn't over yet."
+        }
+    );
+
+    if !report.days.is_empty() {
+        let _ = writeln!(out, "{} Days\n", "#".repeat(level));
+        out.push_str("| Day | Focused | Focus score | Events | Meetings |\n");
+        out.push_str("|---|---|---|---|---|\n");
+        for day in &report.days {
+            let ReportTotals {
+                focused_seconds,
+                focus_score,
+                event_count,
+                meeting_count,
+                ..
+            } = &day.totals;
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                day.date.format("%a %Y-%m-%d"),
+                duration(*focused_seconds),
+                focus_score,
+                event_count,
+                meeting_count
+            );
+        }
+        out.push('\n');
+    }
+
+    if let Some(summary) = &report.summary {
+        write_sections(out, summary, tz, level);
+    }
+}
+
+/// Projects, apps, meetings, key activities and notes, skipping empty ones
+fn write_sections<Tz: TimeZone>(out: &mut String, summary: &SessionSummary, tz: &Tz, level: usize)
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let heading = "#".repeat(level);
+    let start = summary.start_time.with_timezone(tz).date_naive();
+    let end = summary.end_time.with_timezone(tz).date_naive();
+    // Times need a day when the summary spans more than one
+    let time_format = if (end - start).num_days() > 1 {
+        "%a %H:%M"
+    } else {
+        "%H:%M"
+    };
+    let time = |at: DateTime<Utc>| at.with_timezone(tz).format(time_format).to_string();
+
+    if !summary.projects.is_empty() {
+        let _ = writeln!(out, "{} Projects\n", heading);
+        out.push_str("| Project | Time | Events | File changes | Commands |\n");
+        out.push_str("|---|---|---|---|---|\n");
+        for project in &summary.projects {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                cell(&project.name),
+                duration(project.duration_seconds),
+                project.event_count,
+                project.file_changes,
+                project.commands_run
+            );
+        }
+        out.push('\n');
+    }
+
+    if !summary.applications.is_empty() {
+        let _ = writeln!(out, "{} Apps\n", heading);
+        out.push_str("| App | Time | Category |\n");
+        out.push_str("|---|---|---|\n");
+        for app in summary.applications.iter().take(MAX_EXPORTED_APPS) {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} |",
+                cell(&app.app_name),
+                duration(app.duration_seconds),
+                cell(&app.category)
+            );
+        }
+        let more = summary.applications.len().saturating_sub(MAX_EXPORTED_APPS);
+        if more > 0 {
+            let _ = writeln!(out, "\nAnd {} more.", more);
+        }
+        out.push('\n');
+    }
+
+    if !summary.meetings.is_empty() {
+        let _ = writeln!(out, "{} Meetings\n", heading);
+        for meeting in &summary.meetings {
+            let title = meeting
+                .title
+                .as_deref()
+                .map(|t| format!(": {}", line(t)))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "- {} {}{} ({})",
+                time(meeting.started_at),
+                meeting.platform,
+                title,
+                duration(meeting.duration_minutes as u64 * 60)
+            );
+        }
+        out.push('\n');
+    }
+
+    // Oldest first, which reads better than the summary's newest first
+    let (notes, activities): (Vec<_>, Vec<_>) = summary
+        .key_activities
+        .iter()
+        .rev()
+        .partition(|a| a.activity_type == "note" || a.activity_type == "mark");
+
+    if !activities.is_empty() {
+        let _ = writeln!(out, "{} Key activities\n", heading);
+        for activity in activities {
+            let _ = writeln!(
+                out,
+                "- {} {}",
+                time(activity.timestamp),
+                line(&activity.description)
+            );
+        }
+        out.push('\n');
+    }
+
+    if !notes.is_empty() {
+        let _ = writeln!(out, "{} Notes\n", heading);
+        for note in notes {
+            let text = note
+                .description
+                .strip_prefix("Note: ")
+                .unwrap_or(&note.description);
+            let _ = writeln!(out, "- {} {}", time(note.timestamp), line(text));
+        }
+        out.push('\n');
+    }
+}
+
+/// Single trailing newline
+fn finish(out: String) -> String {
+    format!("{}\n", out.trim_end())
+}
+
+/// `1h 5m`, or `45m` under an hour
+fn duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Like `duration` with a sign, e.g. `+45m` or `-1h 5m`
+fn signed_duration(seconds: i64) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    format!("{}{}", sign, duration(seconds.unsigned_abs()))
+}
+
+/// Text on one line
+fn line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Text safe to put in a table cell
+fn cell(text: &str) -> String {
+    line(text).replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reports::{PreviousPeriod, ReportChanges};
+    use crate::summary::{ActivitySummary, AppUsageSummary, MeetingSummary, ProjectSummary};
+    use chrono::{Duration, FixedOffset};
+    use std::collections::HashMap;
+
+    fn summary() -> SessionSummary {
+        let start = Utc.with_ymd_and_hms(2026, 10, 14, 7, 0, 0).unwrap();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        SessionSummary {
+            start_time: start,
+            end_time: at(8 * 60),
+            duration_minutes: 8 * 60,
+            total_events: 120,
+            events_by_source: HashMap::from([
+                ("shell".to_string(), 80),
+                ("editor".to_string(), 40),
+            ]),
+            projects: vec![ProjectSummary {
+                name: "siphon".to_string(),
+                event_count: 100,
+                duration_minutes: 185,
+                duration_seconds: 185 * 60,
+                file_changes: 12,
+                commands_run: 30,
+            }],
+            applications: vec![AppUsageSummary {
+                app_name: "Code | Insiders".to_string(),
+                duration_minutes: 200,
+                duration_seconds: 200 * 60,
+                window_switches: 20,
+                category: "coding".to_string(),
+            }],
+            time_by_category: HashMap::from([("coding".to_string(), 200 * 60)]),
+            key_activities: vec![
+                ActivitySummary {
+                    activity_type: "commit".to_string(),
+                    description: "Made a git commit".to_string(),
+                    timestamp: at(240),
+                },
+                ActivitySummary {
+                    activity_type: "note".to_string(),
+                    description: "Note: Parser is slow #perf".to_string(),
+                    timestamp: at(90),
+                },
+                ActivitySummary {
+                    activity_type: "test".to_string(),
+                    description: "Ran tests".to_string(),
+                    timestamp: at(30),
+                },
+            ],
+            tasks: Vec::new(),
+            meetings: vec![MeetingSummary {
+                platform: "Zoom".to_string(),
+                title: Some("Standup".to_string()),
+                duration_minutes: 15,
+                started_at: at(120),
+            }],
+            focus_score: 78,
+            focus_breakdown: Default::default(),
+            hourly_focus: Vec::new(),
+            summary_text: String::new(),
+        }
+    }
+
+    fn report() -> Report {
+        let summary = summary();
+        Report {
+            kind: ReportKind::Daily,
+            period: "2026-10-14".to_string(),
+            start: summary.start_time,
+            end: summary.end_time,
+            complete: true,
+            generated_at: summary.end_time,
+            totals: ReportTotals {
+                focused_seconds: 200 * 60,
+                event_count: 120,
+                meeting_count: 1,
+                meeting_minutes: 15,
+                focus_score: 78,
+                time_by_category: summary.time_by_category.clone(),
+                projects: vec!["siphon".to_string()],
+            },
+            summary: Some(summary),
+            days: Vec::new(),
+            previous: PreviousPeriod {
+                period: "2026-10-13".to_string(),
+                totals: ReportTotals::default(),
+            },
+            changes: ReportChanges {
+                focused_seconds: -45 * 60,
+                event_count: 20,
+                meeting_minutes: 15,
+                focus_score: 3,
+                new_projects: vec!["siphon".to_string()],
+                dropped_projects: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_summary_markdown() {
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let markdown = summary_markdown(&summary(), &tz);
+
+        assert!(markdown.starts_with("# Siphon summary: 2026-10-14 09:00 – 17:00\n"));
+        assert!(markdown.contains("- **Focused time:** 3h 20m\n"));
+        assert!(markdown.contains("| siphon | 3h 5m | 100 | 12 | 30 |\n"));
+        assert!(markdown.contains("| Code \\| Insiders | 3h 20m | coding |\n"));
+        assert!(markdown.contains("## Meetings\n\n- 11:00 Zoom: Standup (15m)\n"));
+        // Oldest first, notes on their own
+        assert!(markdown
+            .contains("## Key activities\n\n- 09:30 Ran tests\n- 13:00 Made a git commit\n"));
+        assert!(markdown.contains("## Notes\n\n- 10:30 Parser is slow #perf\n"));
+        assert!(markdown.ends_with("#perf\n"));
+    }
+
+    #[test]
+    fn test_report_markdown_and_block() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let report = report();
+
+        let markdown = report_markdown(&report, &tz);
+        assert!(markdown.starts_with("# Daily report: 2026-10-14\n\n"));
+        assert!(markdown.contains("- **Focused time:** 3h 20m (-45m)\n"));
+        assert!(markdown.contains("- **Focus score:** 78/100 (+3)\n"));
+        assert!(markdown.contains("- **New projects:** siphon\n"));
+        assert!(markdown.contains("Changes are from 2026-10-13.\n"));
+        assert!(markdown.contains("\n## Projects\n"));
+
+        let block = daily_note_block(&report, &tz);
+        assert!(block.starts_with("## Siphon\n\n- **Focused time:**"));
+        assert!(block.contains("\n### Projects\n"));
+        assert!(!block.contains("\n## Projects"));
+    }
+
+    #[test]
+    fn test_json_is_versioned_and_stable() {
+        let summary = summary();
+        let json = summary_json(&summary);
+        assert_eq!(json["version"], EXPORT_VERSION);
+        assert_eq!(json["kind"], "summary");
+
+        let text = serde_json::to_string(&json).unwrap();
+        assert_eq!(
+            text,
+            serde_json::to_string(&summary_json(&summary)).unwrap()
+        );
+        // Map keys are sorted
+        assert!(text.find("\"editor\":40").unwrap() < text.find("\"shell\":80").unwrap());
+
+        let json = report_json(&report());
+        assert_eq!(json["kind"], "daily");
+        assert_eq!(json["report"]["period"], "2026-10-14");
+    }
+
+    #[test]
+    fn test_upsert_block() {
+        let block = "## Siphon\n\n- **Focused time:** 1h 0m\n";
+
+        // Appended to a note without one, after a blank line
+        let note = upsert_block("# Wednesday\n\nTodo: call Sam\n", block);
+        assert_eq!(
+            note,
+            "# Wednesday\n\nTodo: call Sam\n\n## Siphon\n\n- **Focused time:** 1h 0m\n"
+        );
+        assert_eq!(upsert_block(&note, block), note);
+        assert_eq!(upsert_block("", block), block);
+
+        // Replaced in place, keeping the sections after it
+        let note =
+            "# Wednesday\n\n## Siphon\n\nold stats\n### Projects\nold\n\n## Journal\nWent well\n";
+        let updated = upsert_block(note, block);
+        assert_eq!(
+            updated,
+            "# Wednesday\n\n## Siphon\n\n- **Focused time:** 1h 0m\n\n## Journal\nWent well\n"
+        );
+        assert_eq!(upsert_block(&updated, block), updated);
+    }
+
+    #[test]
+    fn test_write_daily_note() {
+        let vault = std::env::temp_dir().join(format!("siphon-vault-{}", uuid::Uuid::new_v4()));
+        let config = ObsidianConfig {
+            vault: Some(vault.clone()),
+            daily_folder: PathBuf::from("Daily"),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let block = "## Siphon\n\nstats\n";
+
+        let (path, changed) = write_daily_note(&config, date, block).unwrap();
+        assert_eq!(path, vault.join("Daily").join("2026-10-14.md"));
+        assert!(changed);
+        let (_, changed) = write_daily_note(&config, date, block).unwrap();
+        assert!(!changed);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), block);
+
+        assert!(write_daily_note(&ObsidianConfig::default(), date, block).is_err());
+        std::fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn test_filename_format_is_validated() {
+        let config: ObsidianConfig =
+            toml::from_str("vault = \"/notes\"\nfilename_format = \"%d.%m.%Y\"").unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        assert_eq!(
+            config.daily_note_path(date).unwrap(),
+            PathBuf::from("/notes/14.10.2026.md")
+        );
+
+        // Would panic when formatted
+        let err = toml::from_str::<ObsidianConfig>("filename_format = \"%Y-%Q\"").unwrap_err();
+        assert!(err.to_string().contains("invalid date format"));
+        assert!(toml::from_str::<ObsidianConfig>("filename_format = \"%Y-%\"").is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//! Focus sessions
+//!
+//! A focus session is an explicit "heads down" period, started and stopped
+//! from the API or the toggle-focus hotkey, with optional goal text and a
+//! target duration. While one is running, switching into a Communication app
+//! or joining a meeting counts as an interruption. When the session ends it
+//! gets a focus score from the same rules as session summaries, minus a
+//! penalty per interruption, and is stored in the `focus_sessions` table.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::idle::ActivityCategory;
+use crate::storage::EventStore;
+use crate::summary::SummaryGenerator;
+
+/// Points taken off a session's focus score per interruption
+const INTERRUPTION_PENALTY: u32 = 5;
+
+/// Most points interruptions can take off a session's focus score
+const MAX_INTERRUPTION_PENALTY: u32 = 30;
+
+/// What pulled attention away during a focus session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptionKind {
+    /// Switched into a Communication app (chat, email)
+    Communication,
+    /// A meeting started
+    Meeting,
+}
+
+/// A single interruption
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interruption {
+    pub kind: InterruptionKind,
+    pub timestamp: DateTime<Utc>,
+    /// App name or meeting platform
+    pub source: String,
+}
+
+/// A focus session, running or completed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    /// None while the session is running
+    pub ended_at: Option<DateTime<Utc>>,
+    /// What the session is for
+    pub goal: Option<String>,
+    /// Planned length; the session ends on its own once reached
+    pub target_minutes: Option<u32>,
+    pub interruptions: Vec<Interruption>,
+    /// Set when the session completes
+    pub focus_score: Option<u32>,
+}
+
+impl FocusSession {
+    fn new(goal: Option<String>, target_minutes: Option<u32>, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            started_at: now,
+            ended_at: None,
+            goal,
+            target_minutes,
+            interruptions: Vec::new(),
+            focus_score: None,
+        }
+    }
+
+    /// Whole minutes from start to end, or to `now` while running
+    pub fn duration_minutes(&self, now: DateTime<Utc>) -> u32 {
+        let end = self.ended_at.unwrap_or(now);
+        (end - self.started_at).num_minutes().max(0) as u32
+    }
+
+    /// When the target duration is reached, if one was set
+    pub fn target_end(&self) -> Option<DateTime<Utc>> {
+        self.target_minutes
+            .map(|minutes| self.started_at + Duration::minutes(minutes as i64))
+    }
+
+    /// Whether the session lasted (or has lasted) its full target
+    pub fn target_reached(&self, now: DateTime<Utc>) -> bool {
+        self.target_end()
+            .is_some_and(|target| self.ended_at.unwrap_or(now) >= target)
+    }
+}
+
+/// Why a session couldn't be started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusError {
+    /// Another session is already running
+    AlreadyActive(String),
+}
+
+impl std::fmt::Display for FocusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FocusError::AlreadyActive(id) => write!(f, "focus session {} is already active", id),
+        }
+    }
+}
+
+/// Tracks the running focus session and its interruptions
+#[derive(Debug, Default)]
+pub struct FocusTracker {
+    active: Option<FocusSession>,
+    /// Whether the focused window is a Communication app
+    in_communication: bool,
+    in_meeting: bool,
+}
+
+impl FocusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The running session, if any
+    pub fn active(&self) -> Option<&FocusSession> {
+        self.active.as_ref()
+    }
+
+    /// Start a session
+    pub fn start(
+        &mut self,
+        goal: Option<String>,
+        target_minutes: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> Result<&FocusSession, FocusError> {
+        if let Some(ref session) = self.active {
+            return Err(FocusError::AlreadyActive(session.id.clone()));
+        }
+        let goal = goal.map(|g| g.trim().to_string()).filter(|g| !g.is_empty());
+        let target_minutes = target_minutes.filter(|m| *m > 0);
+        Ok(self
+            .active
+            .insert(FocusSession::new(goal, target_minutes, now)))
+    }
+
+    /// End the running session, returning it without a score
+    pub fn stop(&mut self, now: DateTime<Utc>) -> Option<FocusSession> {
+        let mut session = self.active.take()?;
+        session.ended_at = Some(now.max(session.started_at));
+        Some(session)
+    }
+
+    /// Stop the running session, or start one without a goal or target.
+    /// Returns the session that was stopped.
+    pub fn toggle(&mut self, now: DateTime<Utc>) -> Option<FocusSession> {
+        if self.active.is_some() {
+            self.stop(now)
+        } else {
+            let _ = self.start(None, None, now);
+            None
+        }
+    }
+
+    /// End the running session at its target time once that has passed
+    pub fn check_target(&mut self, now: DateTime<Utc>) -> Option<FocusSession> {
+        let target = self.active.as_ref()?.target_end()?;
+        if now >= target {
+            self.stop(target)
+        } else {
+            None
+        }
+    }
+
+    /// Note a window change; moving into a Communication app is an interruption
+    pub fn record_window(
+        &mut self,
+        app_name: &str,
+        category: &ActivityCategory,
+        now: DateTime<Utc>,
+    ) {
+        let in_communication = *category == ActivityCategory::Communication;
+        if in_communication && !self.in_communication {
+            self.interrupt(InterruptionKind::Communication, app_name, now);
+        }
+        self.in_communication = in_communication;
+    }
+
+    /// Note the meeting state; a meeting starting is an interruption
+    pub fn set_in_meeting(&mut self, in_meeting: bool, platform: &str, now: DateTime<Utc>) {
+        if in_meeting && !self.in_meeting {
+            self.interrupt(InterruptionKind::Meeting, platform, now);
+        }
+        self.in_meeting = in_meeting;
+    }
+
+    fn interrupt(&mut self, kind: InterruptionKind, source: &str, now: DateTime<Utc>) {
+        if let Some(ref mut session) = self.active {
+            session.interruptions.push(Interruption {
+                kind,
+                timestamp: now,
+                source: source.to_string(),
+            });
+        }
+    }
+}
+
+/// Combine the activity-based score with the session's interruptions
+pub fn session_score(base_score: u32, interruptions: usize) -> u32 {
+    let penalty = (interruptions as u32)
+        .saturating_mul(INTERRUPTION_PENALTY)
+        .min(MAX_INTERRUPTION_PENALTY);
+    base_score.saturating_sub(penalty)
+}
+
+/// Score a stopped session from the activity recorded during it, then store it
+pub fn complete_session(
+    mut session: FocusSession,
+    store: &EventStore,
+    generator: &SummaryGenerator,
+) -> rusqlite::Result<FocusSession> {
+    let end = session.ended_at.unwrap_or_else(Utc::now);
+    let base_score = generator.focus_score(store, session.started_at, end);
+    session.ended_at = Some(end);
+    session.focus_score = Some(session_score(base_score, session.interruptions.len()));
+    store.save_focus_session(&session)?;
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_stop_and_toggle() {
+        let now = Utc::now();
+        let mut tracker = FocusTracker::new();
+
+        tracker
+            .start(Some("  write parser  ".to_string()), Some(0), now)
+            .unwrap();
+        let session = tracker.active().unwrap();
+        assert_eq!(session.goal.as_deref(), Some("write parser"));
+        assert_eq!(session.target_minutes, None);
+        assert!(matches!(
+            tracker.start(None, None, now),
+            Err(FocusError::AlreadyActive(_))
+        ));
+
+        let stopped = tracker.stop(now + Duration::minutes(25)).unwrap();
+        assert_eq!(stopped.duration_minutes(Utc::now()), 25);
+        assert!(tracker.active().is_none());
+        assert!(tracker.stop(now).is_none());
+
+        assert!(tracker.toggle(now).is_none());
+        assert!(tracker.active().is_some());
+        assert!(tracker.toggle(now).is_some());
+        assert!(tracker.active().is_none());
+    }
+
+    #[test]
+    fn test_interruptions_counted_on_transitions() {
+        let now = Utc::now();
+        let mut tracker = FocusTracker::new();
+
+        // Switches before the session starts don't count
+        tracker.record_window("Slack", &ActivityCategory::Communication, now);
+        tracker.start(None, None, now).unwrap();
+        tracker.record_window("Slack", &ActivityCategory::Communication, now);
+        tracker.record_window("Code", &ActivityCategory::Coding, now);
+        tracker.record_window("Mail", &ActivityCategory::Communication, now);
+        tracker.record_window("Slack", &ActivityCategory::Communication, now);
+        tracker.set_in_meeting(true, "zoom", now);
+        tracker.set_in_meeting(true, "zoom", now);
+
+        let session = tracker.stop(now).unwrap();
+        let kinds: Vec<_> = session.interruptions.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![InterruptionKind::Communication, InterruptionKind::Meeting]
+        );
+        assert_eq!(session.interruptions[0].source, "Mail");
+    }
+
+    #[test]
+    fn test_target_ends_session() {
+        let now = Utc::now();
+        let mut tracker = FocusTracker::new();
+        tracker.start(None, Some(25), now).unwrap();
+
+        assert!(tracker.check_target(now + Duration::minutes(10)).is_none());
+        let session = tracker.check_target(now + Duration::minutes(30)).unwrap();
+        assert_eq!(session.ended_at, Some(now + Duration::minutes(25)));
+        assert!(session.target_reached(Utc::now()));
+    }
+
+    #[test]
+    fn test_session_score_penalty_is_capped() {
+        assert_eq!(session_score(90, 0), 90);
+        assert_eq!(session_score(90, 2), 80);
+        assert_eq!(session_score(90, 100), 60);
+        assert_eq!(session_score(10, 5), 0);
+    }
+}
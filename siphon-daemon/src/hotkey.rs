@@ -2,13 +2,19 @@
 //!
 //! Provides global hotkey support for "mark this moment" functionality.
 //! Allows users to manually flag important moments during their work.
+//!
+//! Every action can be bound from the `[hotkeys]` section of the config file
+//! with strings like `"ctrl+alt+n"` or `"CmdOrCtrl+Shift+M"`. Each binding is
+//! parsed, checked against the others, and registered on its own, and the
+//! outcome is kept so `GET /hotkeys` can say why a binding isn't active.
 
 use chrono::{DateTime, Utc};
 use global_hotkey::{
-    hotkey::{Code, HotKey, Modifiers},
+    hotkey::{HotKey, Modifiers},
     GlobalHotKeyEvent, GlobalHotKeyManager,
 };
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::OnceLock;
 use tracing::{debug, error, info, warn};
@@ -16,16 +22,11 @@ use tracing::{debug, error, info, warn};
 /// Global channel for hotkey events (set up once at startup)
 static HOTKEY_SENDER: OnceLock<Sender<HotkeyTrigger>> = OnceLock::new();
 
-/// Default hotkey: Cmd+Shift+M (macOS) or Ctrl+Shift+M (Windows/Linux)
-#[cfg(target_os = "macos")]
-const DEFAULT_MODIFIERS: Modifiers = Modifiers::SUPER.union(Modifiers::SHIFT);
-#[cfg(not(target_os = "macos"))]
-const DEFAULT_MODIFIERS: Modifiers = Modifiers::CONTROL.union(Modifiers::SHIFT);
-
-const DEFAULT_KEY: Code = Code::KeyM;
+/// Default mark moment hotkey: Cmd+Shift+M (macOS) or Ctrl+Shift+M (Windows/Linux)
+const DEFAULT_MARK_MOMENT: &str = "CmdOrCtrl+Shift+M";
 
 /// Types of hotkey triggers
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum HotkeyAction {
     /// Mark the current moment as important
@@ -53,27 +54,123 @@ pub struct HotkeyTrigger {
     pub action: HotkeyAction,
     /// When it was triggered
     pub timestamp: DateTime<Utc>,
-    /// Optional note or context
+    /// Optional note or context, added later via `PATCH /notes/{id}`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
-/// Configuration for hotkey system
-#[derive(Debug, Clone)]
+/// Configuration for hotkey system (`[hotkeys]` in the config file)
+///
+/// Each action takes a hotkey string; unset or empty leaves it unbound.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct HotkeyConfig {
     /// Whether hotkeys are enabled
     pub enabled: bool,
-    /// Custom hotkey for mark moment (if None, uses default)
-    pub mark_moment_hotkey: Option<HotKey>,
+    pub mark_moment: Option<String>,
+    pub toggle_focus: Option<String>,
+    pub quick_note: Option<String>,
 }
 
 impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
             enabled: true,
-            mark_moment_hotkey: None,
+            mark_moment: Some(DEFAULT_MARK_MOMENT.to_string()),
+            toggle_focus: None,
+            quick_note: None,
+        }
+    }
+}
+
+impl HotkeyConfig {
+    /// Configured (action, hotkey string) pairs, skipping unbound actions
+    pub fn bindings(&self) -> Vec<(HotkeyAction, &str)> {
+        [
+            (HotkeyAction::MarkMoment, &self.mark_moment),
+            (HotkeyAction::ToggleFocus, &self.toggle_focus),
+            (HotkeyAction::QuickNote, &self.quick_note),
+        ]
+        .into_iter()
+        .filter_map(|(action, hotkey)| {
+            hotkey
+                .as_deref()
+                .map(str::trim)
+                .filter(|h| !h.is_empty())
+                .map(|h| (action, h))
+        })
+        .collect()
+    }
+}
+
+/// Outcome of setting up one binding
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingStatus {
+    Registered,
+    /// The hotkey string couldn't be parsed
+    Invalid,
+    /// Another action already uses the same keys
+    Conflict,
+    /// The OS refused it (usually taken by another app)
+    Failed,
+}
+
+/// A configured hotkey and what happened to it
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyBinding {
+    pub action: HotkeyAction,
+    /// As written in the config
+    pub hotkey: String,
+    /// Normalised, e.g. "Ctrl+Shift+M"; absent if unparseable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub status: BindingStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Parse bindings and detect conflicts; returns every binding plus the hotkeys to register
+///
+/// When two actions share the same keys, the first one keeps them.
+fn plan_bindings(config: &HotkeyConfig) -> (Vec<HotkeyBinding>, Vec<(HotKey, HotkeyAction)>) {
+    let mut bindings = Vec::new();
+    let mut to_register: Vec<(HotKey, HotkeyAction)> = Vec::new();
+
+    for (action, text) in config.bindings() {
+        let mut binding = HotkeyBinding {
+            action,
+            hotkey: text.to_string(),
+            description: None,
+            status: BindingStatus::Invalid,
+            error: None,
+        };
+
+        match HotKey::from_str(text) {
+            Err(e) => binding.error = Some(e.to_string()),
+            Ok(hotkey) => {
+                binding.description = Some(hotkey_description(&hotkey));
+                match to_register
+                    .iter()
+                    .find(|(other, _)| other.id() == hotkey.id())
+                {
+                    Some((_, other_action)) => {
+                        binding.status = BindingStatus::Conflict;
+                        binding.error = Some(format!("already bound to {}", other_action));
+                    }
+                    None => {
+                        binding.status = BindingStatus::Registered;
+                        to_register.push((hotkey, action));
+                    }
+                }
+            }
         }
+        bindings.push(binding);
     }
+
+    (bindings, to_register)
 }
 
 /// Manages global hotkey registration and events
@@ -81,6 +178,7 @@ pub struct HotkeyManager {
     manager: Option<GlobalHotKeyManager>,
     receiver: Option<Receiver<HotkeyTrigger>>,
     registered_hotkeys: Vec<(HotKey, HotkeyAction)>,
+    bindings: Vec<HotkeyBinding>,
 }
 
 impl HotkeyManager {
@@ -95,6 +193,7 @@ impl HotkeyManager {
                 manager: None,
                 receiver: None,
                 registered_hotkeys: Vec::new(),
+                bindings: Vec::new(),
             };
         }
 
@@ -107,6 +206,7 @@ impl HotkeyManager {
                     manager: None,
                     receiver: None,
                     registered_hotkeys: Vec::new(),
+                    bindings: Vec::new(),
                 };
             }
         };
@@ -115,27 +215,53 @@ impl HotkeyManager {
         let (tx, rx) = mpsc::channel();
         let _ = HOTKEY_SENDER.set(tx);
 
-        // Register default hotkey
-        let mark_hotkey = config
-            .mark_moment_hotkey
-            .unwrap_or_else(|| HotKey::new(Some(DEFAULT_MODIFIERS), DEFAULT_KEY));
+        let (mut bindings, planned) = plan_bindings(&config);
+        for binding in bindings
+            .iter()
+            .filter(|b| b.status != BindingStatus::Registered)
+        {
+            warn!(
+                "Hotkey '{}' for {} not registered: {}",
+                binding.hotkey,
+                binding.action,
+                binding.error.as_deref().unwrap_or("unknown error")
+            );
+        }
 
+        // Register each binding on its own so one failure doesn't hide the others
         let mut registered = Vec::new();
-
-        if let Err(e) = manager.register(mark_hotkey) {
-            warn!("Failed to register mark moment hotkey: {}", e);
-        } else {
-            info!(
-                "Registered hotkey: {:?}+{:?} for mark_moment",
-                DEFAULT_MODIFIERS, DEFAULT_KEY
-            );
-            registered.push((mark_hotkey, HotkeyAction::MarkMoment));
+        for (hotkey, action) in planned {
+            let binding = bindings
+                .iter_mut()
+                .find(|b| b.action == action)
+                .expect("planned hotkeys come from bindings");
+            match manager.register(hotkey) {
+                Ok(()) => {
+                    info!(
+                        "Registered hotkey {} for {}",
+                        hotkey_description(&hotkey),
+                        action
+                    );
+                    registered.push((hotkey, action));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to register hotkey {} for {}: {}",
+                        hotkey_description(&hotkey),
+                        action,
+                        e
+                    );
+                    binding.status = BindingStatus::Failed;
+                    binding.error = Some(e.to_string());
+                }
+            }
         }
 
         Self {
             manager: Some(manager),
             receiver: Some(rx),
             registered_hotkeys: registered,
+            bindings,
         }
     }
 
@@ -164,9 +290,10 @@ impl HotkeyManager {
                     for (hotkey, action) in &registered {
                         if event.id == hotkey.id() {
                             let trigger = HotkeyTrigger {
-                                action: action.clone(),
+                                action: *action,
                                 timestamp: Utc::now(),
                                 note: None,
+                                tags: Vec::new(),
                             };
 
                             if let Some(sender) = HOTKEY_SENDER.get() {
@@ -204,6 +331,11 @@ impl HotkeyManager {
     pub fn registered_hotkeys(&self) -> &[(HotKey, HotkeyAction)] {
         &self.registered_hotkeys
     }
+
+    /// Every configured binding with its registration outcome
+    pub fn bindings(&self) -> &[HotkeyBinding] {
+        &self.bindings
+    }
 }
 
 /// Get a human-readable description of a hotkey
@@ -230,9 +362,13 @@ pub fn hotkey_description(hotkey: &HotKey) -> String {
         parts.push("Shift");
     }
 
-    // Add key code
+    // Add key code ("KeyM" -> "M", "Digit1" -> "1")
     let key_str = format!("{:?}", hotkey.key);
-    parts.push(&key_str);
+    let key_str = key_str
+        .strip_prefix("Key")
+        .or_else(|| key_str.strip_prefix("Digit"))
+        .unwrap_or(&key_str);
+    parts.push(key_str);
 
     parts.join("+")
 }
@@ -245,7 +381,65 @@ mod tests {
     fn test_hotkey_config_default() {
         let config = HotkeyConfig::default();
         assert!(config.enabled);
-        assert!(config.mark_moment_hotkey.is_none());
+        assert_eq!(
+            config.bindings(),
+            vec![(HotkeyAction::MarkMoment, DEFAULT_MARK_MOMENT)]
+        );
+    }
+
+    #[test]
+    fn test_plan_bindings() {
+        let config = HotkeyConfig {
+            enabled: true,
+            mark_moment: Some("ctrl+shift+m".to_string()),
+            toggle_focus: Some("ctrl+alt+f".to_string()),
+            quick_note: Some("ctrl+alt+n".to_string()),
+        };
+        let (bindings, planned) = plan_bindings(&config);
+
+        assert_eq!(planned.len(), 3);
+        assert!(bindings
+            .iter()
+            .all(|b| b.status == BindingStatus::Registered));
+        assert_eq!(bindings[0].description.as_deref(), Some("Ctrl+Shift+M"));
+        assert_eq!(bindings[2].description.as_deref(), Some("Ctrl+Alt+N"));
+    }
+
+    #[test]
+    fn test_plan_bindings_reports_invalid_and_conflicts() {
+        let config = HotkeyConfig {
+            enabled: true,
+            mark_moment: Some("ctrl+shift+m".to_string()),
+            // Same keys, different spelling and order of modifiers
+            toggle_focus: Some("Shift+Control+KeyM".to_string()),
+            quick_note: Some("ctrl+banana".to_string()),
+        };
+        let (bindings, planned) = plan_bindings(&config);
+
+        assert_eq!(
+            planned,
+            vec![(
+                HotKey::from_str("ctrl+shift+m").unwrap(),
+                HotkeyAction::MarkMoment
+            )]
+        );
+        assert_eq!(bindings[1].status, BindingStatus::Conflict);
+        assert_eq!(
+            bindings[1].error.as_deref(),
+            Some("already bound to mark_moment")
+        );
+        assert_eq!(bindings[2].status, BindingStatus::Invalid);
+        assert!(bindings[2].description.is_none());
+        assert!(bindings[2].error.is_some());
+    }
+
+    #[test]
+    fn test_empty_binding_is_unbound() {
+        let config = HotkeyConfig {
+            mark_moment: Some(" ".to_string()),
+            ..Default::default()
+        };
+        assert!(config.bindings().is_empty());
     }
 
     #[test]
@@ -4,16 +4,28 @@
 //! Useful for understanding work sessions and breaks.
 //!
 //! On macOS, this module can also detect system-level idle time
-//! (time since last mouse/keyboard input) using IOKit.
+//! (time since last mouse/keyboard input) using IOKit; on Linux it uses
+//! logind, XScreenSaver or `ext-idle-notify` (see [`linux`]).
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, info};
 
+use crate::categories::{ActivityContext, CategoryRules};
+use crate::power::{PowerEvent, PowerEventKind};
+use crate::window::WindowInfo;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 /// Activity categories for better tracking
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+///
+/// Serialised as snake_case names; anything that isn't a built-in name
+/// becomes a [`ActivityCategory::Custom`] category from the config file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum ActivityCategory {
     /// Writing code, editing files
     Coding,
@@ -27,6 +39,10 @@ pub enum ActivityCategory {
     Creative,
     /// Terminal/shell commands
     Terminal,
+    /// Video, social media and other leisure sites
+    Entertainment,
+    /// User-defined category from the categorisation rules
+    Custom(String),
     /// Other/unknown activity
     Other,
 }
@@ -45,75 +61,52 @@ impl ActivityCategory {
         }
     }
 
-    /// Categorize based on application name
+    /// Categorize based on application name, using the built-in rules
     pub fn from_app_name(app_name: &str) -> Self {
-        let app_lower = app_name.to_lowercase();
-
-        // IDEs and code editors
-        if app_lower.contains("code")
-            || app_lower.contains("vim")
-            || app_lower.contains("emacs")
-            || app_lower.contains("idea")
-            || app_lower.contains("xcode")
-            || app_lower.contains("sublime")
-            || app_lower.contains("atom")
-            || app_lower.contains("cursor")
-        {
-            return ActivityCategory::Coding;
-        }
-
-        // Communication tools
-        if app_lower.contains("slack")
-            || app_lower.contains("teams")
-            || app_lower.contains("mail")
-            || app_lower.contains("outlook")
-            || app_lower.contains("messages")
-            || app_lower.contains("discord")
-        {
-            return ActivityCategory::Communication;
-        }
+        CategoryRules::builtin_ref().categorize_app(app_name)
+    }
 
-        // Meeting apps
-        if app_lower.contains("zoom")
-            || app_lower.contains("meet")
-            || app_lower.contains("facetime")
-            || app_lower.contains("webex")
-        {
-            return ActivityCategory::Meeting;
-        }
-
-        // Creative apps
-        if app_lower.contains("photoshop")
-            || app_lower.contains("illustrator")
-            || app_lower.contains("figma")
-            || app_lower.contains("sketch")
-            || app_lower.contains("blender")
-            || app_lower.contains("premiere")
-        {
-            return ActivityCategory::Creative;
+    /// The snake_case name used in summaries and config
+    pub fn name(&self) -> &str {
+        match self {
+            ActivityCategory::Coding => "coding",
+            ActivityCategory::Meeting => "meeting",
+            ActivityCategory::Communication => "communication",
+            ActivityCategory::Research => "research",
+            ActivityCategory::Creative => "creative",
+            ActivityCategory::Terminal => "terminal",
+            ActivityCategory::Entertainment => "entertainment",
+            ActivityCategory::Custom(name) => name,
+            ActivityCategory::Other => "other",
         }
+    }
+}
 
-        // Browsers (could be research or distraction)
-        if app_lower.contains("chrome")
-            || app_lower.contains("safari")
-            || app_lower.contains("firefox")
-            || app_lower.contains("arc")
-            || app_lower.contains("edge")
-        {
-            return ActivityCategory::Research;
+impl From<String> for ActivityCategory {
+    fn from(name: String) -> Self {
+        match name.to_lowercase().as_str() {
+            "coding" => ActivityCategory::Coding,
+            "meeting" => ActivityCategory::Meeting,
+            "communication" => ActivityCategory::Communication,
+            "research" => ActivityCategory::Research,
+            "creative" => ActivityCategory::Creative,
+            "terminal" => ActivityCategory::Terminal,
+            "entertainment" => ActivityCategory::Entertainment,
+            "other" => ActivityCategory::Other,
+            _ => ActivityCategory::Custom(name),
         }
+    }
+}
 
-        // Terminal
-        if app_lower.contains("terminal")
-            || app_lower.contains("iterm")
-            || app_lower.contains("warp")
-            || app_lower.contains("alacritty")
-            || app_lower.contains("kitty")
-        {
-            return ActivityCategory::Terminal;
-        }
+impl From<ActivityCategory> for String {
+    fn from(category: ActivityCategory) -> Self {
+        category.name().to_string()
+    }
+}
 
-        ActivityCategory::Other
+impl std::fmt::Display for ActivityCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
     }
 }
 
@@ -124,7 +117,7 @@ pub struct IdleConfig {
     pub idle_threshold: Duration,
     /// Duration of inactivity before session ends (default: 30 minutes)
     pub session_end_threshold: Duration,
-    /// Whether to use system-level idle detection (macOS only)
+    /// Whether to use system-level idle detection (macOS and Linux)
     pub use_system_idle: bool,
     /// Ignore idle detection during meetings
     pub ignore_idle_in_meetings: bool,
@@ -160,7 +153,7 @@ pub struct IdleEventData {
     /// Activity category of last activity
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_activity_category: Option<ActivityCategory>,
-    /// Whether idle was detected via system-level (IOKit) or app-level
+    /// Whether idle was detected via system-level (IOKit, logind, X11, Wayland) or app-level
     #[serde(default)]
     pub system_idle: bool,
 }
@@ -215,6 +208,18 @@ pub struct IdleDetector {
     current_category: Option<ActivityCategory>,
     /// Track if session had meetings
     session_had_meetings: bool,
+    /// Rules used to categorise apps and windows
+    categories: Arc<CategoryRules>,
+    /// Sessions ended by inactivity, waiting to be persisted
+    completed_sessions: Vec<SessionData>,
+    /// Screen is locked
+    locked: bool,
+    /// Machine is asleep (between suspend and resume)
+    asleep: bool,
+    /// When the screen locked or the machine slept, whichever came first
+    away_since: Option<DateTime<Utc>>,
+    #[cfg(target_os = "linux")]
+    linux_backend: Option<linux::LinuxIdleBackend>,
 }
 
 /// Get system idle time on macOS using IOKit
@@ -248,7 +253,7 @@ pub fn get_system_idle_time() -> Option<Duration> {
     None
 }
 
-/// Fallback for non-macOS: no system idle detection
+/// Fallback for other platforms; Linux uses [`linux::LinuxIdleBackend`]
 #[cfg(not(target_os = "macos"))]
 pub fn get_system_idle_time() -> Option<Duration> {
     None
@@ -257,6 +262,18 @@ pub fn get_system_idle_time() -> Option<Duration> {
 impl IdleDetector {
     /// Create a new idle detector
     pub fn new(config: IdleConfig) -> Self {
+        Self::with_categories(config, Arc::new(CategoryRules::default()))
+    }
+
+    /// Create an idle detector using the given categorisation rules
+    pub fn with_categories(config: IdleConfig, categories: Arc<CategoryRules>) -> Self {
+        #[cfg(target_os = "linux")]
+        let linux_backend = if config.use_system_idle {
+            linux::LinuxIdleBackend::detect()
+        } else {
+            None
+        };
+
         Self {
             config,
             last_activity: Instant::now(),
@@ -273,6 +290,13 @@ impl IdleDetector {
             last_category_change: Instant::now(),
             current_category: None,
             session_had_meetings: false,
+            categories,
+            completed_sessions: Vec::new(),
+            locked: false,
+            asleep: false,
+            away_since: None,
+            #[cfg(target_os = "linux")]
+            linux_backend,
         }
     }
 
@@ -294,8 +318,7 @@ impl IdleDetector {
 
         // Record time for previous category
         if let Some(ref cat) = self.current_category {
-            let cat_name = format!("{:?}", cat).to_lowercase();
-            *self.category_time.entry(cat_name).or_insert(0) += elapsed;
+            *self.category_time.entry(cat.to_string()).or_insert(0) += elapsed;
         }
 
         self.current_category = new_category;
@@ -313,16 +336,34 @@ impl IdleDetector {
         activity_type: &str,
         app_name: Option<&str>,
     ) -> Option<IdleEventData> {
-        let now = Instant::now();
-        let now_utc = Utc::now();
-        let previous_state = self.current_state;
-
         // Determine activity category
         let category = if let Some(app) = app_name {
-            ActivityCategory::from_app_name(app)
+            self.categories.categorize_app(app)
         } else {
             ActivityCategory::from_activity_type(activity_type)
         };
+        self.record_categorized_activity(activity_type, category)
+    }
+
+    /// Record a window change, categorised by app, title and URL
+    pub fn record_window_activity(&mut self, window: &WindowInfo) -> Option<IdleEventData> {
+        let category = self.categories.categorize(&ActivityContext::from(window));
+        self.record_categorized_activity("window_change", category)
+    }
+
+    fn record_categorized_activity(
+        &mut self,
+        activity_type: &str,
+        category: ActivityCategory,
+    ) -> Option<IdleEventData> {
+        // Background events while locked or asleep aren't the user
+        if self.locked || self.asleep {
+            return None;
+        }
+
+        let now = Instant::now();
+        let now_utc = Utc::now();
+        let previous_state = self.current_state;
 
         // Update category time tracking
         self.update_category_time(Some(category.clone()));
@@ -381,6 +422,11 @@ impl IdleDetector {
 
     /// Check current idle status (call periodically)
     pub fn check_idle(&mut self) -> Option<IdleEventData> {
+        // Already away; wait for the unlock or resume
+        if self.locked || self.asleep {
+            return None;
+        }
+
         // If in a meeting and configured to ignore idle during meetings, stay active
         if self.in_meeting && self.config.ignore_idle_in_meetings {
             // Record implicit activity from being in a meeting
@@ -402,21 +448,27 @@ impl IdleDetector {
         let now_utc = Utc::now();
         let previous_state = self.current_state;
 
-        // Determine idle time - prefer system-level if available and configured
-        let (elapsed, using_system_idle) = if self.config.use_system_idle {
-            if let Some(system_idle) = get_system_idle_time() {
-                // Use system idle time if it's longer than our app-level tracking
-                let app_elapsed = now.duration_since(self.last_activity);
-                if system_idle > app_elapsed {
-                    (system_idle, true)
-                } else {
-                    (app_elapsed, false)
+        // Determine idle time - prefer system-level if available and configured.
+        // It sees all input, so scrolling through a long doc counts as
+        // activity even though the daemon gets no event for it.
+        let app_elapsed = now.duration_since(self.last_activity);
+        let system_idle = if self.config.use_system_idle {
+            self.system_idle_time()
+        } else {
+            None
+        };
+        let (elapsed, using_system_idle) = match system_idle {
+            Some(system_idle) => {
+                if system_idle < app_elapsed {
+                    if let Some(input_at) = now.checked_sub(system_idle) {
+                        self.last_activity = input_at;
+                    }
+                    self.last_activity_time =
+                        now_utc - chrono::Duration::from_std(system_idle).unwrap_or_default();
                 }
-            } else {
-                (now.duration_since(self.last_activity), false)
+                (system_idle, true)
             }
-        } else {
-            (now.duration_since(self.last_activity), false)
+            None => (app_elapsed, false),
         };
 
         let new_state = if elapsed >= self.config.session_end_threshold {
@@ -440,17 +492,26 @@ impl IdleDetector {
                 self.current_idle_start = Some(now_utc);
             }
 
-            // End session if away
-            if new_state == ActivityState::Away {
+            // Input seen only by the system ends the idle period
+            if new_state == ActivityState::Active {
                 if let Some(idle_start) = self.current_idle_start.take() {
-                    let duration = (now_utc - idle_start).num_seconds() as u64;
+                    let ended_at = self.last_activity_time.max(idle_start);
                     self.idle_periods.push(IdlePeriod {
                         started_at: idle_start,
-                        ended_at: now_utc,
-                        duration_seconds: duration,
+                        ended_at,
+                        duration_seconds: (ended_at - idle_start).num_seconds() as u64,
                     });
                 }
-                info!("Session ended due to inactivity");
+            }
+
+            // End session if away. It ends at the last activity, so the
+            // idle stretch leading up to this point isn't part of it.
+            if new_state == ActivityState::Away {
+                self.current_idle_start = None;
+                if let Some(session) = self.end_session() {
+                    info!("Session ended due to inactivity");
+                    self.completed_sessions.push(session);
+                }
             }
 
             Some(IdleEventData {
@@ -466,19 +527,100 @@ impl IdleDetector {
         }
     }
 
+    /// Handle a screen lock, suspend, unlock or resume
+    ///
+    /// Lock and suspend end the session at the moment they happen rather than
+    /// after the away threshold. Activity is ignored until the matching unlock
+    /// or resume; the wall-clock timestamps are used throughout since
+    /// `Instant` doesn't advance during sleep.
+    pub fn record_power_event(&mut self, event: &PowerEvent) -> Option<IdleEventData> {
+        let was_away = self.locked || self.asleep;
+        match event.kind {
+            PowerEventKind::Lock => self.locked = true,
+            PowerEventKind::Suspend => self.asleep = true,
+            PowerEventKind::Unlock => self.locked = false,
+            PowerEventKind::Resume => self.asleep = false,
+        }
+
+        if event.kind.is_away() {
+            if was_away {
+                return None;
+            }
+            self.away_since = Some(event.timestamp);
+            return self.go_away(event);
+        }
+
+        if self.locked || self.asleep {
+            return None;
+        }
+        let away_since = self.away_since.take()?;
+        let activity = event.kind.to_string();
+        self.record_activity(&activity).map(|mut data| {
+            data.idle_duration_seconds = (event.timestamp - away_since).num_seconds().max(0) as u64;
+            data
+        })
+    }
+
+    /// End the session at a lock or suspend
+    fn go_away(&mut self, event: &PowerEvent) -> Option<IdleEventData> {
+        let previous_state = self.current_state;
+        let idle_duration_seconds = (event.timestamp - self.last_activity_time)
+            .num_seconds()
+            .max(0) as u64;
+
+        if previous_state == ActivityState::Active {
+            self.update_category_time(None);
+            // An active session runs right up to the lock
+            self.last_activity_time = self.last_activity_time.max(event.timestamp);
+        }
+        self.current_state = ActivityState::Away;
+        self.current_idle_start = None;
+        if let Some(session) = self.end_session() {
+            info!("Session ended by {}", event.kind);
+            self.completed_sessions.push(session);
+        }
+
+        if previous_state == ActivityState::Away {
+            return None;
+        }
+        Some(IdleEventData {
+            previous_state: format!("{:?}", previous_state).to_lowercase(),
+            new_state: "away".to_string(),
+            idle_duration_seconds,
+            last_activity_type: self.last_activity_type.clone(),
+            last_activity_category: self.last_activity_category.clone(),
+            system_idle: true,
+        })
+    }
+
+    /// System-wide time since the last input, if a backend is available
+    fn system_idle_time(&self) -> Option<Duration> {
+        #[cfg(target_os = "linux")]
+        {
+            self.linux_backend.as_ref().and_then(|b| b.idle_time())
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            get_system_idle_time()
+        }
+    }
+
     /// Get current session data
     pub fn get_session(&self) -> Option<SessionData> {
         self.session_start.map(|(start_instant, start_time)| {
-            let duration = Instant::now().duration_since(start_instant);
             let ended = if self.current_state == ActivityState::Away {
                 Some(self.last_activity_time)
             } else {
                 None
             };
+            let duration_secs = match ended {
+                Some(end) => (end - start_time).num_seconds().max(0) as u64,
+                None => Instant::now().duration_since(start_instant).as_secs(),
+            };
 
             // Calculate total idle time
             let total_idle_secs: u64 = self.idle_periods.iter().map(|p| p.duration_seconds).sum();
-            let duration_secs = duration.as_secs();
             let active_secs = duration_secs.saturating_sub(total_idle_secs);
 
             SessionData {
@@ -501,8 +643,22 @@ impl IdleDetector {
     }
 
     /// End current session and reset
+    ///
+    /// An idle session ends at its last activity; an active one ends now.
     pub fn end_session(&mut self) -> Option<SessionData> {
-        let session = self.get_session();
+        let mut session = self.get_session();
+        if let Some(ref mut session) = session {
+            if session.ended_at.is_none() {
+                let ended_at = if self.current_state == ActivityState::Active {
+                    Utc::now()
+                } else {
+                    self.last_activity_time
+                };
+                session.ended_at = Some(ended_at);
+                session.duration_minutes =
+                    ((ended_at - session.started_at).num_seconds().max(0) / 60) as u64;
+            }
+        }
 
         // Reset state
         self.session_start = None;
@@ -516,6 +672,11 @@ impl IdleDetector {
         session
     }
 
+    /// Take the sessions ended by inactivity since the last call
+    pub fn drain_completed_sessions(&mut self) -> Vec<SessionData> {
+        std::mem::take(&mut self.completed_sessions)
+    }
+
     /// Get current activity state
     pub fn state(&self) -> ActivityState {
         self.current_state
@@ -657,6 +818,102 @@ mod tests {
         // Note: Timing in tests is approximate
     }
 
+    #[test]
+    fn test_away_completes_session() {
+        let mut detector = IdleDetector::new(IdleConfig {
+            idle_threshold: Duration::from_millis(20),
+            session_end_threshold: Duration::from_millis(60),
+            use_system_idle: false,
+            ignore_idle_in_meetings: true,
+        });
+
+        detector.record_activity("shell");
+        detector.record_activity("editor");
+        sleep(Duration::from_millis(30));
+        detector.check_idle();
+        assert!(detector.drain_completed_sessions().is_empty());
+
+        sleep(Duration::from_millis(40));
+        detector.check_idle();
+        assert_eq!(detector.state(), ActivityState::Away);
+        assert!(detector.get_session().is_none());
+
+        let sessions = detector.drain_completed_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].event_count, 2);
+        assert!(sessions[0].ended_at.is_some());
+        // The trailing idle stretch isn't part of the session
+        assert!(sessions[0].idle_periods.is_empty());
+        assert!(detector.drain_completed_sessions().is_empty());
+
+        // Activity afterwards starts a fresh session
+        detector.record_activity("shell");
+        let next = detector.get_session().unwrap();
+        assert_eq!(next.event_count, 1);
+        assert_ne!(next.started_at, sessions[0].started_at);
+    }
+
+    #[test]
+    fn test_end_session_sets_end_time() {
+        let mut detector = IdleDetector::new(test_config());
+        detector.record_activity("shell");
+
+        let session = detector.end_session().unwrap();
+        assert!(session.ended_at.unwrap() >= session.started_at);
+        assert!(detector.end_session().is_none());
+    }
+
+    #[test]
+    fn test_lock_and_suspend_end_session_immediately() {
+        let mut detector = IdleDetector::new(IdleConfig {
+            idle_threshold: Duration::from_millis(20),
+            session_end_threshold: Duration::from_millis(60),
+            use_system_idle: false,
+            ignore_idle_in_meetings: true,
+        });
+        detector.record_activity("shell");
+
+        let locked_at = Utc::now() + chrono::Duration::seconds(5);
+        let event = detector
+            .record_power_event(&PowerEvent::new(PowerEventKind::Lock, locked_at, "logind"))
+            .unwrap();
+        assert_eq!(event.new_state, "away");
+        assert_eq!(detector.state(), ActivityState::Away);
+        let sessions = detector.drain_completed_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].ended_at, Some(locked_at));
+
+        // Nothing counts as the user until they unlock, even across a sleep
+        let slept_at = locked_at + chrono::Duration::minutes(1);
+        let resumed_at = locked_at + chrono::Duration::hours(3);
+        let suspend = PowerEvent::new(PowerEventKind::Suspend, slept_at, "logind");
+        let resume = PowerEvent::new(PowerEventKind::Resume, resumed_at, "logind");
+        assert!(detector.record_power_event(&suspend).is_none());
+        assert!(detector.record_activity("filesystem").is_none());
+        sleep(Duration::from_millis(30));
+        assert!(detector.check_idle().is_none());
+        assert!(detector.record_power_event(&resume).is_none());
+        assert!(!detector.in_session());
+        assert!(detector.drain_completed_sessions().is_empty());
+
+        let unlocked_at = resumed_at + chrono::Duration::seconds(10);
+        let event = detector
+            .record_power_event(&PowerEvent::new(
+                PowerEventKind::Unlock,
+                unlocked_at,
+                "logind",
+            ))
+            .unwrap();
+        assert_eq!(event.previous_state, "away");
+        assert_eq!(event.new_state, "active");
+        assert_eq!(
+            event.idle_duration_seconds,
+            (unlocked_at - locked_at).num_seconds() as u64
+        );
+        assert!(detector.in_session());
+        assert_eq!(detector.get_session().unwrap().event_count, 1);
+    }
+
     #[test]
     fn test_session_data_fields() {
         let mut detector = IdleDetector::new(test_config());
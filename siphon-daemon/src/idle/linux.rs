@@ -0,0 +1,507 @@
+//! Linux system idle backends
+//!
+//! Without one of these, idle on Linux is inferred only from events the
+//! daemon happens to see, so reading a long document looks like being away.
+//! - Wayland compositors with `ext-idle-notify-v1` (sway, Hyprland, KDE)
+//! - X11 servers with the MIT-SCREEN-SAVER extension
+//! - systemd-logind's `IdleHint`/`IdleSinceHint` over D-Bus, for desktops
+//!   that report idleness to logind (GNOME) but have neither of the above
+//!
+//! The backend is selected from the session environment at startup and can be
+//! forced with `SIPHON_IDLE_BACKEND=wayland|x11|logind`.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// How long without input before the compositor reports idle. Idle time is
+/// this plus the time since the `idled` event, so it's the resolution of the
+/// Wayland backend.
+const WAYLAND_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const LOGIND_DESTINATION: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+/// A source of system-wide idle time
+pub enum LinuxIdleBackend {
+    /// `ext-idle-notify-v1` notifications from the compositor
+    Wayland(WaylandIdle),
+    /// XScreenSaver extension query
+    X11(Box<X11Idle>),
+    /// logind idle hint over the system bus
+    Logind(LogindIdle),
+}
+
+impl LinuxIdleBackend {
+    /// Pick a backend from the session environment
+    pub fn detect() -> Option<Self> {
+        let forced = std::env::var("SIPHON_IDLE_BACKEND")
+            .ok()
+            .map(|v| v.to_lowercase());
+
+        let backend = match forced.as_deref() {
+            Some("wayland") => WaylandIdle::connect().map(LinuxIdleBackend::Wayland),
+            Some("x11") => X11Idle::connect().map(|x| LinuxIdleBackend::X11(Box::new(x))),
+            Some("logind") => LogindIdle::connect().map(LinuxIdleBackend::Logind),
+            Some(other) if other != "auto" => {
+                warn!("Unknown SIPHON_IDLE_BACKEND '{}', detecting", other);
+                Self::detect_auto()
+            }
+            _ => Self::detect_auto(),
+        };
+
+        match &backend {
+            Some(b) => info!("Using {} idle backend", b.name()),
+            None => debug!("No Linux idle backend available, using app-level idle tracking"),
+        }
+
+        backend
+    }
+
+    fn detect_auto() -> Option<Self> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            // XScreenSaver under XWayland only sees input to X clients, so skip it
+            if let Some(wayland) = WaylandIdle::connect() {
+                return Some(LinuxIdleBackend::Wayland(wayland));
+            }
+        } else if std::env::var_os("DISPLAY").is_some() {
+            if let Some(x11) = X11Idle::connect() {
+                return Some(LinuxIdleBackend::X11(Box::new(x11)));
+            }
+        }
+        LogindIdle::connect().map(LinuxIdleBackend::Logind)
+    }
+
+    /// Backend name for logging
+    pub fn name(&self) -> &'static str {
+        match self {
+            LinuxIdleBackend::Wayland(_) => "wayland",
+            LinuxIdleBackend::X11(_) => "x11",
+            LinuxIdleBackend::Logind(_) => "logind",
+        }
+    }
+
+    /// Time since the last user input, if the backend knows it
+    pub fn idle_time(&self) -> Option<Duration> {
+        let result = match self {
+            LinuxIdleBackend::Wayland(wayland) => wayland.idle_time().map(Some),
+            LinuxIdleBackend::X11(x11) => x11.idle_time().map(Some).map_err(|e| e.to_string()),
+            LinuxIdleBackend::Logind(logind) => logind.idle_time().map_err(|e| e.to_string()),
+        };
+
+        match result {
+            Ok(idle) => idle,
+            Err(e) => {
+                debug!("{} idle backend failed: {}", self.name(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Idle time from logind's idle hint
+///
+/// logind only knows what the desktop tells it, and many (sway, i3,
+/// Hyprland) never set the hint, so an unset hint says nothing and idle falls
+/// back to app-level tracking.
+pub struct LogindIdle {
+    proxy: zbus::blocking::Proxy<'static>,
+}
+
+impl LogindIdle {
+    /// Connect to logind on the system bus
+    pub fn connect() -> Option<Self> {
+        match zbus::blocking::Connection::system() {
+            Ok(conn) => Self::with_connection(&conn),
+            Err(e) => {
+                debug!("Failed to connect to the system bus: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Use logind on an existing bus connection, if it's there
+    pub fn with_connection(conn: &zbus::blocking::Connection) -> Option<Self> {
+        let proxy = zbus::blocking::proxy::Builder::new(conn)
+            .destination(LOGIND_DESTINATION)
+            .ok()?
+            .path(LOGIND_PATH)
+            .ok()?
+            .interface(LOGIND_MANAGER_INTERFACE)
+            .ok()?
+            // The hint changes without us subscribing to anything; always ask
+            .cache_properties(zbus::proxy::CacheProperties::No)
+            .build()
+            .ok()?;
+
+        let logind = Self { proxy };
+        match logind.idle_time() {
+            Ok(_) => Some(logind),
+            Err(e) => {
+                debug!("logind idle hint unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Read `IdleHint` and `IdleSinceHint` (microseconds since the epoch);
+    /// None while the hint isn't set
+    pub fn idle_time(&self) -> zbus::Result<Option<Duration>> {
+        let idle_hint: bool = self.proxy.get_property("IdleHint")?;
+        if !idle_hint {
+            return Ok(None);
+        }
+        let idle_since: u64 = self.proxy.get_property("IdleSinceHint")?;
+        Ok(Some(logind_idle_duration(idle_since, realtime_micros())))
+    }
+}
+
+/// Idle time from an `IdleSinceHint` timestamp
+fn logind_idle_duration(idle_since_us: u64, now_us: u64) -> Duration {
+    Duration::from_micros(now_us.saturating_sub(idle_since_us))
+}
+
+fn realtime_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or_default()
+}
+
+/// Idle time from the X server's MIT-SCREEN-SAVER extension
+pub struct X11Idle {
+    conn: x11rb::rust_connection::RustConnection,
+    root: u32,
+}
+
+impl X11Idle {
+    /// Connect to the X server named by `DISPLAY`
+    pub fn connect() -> Option<Self> {
+        use x11rb::connection::{Connection, RequestConnection};
+        use x11rb::protocol::screensaver;
+
+        let (conn, screen_num) = match x11rb::connect(None) {
+            Ok(c) => c,
+            Err(e) => {
+                debug!("Failed to connect to X server: {}", e);
+                return None;
+            }
+        };
+        if conn
+            .extension_information(screensaver::X11_EXTENSION_NAME)
+            .ok()
+            .flatten()
+            .is_none()
+        {
+            debug!("X server has no MIT-SCREEN-SAVER extension");
+            return None;
+        }
+        let root = conn.setup().roots.get(screen_num)?.root;
+
+        let x11 = Self { conn, root };
+        x11.idle_time().ok()?;
+        Some(x11)
+    }
+
+    /// Time since the last keyboard or pointer input
+    pub fn idle_time(&self) -> Result<Duration, Box<dyn std::error::Error>> {
+        use x11rb::protocol::screensaver::ConnectionExt;
+
+        let info = self.conn.screensaver_query_info(self.root)?.reply()?;
+        Ok(Duration::from_millis(info.ms_since_user_input as u64))
+    }
+}
+
+/// What the Wayland event thread has seen
+#[derive(Debug, Default)]
+struct WaylandIdleStatus {
+    /// False once the compositor connection is gone
+    connected: bool,
+    /// When the compositor reported idle, cleared on resume
+    idled_at: Option<Instant>,
+}
+
+/// Idle time from `ext-idle-notify-v1`
+///
+/// The protocol only says "idle" after a timeout and "resumed" on input, so a
+/// thread follows those events and idle time is derived from when the last
+/// `idled` arrived. Idle inhibitors (video playback) keep it from firing.
+pub struct WaylandIdle {
+    status: Arc<Mutex<WaylandIdleStatus>>,
+}
+
+impl WaylandIdle {
+    /// Connect to the compositor named by `WAYLAND_DISPLAY`
+    pub fn connect() -> Option<Self> {
+        use wayland_client::globals::registry_queue_init;
+        use wayland_client::protocol::wl_seat::WlSeat;
+        use wayland_client::Connection;
+        use wayland_protocols::ext::idle_notify::v1::client::ext_idle_notifier_v1::ExtIdleNotifierV1;
+
+        let conn = match Connection::connect_to_env() {
+            Ok(conn) => conn,
+            Err(e) => {
+                debug!("Failed to connect to Wayland compositor: {}", e);
+                return None;
+            }
+        };
+        let (globals, mut queue) = registry_queue_init::<WaylandIdleState>(&conn).ok()?;
+        let qh = queue.handle();
+        let seat: WlSeat = globals.bind(&qh, 1..=1, ()).ok()?;
+        let notifier: ExtIdleNotifierV1 = match globals.bind(&qh, 1..=1, ()) {
+            Ok(notifier) => notifier,
+            Err(e) => {
+                debug!("Compositor has no ext-idle-notify-v1: {}", e);
+                return None;
+            }
+        };
+        let notification =
+            notifier.get_idle_notification(WAYLAND_IDLE_TIMEOUT.as_millis() as u32, &seat, &qh, ());
+
+        let status = Arc::new(Mutex::new(WaylandIdleStatus {
+            connected: true,
+            idled_at: None,
+        }));
+        let mut state = WaylandIdleState {
+            status: Arc::clone(&status),
+        };
+        queue.roundtrip(&mut state).ok()?;
+
+        std::thread::Builder::new()
+            .name("siphon-wayland-idle".to_string())
+            .spawn(move || {
+                // Keep the protocol objects alive for as long as we listen
+                let _objects = (seat, notifier, notification);
+                loop {
+                    if let Err(e) = queue.blocking_dispatch(&mut state) {
+                        debug!("Wayland idle connection closed: {}", e);
+                        if let Ok(mut status) = state.status.lock() {
+                            status.connected = false;
+                        }
+                        return;
+                    }
+                }
+            })
+            .ok()?;
+
+        Some(Self { status })
+    }
+
+    /// Time since the last input, to within `WAYLAND_IDLE_TIMEOUT`
+    pub fn idle_time(&self) -> Result<Duration, String> {
+        let status = self.status.lock().map_err(|e| e.to_string())?;
+        wayland_idle_duration(&status, Instant::now())
+    }
+}
+
+fn wayland_idle_duration(status: &WaylandIdleStatus, now: Instant) -> Result<Duration, String> {
+    if !status.connected {
+        return Err("compositor connection closed".to_string());
+    }
+    Ok(status
+        .idled_at
+        .map(|idled_at| now.saturating_duration_since(idled_at) + WAYLAND_IDLE_TIMEOUT)
+        .unwrap_or(Duration::ZERO))
+}
+
+/// Dispatch state for the Wayland event thread
+struct WaylandIdleState {
+    status: Arc<Mutex<WaylandIdleStatus>>,
+}
+
+mod wayland_dispatch {
+    use super::WaylandIdleState;
+    use std::time::Instant;
+    use wayland_client::globals::GlobalListContents;
+    use wayland_client::protocol::{wl_registry::WlRegistry, wl_seat::WlSeat};
+    use wayland_client::{delegate_noop, Connection, Dispatch, QueueHandle};
+    use wayland_protocols::ext::idle_notify::v1::client::ext_idle_notification_v1::{
+        self, ExtIdleNotificationV1,
+    };
+    use wayland_protocols::ext::idle_notify::v1::client::ext_idle_notifier_v1::ExtIdleNotifierV1;
+
+    impl Dispatch<WlRegistry, GlobalListContents> for WaylandIdleState {
+        fn event(
+            _: &mut Self,
+            _: &WlRegistry,
+            _: <WlRegistry as wayland_client::Proxy>::Event,
+            _: &GlobalListContents,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<ExtIdleNotificationV1, ()> for WaylandIdleState {
+        fn event(
+            state: &mut Self,
+            _: &ExtIdleNotificationV1,
+            event: ext_idle_notification_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            let Ok(mut status) = state.status.lock() else {
+                return;
+            };
+            match event {
+                ext_idle_notification_v1::Event::Idled => status.idled_at = Some(Instant::now()),
+                ext_idle_notification_v1::Event::Resumed => status.idled_at = None,
+                _ => {}
+            }
+        }
+    }
+
+    delegate_noop!(WaylandIdleState: ignore WlSeat);
+    delegate_noop!(WaylandIdleState: ExtIdleNotifierV1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idle::{ActivityState, IdleConfig, IdleDetector};
+    use crate::testutil::MockBus;
+
+    /// The parts of `org.freedesktop.login1.Manager` the backend reads
+    struct MockLogind {
+        idle_hint: bool,
+        idle_since_hint: u64,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.login1.Manager")]
+    impl MockLogind {
+        #[zbus(property)]
+        fn idle_hint(&self) -> bool {
+            self.idle_hint
+        }
+
+        #[zbus(property)]
+        fn idle_since_hint(&self) -> u64 {
+            self.idle_since_hint
+        }
+    }
+
+    fn serve_logind(bus: &MockBus, logind: MockLogind) -> zbus::blocking::Connection {
+        zbus::blocking::connection::Builder::address(bus.address())
+            .unwrap()
+            .name(LOGIND_DESTINATION)
+            .unwrap()
+            .serve_at(LOGIND_PATH, logind)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_logind_idle_over_mock_bus() {
+        let Some(bus) = MockBus::start() else {
+            eprintln!("dbus-daemon not installed, skipping");
+            return;
+        };
+
+        // Nothing serving login1 yet
+        assert!(LogindIdle::with_connection(&bus.connect()).is_none());
+
+        let idle_since = realtime_micros() - 90_000_000;
+        let _server = serve_logind(
+            &bus,
+            MockLogind {
+                idle_hint: true,
+                idle_since_hint: idle_since,
+            },
+        );
+        let logind = LogindIdle::with_connection(&bus.connect()).expect("mock logind");
+        let idle = logind.idle_time().unwrap().unwrap();
+        assert!(idle >= Duration::from_secs(90) && idle < Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_logind_not_idle_over_mock_bus() {
+        let Some(bus) = MockBus::start() else {
+            eprintln!("dbus-daemon not installed, skipping");
+            return;
+        };
+
+        let _server = serve_logind(
+            &bus,
+            MockLogind {
+                idle_hint: false,
+                idle_since_hint: 1,
+            },
+        );
+        let logind = LogindIdle::with_connection(&bus.connect()).expect("mock logind");
+        assert_eq!(logind.idle_time().unwrap(), None);
+    }
+
+    #[test]
+    fn test_unset_logind_hint_falls_back_to_app_idle() {
+        let Some(bus) = MockBus::start() else {
+            eprintln!("dbus-daemon not installed, skipping");
+            return;
+        };
+
+        // sway and friends never set the hint
+        let _server = serve_logind(
+            &bus,
+            MockLogind {
+                idle_hint: false,
+                idle_since_hint: 0,
+            },
+        );
+        let logind = LogindIdle::with_connection(&bus.connect()).expect("mock logind");
+
+        let mut detector = IdleDetector::new(IdleConfig {
+            idle_threshold: Duration::from_millis(50),
+            session_end_threshold: Duration::from_secs(60),
+            use_system_idle: false,
+            ignore_idle_in_meetings: true,
+        });
+        detector.config.use_system_idle = true;
+        detector.linux_backend = Some(LinuxIdleBackend::Logind(logind));
+
+        detector.record_activity("shell");
+        std::thread::sleep(Duration::from_millis(60));
+
+        let event = detector.check_idle().expect("went idle");
+        assert!(!event.system_idle);
+        assert_eq!(detector.state(), ActivityState::Idle);
+    }
+
+    #[test]
+    fn test_logind_idle_duration() {
+        assert_eq!(
+            logind_idle_duration(1_000_000, 4_500_000),
+            Duration::from_millis(3500)
+        );
+        // Clock skew never yields a negative idle time
+        assert_eq!(logind_idle_duration(5_000_000, 4_000_000), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_wayland_idle_duration() {
+        let now = Instant::now();
+        let mut status = WaylandIdleStatus {
+            connected: true,
+            idled_at: None,
+        };
+        assert_eq!(wayland_idle_duration(&status, now), Ok(Duration::ZERO));
+
+        status.idled_at = now.checked_sub(Duration::from_secs(60));
+        assert_eq!(
+            wayland_idle_duration(&status, now),
+            Ok(Duration::from_secs(60) + WAYLAND_IDLE_TIMEOUT)
+        );
+
+        status.connected = false;
+        assert!(wayland_idle_duration(&status, now).is_err());
+    }
+
+    /// Run with `xvfb-run cargo test -- --ignored` to exercise the XScreenSaver path
+    #[test]
+    #[ignore]
+    fn test_x11_idle_connects() {
+        let x11 = X11Idle::connect().expect("DISPLAY must point at an X server");
+        assert!(x11.idle_time().is_ok());
+    }
+}
@@ -0,0 +1,718 @@
+//! Scheduled jobs
+//!
+//! Background work (the idle check, storing reports, retention cleanup, daily
+//! note export) runs as named jobs on a schedule. A schedule is a five-field
+//! cron expression in local time (`minute hour day-of-month month
+//! day-of-week`), an alias such as `@hourly` or `@daily`, or `@every 10s` for
+//! a fixed interval. Schedules can be overridden, and jobs turned on or off,
+//! in the `[jobs.<name>]` config sections. Each job keeps its last run, how
+//! long it took and whether it worked.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{debug, warn};
+
+/// What a job reports: a short message on success, the error on failure
+pub type JobResult = Result<String, String>;
+
+/// Schedule and on/off switch for one job (a `[jobs.<name>]` config section)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JobConfig {
+    /// Replaces the job's default schedule
+    pub schedule: Option<String>,
+    /// Replaces whether the job runs by default
+    pub enabled: Option<bool>,
+}
+
+/// When a job runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    expression: String,
+    kind: ScheduleKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ScheduleKind {
+    Every(Duration),
+    Cron(CronFields),
+}
+
+/// Allowed values of each cron field, as bit sets
+#[derive(Debug, Clone, PartialEq)]
+struct CronFields {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    /// Sunday is 0
+    weekdays: u64,
+    /// Whether the day-of-month and day-of-week fields start with `*`
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// Furthest ahead to look for a matching day
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+
+impl Schedule {
+    /// Parse a cron expression, an `@` alias or `@every <n>s|m|h`
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+        let cron = match expression {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let kind = if let Some(interval) = cron.strip_prefix("@every ") {
+            ScheduleKind::Every(parse_interval(interval.trim())?)
+        } else {
+            let fields: Vec<&str> = cron.split_whitespace().collect();
+            let [minute, hour, day, month, weekday] = fields[..] else {
+                return Err(format!(
+                    "'{}' must have 5 fields (minute hour day month weekday)",
+                    expression
+                ));
+            };
+            ScheduleKind::Cron(CronFields {
+                minutes: parse_field(minute, 0, 59)?,
+                hours: parse_field(hour, 0, 23)?,
+                days: parse_field(day, 1, 31)?,
+                months: parse_field(month, 1, 12)?,
+                // 7 is Sunday too
+                weekdays: {
+                    let bits = parse_field(weekday, 0, 7)?;
+                    (bits | (bits >> 7)) & 0x7f
+                },
+                any_day: day.starts_with('*'),
+                any_weekday: weekday.starts_with('*'),
+            })
+        };
+
+        Ok(Self {
+            expression: expression.to_string(),
+            kind,
+        })
+    }
+
+    /// The schedule as written
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// First time the schedule fires strictly after `after`
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let fields = match &self.kind {
+            ScheduleKind::Every(interval) => return Some(after.clone() + *interval),
+            ScheduleKind::Cron(fields) => fields,
+        };
+
+        let tz = after.timezone();
+        let local = after.naive_local();
+        let mut date = local.date();
+        for _ in 0..MAX_SEARCH_DAYS {
+            if fields.matches_day(date) {
+                for hour in 0..24 {
+                    if !has(fields.hours, hour) {
+                        continue;
+                    }
+                    for minute in 0..60 {
+                        if !has(fields.minutes, minute) {
+                            continue;
+                        }
+                        let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
+                            continue;
+                        };
+                        if naive <= local {
+                            continue;
+                        }
+                        // Times skipped by a DST change don't fire
+                        if let Some(at) = tz.from_local_datetime(&naive).earliest() {
+                            return Some(at);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+impl CronFields {
+    /// Like cron, a day matches either day field when both are restricted
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+/// One cron field (`*`, `5`, `1-5`, `*/15`, `0-30/10` or a list of them) as a bit set
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let number = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|n| (min..=max).contains(n))
+            .ok_or_else(|| format!("'{}' is not a number from {} to {}", s, min, max))
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("'{}' has an invalid step", part)),
+            },
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/10` means from 5 to the end
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let n = number(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("'{}' is an empty range", part));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// `10s`, `5m` or `1h`
+fn parse_interval(interval: &str) -> Result<Duration, String> {
+    let invalid = || format!("'{}' must be a number followed by s, m or h", interval);
+    let split = interval
+        .char_indices()
+        .last()
+        .map(|(i, _)| i)
+        .ok_or_else(invalid)?;
+    let (amount, unit) = interval.split_at(split);
+    let amount: i64 = amount.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?;
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        _ => Err(invalid()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobOutcome {
+    Success,
+    Failed,
+}
+
+/// One run of a job
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRun {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub outcome: JobOutcome,
+    /// What the job reported, or the error
+    pub message: String,
+    /// Started through the API rather than by the schedule
+    pub manual: bool,
+}
+
+/// A job as reported by `GET /jobs`
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub schedule: String,
+    pub enabled: bool,
+    pub running: bool,
+    /// None while the job is turned off
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<JobRun>,
+    pub run_count: u64,
+    pub failure_count: u64,
+}
+
+/// Why a job couldn't be started by hand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunError {
+    Unknown(String),
+    AlreadyRunning(String),
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Unknown(name) => write!(f, "no job named '{}'", name),
+            RunError::AlreadyRunning(name) => write!(f, "job '{}' is already running", name),
+        }
+    }
+}
+
+type Task<C> = Box<dyn Fn(&C) -> JobResult + Send + Sync>;
+
+struct Job<C> {
+    name: String,
+    schedule: Schedule,
+    enabled: bool,
+    task: Task<C>,
+    state: Mutex<JobState>,
+}
+
+#[derive(Default)]
+struct JobState {
+    running: bool,
+    next_run: Option<DateTime<Utc>>,
+    last_run: Option<JobRun>,
+    run_count: u64,
+    failure_count: u64,
+}
+
+/// Runs jobs on their schedules; each job gets the context `C` when it runs
+pub struct Scheduler<C> {
+    jobs: Vec<Job<C>>,
+    config: HashMap<String, JobConfig>,
+}
+
+impl<C> Scheduler<C> {
+    /// Create a scheduler with the `[jobs]` config sections
+    pub fn new(config: HashMap<String, JobConfig>) -> Self {
+        Self {
+            jobs: Vec::new(),
+            config,
+        }
+    }
+
+    /// Add a job with its default schedule and on/off state, which the
+    /// config can override; it first runs at the next scheduled time
+    pub fn add(
+        &mut self,
+        name: &str,
+        schedule: &str,
+        enabled: bool,
+        now: DateTime<Utc>,
+        task: impl Fn(&C) -> JobResult + Send + Sync + 'static,
+    ) {
+        let config = self.config.get(name).cloned().unwrap_or_default();
+        let default = Schedule::parse(schedule).expect("default job schedule is valid");
+        let schedule = match config.schedule.as_deref().map(Schedule::parse) {
+            Some(Ok(schedule)) => schedule,
+            Some(Err(e)) => {
+                warn!("Ignoring schedule for job '{}': {}", name, e);
+                default
+            }
+            None => default,
+        };
+        let enabled = config.enabled.unwrap_or(enabled);
+        let state = JobState {
+            next_run: enabled.then(|| next_run(&schedule, now)).flatten(),
+            ..Default::default()
+        };
+
+        self.jobs.push(Job {
+            name: name.to_string(),
+            schedule,
+            enabled,
+            task: Box::new(task),
+            state: Mutex::new(state),
+        });
+    }
+
+    /// Config sections that don't name a job
+    pub fn unknown_config(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .config
+            .keys()
+            .filter(|name| !self.jobs.iter().any(|job| &job.name == *name))
+            .map(String::as_str)
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Run every job that is due, one after another; returns how many ran
+    pub fn run_due(&self, ctx: &C, now: DateTime<Utc>) -> usize {
+        let due = self.claim_due(now);
+        for name in &due {
+            self.run_claimed(ctx, name);
+        }
+        due.len()
+    }
+
+    /// Mark every job that is due as running and return their names, to be
+    /// run with [`Self::run_claimed`], each on a thread of its own if need be
+    pub fn claim_due(&self, now: DateTime<Utc>) -> Vec<String> {
+        let mut due = Vec::new();
+        for job in &self.jobs {
+            let mut state = job.state.lock().unwrap();
+            let is_due = state.next_run.is_some_and(|at| at <= now);
+            if !job.enabled || state.running || !is_due {
+                continue;
+            }
+            state.running = true;
+            due.push(job.name.clone());
+        }
+        due
+    }
+
+    /// Run a job returned by [`Self::claim_due`]
+    pub fn run_claimed(&self, ctx: &C, name: &str) {
+        if let Some(job) = self.jobs.iter().find(|job| job.name == name) {
+            self.run(job, ctx, false);
+        }
+    }
+
+    /// Run a job now, whether or not it's due or turned on
+    pub fn run_now(&self, ctx: &C, name: &str) -> Result<JobStatus, RunError> {
+        let job = self
+            .jobs
+            .iter()
+            .find(|job| job.name == name)
+            .ok_or_else(|| RunError::Unknown(name.to_string()))?;
+        {
+            let mut state = job.state.lock().unwrap();
+            if state.running {
+                return Err(RunError::AlreadyRunning(name.to_string()));
+            }
+            state.running = true;
+        }
+        self.run(job, ctx, true);
+        Ok(status(job))
+    }
+
+    /// Every job, in the order they were added
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        self.jobs.iter().map(status).collect()
+    }
+
+    /// Run a job that has been marked as running, then record the run
+    ///
+    /// A panicking task counts as a failed run, so the job isn't left marked
+    /// as running for good.
+    fn run(&self, job: &Job<C>, ctx: &C, manual: bool) {
+        let started_at = Utc::now();
+        let timer = Instant::now();
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| (job.task)(ctx)))
+            .unwrap_or_else(|panic| Err(format!("panicked: {}", panic_message(&*panic))));
+        let duration_ms = timer.elapsed().as_millis() as u64;
+
+        let (outcome, message) = match result {
+            Ok(message) => {
+                debug!(
+                    "Job '{}' finished in {}ms {}",
+                    job.name, duration_ms, message
+                );
+                (JobOutcome::Success, message)
+            }
+            Err(error) => {
+                warn!("Job '{}' failed: {}", job.name, error);
+                (JobOutcome::Failed, error)
+            }
+        };
+
+        let mut state = job.state.lock().unwrap();
+        state.running = false;
+        state.run_count += 1;
+        if outcome == JobOutcome::Failed {
+            state.failure_count += 1;
+        }
+        if !manual && job.enabled {
+            state.next_run = next_run(&job.schedule, Utc::now());
+        }
+        state.last_run = Some(JobRun {
+            started_at,
+            duration_ms,
+            outcome,
+            message,
+            manual,
+        });
+    }
+}
+
+/// What a task passed to `panic!`
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message")
+}
+
+fn status<C>(job: &Job<C>) -> JobStatus {
+    let state = job.state.lock().unwrap();
+    JobStatus {
+        name: job.name.clone(),
+        schedule: job.schedule.expression().to_string(),
+        enabled: job.enabled,
+        running: state.running,
+        next_run: state.next_run,
+        last_run: state.last_run.clone(),
+        run_count: state.run_count,
+        failure_count: state.failure_count,
+    }
+}
+
+/// Cron schedules are in local time
+fn next_run(schedule: &Schedule, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    schedule
+        .next_after(&after.with_timezone(&Local))
+        .map(|at| at.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expression: &str, after: &str) -> String {
+        Schedule::parse(expression)
+            .unwrap()
+            .next_after(&utc(after))
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn test_parse_schedules() {
+        assert!(Schedule::parse("*/15 9-17 * * 1-5").is_ok());
+        assert!(Schedule::parse("0 3 * * *").is_ok());
+        assert!(Schedule::parse("@every 10s").is_ok());
+        assert!(Schedule::parse("@hourly").is_ok());
+
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("@every 10").is_err());
+        assert!(Schedule::parse("@every").is_err());
+    }
+
+    #[test]
+    fn test_next_after() {
+        // Minute steps and the next hour
+        assert_eq!(
+            next("*/15 * * * *", "2026-10-14T09:07:30Z"),
+            "2026-10-14T09:15:00+00:00"
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2026-10-14T09:45:00Z"),
+            "2026-10-14T10:00:00+00:00"
+        );
+        // Strictly after, so a run at 03:00 next fires tomorrow
+        assert_eq!(
+            next("0 3 * * *", "2026-10-14T03:00:00Z"),
+            "2026-10-15T03:00:00+00:00"
+        );
+        // Weekdays only: Friday evening to Monday morning (7 is Sunday)
+        assert_eq!(
+            next("30 9 * * 1-5", "2026-10-16T18:00:00Z"),
+            "2026-10-19T09:30:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 * * 7", "2026-10-14T00:00:00Z"),
+            "2026-10-18T00:00:00+00:00"
+        );
+        // Both day fields restricted: either matches
+        assert_eq!(
+            next("0 0 1 * 1", "2026-10-14T00:00:00Z"),
+            "2026-10-19T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("@monthly", "2026-10-14T00:00:00Z"),
+            "2026-11-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("@every 10s", "2026-10-14T00:00:00Z"),
+            "2026-10-14T00:00:10+00:00"
+        );
+        // Never matches
+        assert!(Schedule::parse("0 0 31 2 *")
+            .unwrap()
+            .next_after(&utc("2026-10-14T00:00:00Z"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_next_after_uses_local_time() {
+        let tz = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let after = utc("2026-10-14T23:30:00Z").with_timezone(&tz);
+        let at = Schedule::parse("0 3 * * *")
+            .unwrap()
+            .next_after(&after)
+            .unwrap();
+        assert_eq!((at.hour(), at.minute()), (3, 0));
+        assert_eq!(at.with_timezone(&Utc), utc("2026-10-15T01:00:00Z"));
+    }
+
+    struct Counter(AtomicUsize);
+
+    fn scheduler(config: &str, now: DateTime<Utc>) -> Scheduler<Counter> {
+        let config: HashMap<String, JobConfig> = toml::from_str(config).unwrap();
+        let mut scheduler = Scheduler::new(config);
+        scheduler.add("count", "@every 10s", true, now, |c: &Counter| {
+            let n = c.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("counted to {}", n))
+        });
+        scheduler.add("broken", "@every 1m", true, now, |_: &Counter| {
+            Err("disk full".to_string())
+        });
+        scheduler.add("off", "@every 10s", false, now, |_: &Counter| {
+            Ok(String::new())
+        });
+        scheduler
+    }
+
+    #[test]
+    fn test_claimed_jobs_are_not_claimed_again() {
+        let now = Utc::now();
+        let scheduler = scheduler("", now);
+        let counter = Counter(AtomicUsize::new(0));
+        let later = now + Duration::minutes(2);
+
+        assert_eq!(scheduler.claim_due(later), vec!["count", "broken"]);
+        // Still running: neither the next tick nor a manual run starts it twice
+        assert!(scheduler.claim_due(later).is_empty());
+        assert!(matches!(
+            scheduler.run_now(&counter, "count"),
+            Err(RunError::AlreadyRunning(_))
+        ));
+
+        scheduler.run_claimed(&counter, "count");
+        scheduler.run_claimed(&counter, "broken");
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert!(scheduler.statuses().iter().all(|s| !s.running));
+    }
+
+    #[test]
+    fn test_run_due_records_runs() {
+        let now = Utc::now();
+        let scheduler = scheduler("", now);
+        let counter = Counter(AtomicUsize::new(0));
+
+        assert_eq!(scheduler.run_due(&counter, now), 0);
+        assert_eq!(scheduler.run_due(&counter, now + Duration::seconds(10)), 1);
+        assert_eq!(scheduler.run_due(&counter, now + Duration::minutes(2)), 2);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+
+        let statuses = scheduler.statuses();
+        let count = &statuses[0];
+        assert_eq!(count.run_count, 2);
+        let last = count.last_run.as_ref().unwrap();
+        assert_eq!(last.outcome, JobOutcome::Success);
+        assert_eq!(last.message, "counted to 2");
+        assert!(!last.manual);
+        assert!(count.next_run.unwrap() > now);
+
+        let broken = &statuses[1];
+        assert_eq!(broken.failure_count, 1);
+        assert_eq!(broken.last_run.as_ref().unwrap().message, "disk full");
+
+        let off = &statuses[2];
+        assert!(!off.enabled);
+        assert!(off.next_run.is_none());
+        assert!(off.last_run.is_none());
+    }
+
+    #[test]
+    fn test_panicking_job_is_recorded_as_failed() {
+        let now = Utc::now();
+        let mut scheduler = Scheduler::new(HashMap::new());
+        scheduler.add(
+            "panics",
+            "@every 10s",
+            true,
+            now,
+            |_: &Counter| -> JobResult { panic!("out of disk") },
+        );
+        let counter = Counter(AtomicUsize::new(0));
+
+        assert_eq!(scheduler.run_due(&counter, now + Duration::seconds(10)), 1);
+        let status = &scheduler.statuses()[0];
+        assert!(!status.running);
+        assert_eq!(status.failure_count, 1);
+        let last = status.last_run.as_ref().unwrap();
+        assert_eq!(last.outcome, JobOutcome::Failed);
+        assert_eq!(last.message, "panicked: out of disk");
+
+        // Still scheduled, and can be run by hand
+        assert_eq!(scheduler.run_due(&counter, status.next_run.unwrap()), 1);
+        assert_eq!(
+            scheduler.run_now(&counter, "panics").unwrap().failure_count,
+            3
+        );
+    }
+
+    #[test]
+    fn test_run_now() {
+        let scheduler = scheduler("", Utc::now());
+        let counter = Counter(AtomicUsize::new(0));
+
+        // Turned-off jobs can still be run by hand
+        let status = scheduler.run_now(&counter, "off").unwrap();
+        assert_eq!(status.run_count, 1);
+        assert!(status.last_run.unwrap().manual);
+
+        let status = scheduler.run_now(&counter, "count").unwrap();
+        assert_eq!(status.last_run.unwrap().message, "counted to 1");
+        assert_eq!(
+            scheduler.run_now(&counter, "nope").unwrap_err(),
+            RunError::Unknown("nope".to_string())
+        );
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let now = Utc::now();
+        let scheduler = scheduler(
+            r#"
+            [count]
+            schedule = "@every 1h"
+            [broken]
+            enabled = false
+            [off]
+            enabled = true
+            schedule = "not a schedule"
+            [typo]
+            enabled = false
+            "#,
+            now,
+        );
+        let statuses = scheduler.statuses();
+
+        assert_eq!(statuses[0].schedule, "@every 1h");
+        assert!(!statuses[1].enabled);
+        // A bad schedule falls back to the default
+        assert!(statuses[2].enabled);
+        assert_eq!(statuses[2].schedule, "@every 10s");
+        assert_eq!(scheduler.unknown_config(), vec!["typo"]);
+    }
+}
@@ -4,37 +4,67 @@
 //! Runs on localhost:9847 and stores events in SQLite.
 
 mod api;
+pub mod blobs;
+pub mod browser;
+pub mod calendar;
+pub mod categories;
 pub mod clipboard;
+pub mod config;
 pub mod dedup;
+pub mod export;
+pub mod focus;
 pub mod hotkey;
 pub mod idle;
+pub mod jobs;
 pub mod meeting;
+pub mod notes;
+pub mod power;
 pub mod redact;
+pub mod reports;
+pub mod scoring;
+pub mod secrets;
 mod storage;
 pub mod summary;
+pub mod tasks;
+#[cfg(test)]
+mod testutil;
 pub mod triggers;
+pub mod vault;
 pub mod watcher;
 pub mod window;
 
 use axum::{
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
 };
+use chrono::{DateTime, Local, Utc};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::time::interval;
+use tokio::time::{interval, MissedTickBehavior};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
 use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
-use crate::clipboard::{ClipboardConfig, ClipboardTracker};
+use crate::blobs::BlobStore;
+use crate::browser::BrowserHistoryCollector;
+use crate::calendar::Calendar;
+use crate::categories::{ActivityContext, CategoryRules};
+use crate::clipboard::{BackgroundClipboard, ClipboardConfig, ClipboardTracker};
+use crate::config::SiphonConfig;
 use crate::dedup::{DedupConfig, Deduplicator};
-use crate::hotkey::{HotkeyConfig, HotkeyManager};
-use crate::idle::{IdleConfig, IdleDetector};
-use crate::meeting::{MeetingConfig, MeetingDetector};
+use crate::export::ObsidianConfig;
+use crate::focus::{FocusSession, FocusTracker};
+use crate::hotkey::{HotkeyAction, HotkeyManager};
+use crate::idle::{ActivityState, IdleConfig, IdleDetector, IdleEventData, SessionData};
+use crate::jobs::{JobResult, Scheduler};
+use crate::meeting::{MeetingDetector, MeetingEvent, MeetingEventType};
+use crate::power::{ClockGapDetector, PowerEvent, PowerMonitor};
+use crate::redact::Redactor;
+use crate::reports::{ReportGenerator, ReportPeriod};
 use crate::storage::{EventSource, EventStore};
+use crate::summary::{SummaryConfig, SummaryGenerator};
 use crate::watcher::{FileWatcher, WatcherConfig};
 use crate::window::{WindowConfig, WindowTracker};
 
@@ -45,9 +75,20 @@ pub struct AppState {
     pub idle_detector: Mutex<IdleDetector>,
     pub file_watcher: Mutex<Option<FileWatcher>>,
     pub window_tracker: Mutex<Option<WindowTracker>>,
-    pub clipboard_tracker: Mutex<Option<ClipboardTracker>>,
+    pub clipboard: Option<BackgroundClipboard>,
     pub hotkey_manager: Mutex<Option<HotkeyManager>>,
     pub meeting_detector: Mutex<MeetingDetector>,
+    pub focus: Mutex<FocusTracker>,
+    pub categories: Arc<CategoryRules>,
+    /// Summary settings from the config, including the focus score model
+    pub summary_config: SummaryConfig,
+    /// Days events are kept for
+    pub retention_days: u32,
+    pub obsidian_config: ObsidianConfig,
+    pub jobs: Scheduler<AppState>,
+    pub blobs: BlobStore,
+    pub clipboard_config: ClipboardConfig,
+    pub redactor: Redactor,
 }
 
 #[tokio::main]
@@ -60,6 +101,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Starting Siphon daemon...");
 
+    // Load config file (~/.siphon/config.toml)
+    let config = SiphonConfig::load();
+    let categories = Arc::new(CategoryRules::from_config(&config.categories));
+
     // Initialize storage
     let store = EventStore::new()?;
     info!("Database initialized at {:?}", store.db_path());
@@ -70,12 +115,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .and_then(|v| v.parse().ok())
         .unwrap_or(30u32);
 
-    if let Ok(deleted) = store.cleanup_old_events(retention_days) {
-        if deleted > 0 {
-            info!(
-                "Startup cleanup: removed {} events older than {} days",
-                deleted, retention_days
-            );
+    let blobs = BlobStore::new();
+    let max_blob_bytes = config.clipboard.max_blob_storage_bytes;
+    match cleanup_retention(&store, &blobs, retention_days, max_blob_bytes) {
+        Ok(message) => info!("Startup cleanup: {}", message),
+        Err(e) => warn!("Startup cleanup failed: {}", e),
+    }
+
+    // Close focus spans and meetings left open by an unclean shutdown
+    if let Ok(closed) = store.close_dangling_focus_spans() {
+        if closed > 0 {
+            info!("Closed {} focus span(s) left open by previous run", closed);
+        }
+    }
+    if let Ok(closed) = store.close_dangling_meeting_spans() {
+        if closed > 0 {
+            info!("Closed {} meeting(s) left open by previous run", closed);
         }
     }
 
@@ -84,7 +139,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Event deduplication enabled");
 
     // Initialize idle detector
-    let idle_detector = IdleDetector::new(IdleConfig::default());
+    let idle_detector =
+        IdleDetector::with_categories(IdleConfig::default(), Arc::clone(&categories));
     info!("Idle detection enabled");
 
     // Initialize file watcher (optional - based on env var)
@@ -129,14 +185,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     // Initialize clipboard tracker (optional - disabled with SIPHON_DISABLE_CLIPBOARD_TRACKING=1)
-    let clipboard_tracker = if std::env::var("SIPHON_DISABLE_CLIPBOARD_TRACKING").is_ok() {
+    let clipboard = if std::env::var("SIPHON_DISABLE_CLIPBOARD_TRACKING").is_ok() {
         info!("Clipboard tracking disabled via environment variable");
         None
     } else {
-        let tracker = ClipboardTracker::new(ClipboardConfig::default());
+        let tracker = ClipboardTracker::new(config.clipboard.clone());
         if tracker.is_available() {
             info!("Clipboard tracking enabled");
-            Some(tracker)
+            Some(BackgroundClipboard::spawn(tracker))
         } else {
             warn!("Clipboard tracking unavailable (no clipboard access)");
             None
@@ -149,10 +205,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Hotkey system disabled via environment variable");
         None
     } else {
-        let manager = HotkeyManager::new(HotkeyConfig::default());
+        let manager = HotkeyManager::new(config.hotkeys.clone());
         if manager.is_available() {
             manager.start_listener();
-            info!("Hotkey system enabled (Cmd+Shift+M to mark moment)");
+            info!(
+                "Hotkey system enabled ({} of {} binding(s) registered)",
+                manager.registered_hotkeys().len(),
+                manager.bindings().len()
+            );
             Some(manager)
         } else {
             warn!("Hotkey system unavailable");
@@ -161,27 +221,97 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     // Initialize meeting detector
-    let meeting_detector = MeetingDetector::new(MeetingConfig::default());
+    let mut meeting_detector = MeetingDetector::new(config.meeting.clone());
+    if let Some(calendar) = Calendar::from_config(&config.calendar) {
+        meeting_detector = meeting_detector.with_calendar(calendar);
+    }
+    // Polled on a thread of its own, since it runs pactl and scans /proc
+    #[cfg(target_os = "linux")]
+    if config.meeting.media_signals {
+        meeting_detector =
+            meeting_detector.with_media_signals(Box::new(meeting::BackgroundMediaSignals::spawn(
+                meeting::linux::LinuxMediaSignals::new(),
+                meeting::linux::POLL_INTERVAL,
+            )));
+    }
     info!("Meeting detection enabled");
 
+    // Background jobs, with schedules from the [jobs] config sections
+    let now = Utc::now();
+    let mut jobs = Scheduler::new(config.jobs.clone());
+    jobs.add("idle_check", "@every 10s", true, now, check_idle);
+    jobs.add("store_reports", "@hourly", true, now, store_reports);
+    jobs.add(
+        "retention_cleanup",
+        "0 3 * * *",
+        true,
+        now,
+        move |state: &AppState| {
+            let store = state.store.lock().map_err(|e| e.to_string())?;
+            cleanup_retention(&store, &state.blobs, retention_days, max_blob_bytes)
+        },
+    );
+    jobs.add(
+        "obsidian_export",
+        "@hourly",
+        config.obsidian.vault.is_some(),
+        now,
+        export_daily_notes,
+    );
+    for name in jobs.unknown_config() {
+        warn!(
+            "Ignoring [jobs.{}] config: there is no job by that name",
+            name
+        );
+    }
+
     let state = Arc::new(AppState {
         store: Mutex::new(store),
         dedup: Mutex::new(dedup),
         idle_detector: Mutex::new(idle_detector),
         file_watcher: Mutex::new(file_watcher),
         window_tracker: Mutex::new(window_tracker),
-        clipboard_tracker: Mutex::new(clipboard_tracker),
+        clipboard,
         hotkey_manager: Mutex::new(hotkey_manager),
         meeting_detector: Mutex::new(meeting_detector),
+        focus: Mutex::new(FocusTracker::new()),
+        categories,
+        summary_config: SummaryConfig {
+            focus_score: config.focus_score.clone(),
+            ..SummaryConfig::default()
+        },
+        retention_days,
+        obsidian_config: config.obsidian.clone(),
+        jobs,
+        blobs,
+        clipboard_config: config.clipboard.clone(),
+        redactor: Redactor::from_config(&config.redaction),
     });
 
     // Spawn background task for file watching and idle detection
     let state_clone = Arc::clone(&state);
     tokio::spawn(async move {
+        // Lock and sleep events from the OS, or failing that, clock jumps
+        let power_monitor = PowerMonitor::start();
+        if power_monitor.is_none() {
+            info!("No lock or sleep notifications; detecting sleep from clock gaps");
+        }
+        let mut clock_gap = ClockGapDetector::new(Utc::now());
+
         let mut ticker = interval(Duration::from_secs(1));
+        // Don't fire a burst of catch-up ticks after a sleep
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
         loop {
             ticker.tick().await;
 
+            let power_events = match power_monitor {
+                Some(ref monitor) => monitor.poll_events(),
+                None => clock_gap.tick(Utc::now()),
+            };
+            for event in power_events {
+                handle_power_event(&state_clone, &event);
+            }
+
             // Check for file events
             if let Ok(mut watcher_guard) = state_clone.file_watcher.try_lock() {
                 if let Some(ref mut watcher) = *watcher_guard {
@@ -212,14 +342,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
 
             // Check for window changes and get current window for other trackers
-            let (current_app, current_window) =
+            let current_window =
                 if let Ok(mut tracker_guard) = state_clone.window_tracker.try_lock() {
                     if let Some(ref mut tracker) = *tracker_guard {
                         if let Some(window_event) = tracker.check_active_window() {
-                            // Record activity for idle detection with app name for categorization
-                            let app_name = window_event.current.app_name.clone();
+                            // Record activity for idle detection, categorised by app, title and URL
                             if let Ok(mut idle) = state_clone.idle_detector.try_lock() {
-                                idle.record_activity_with_app("window_change", Some(&app_name));
+                                idle.record_window_activity(&window_event.current);
+                            }
+
+                            // Switching into chat or email interrupts a focus session
+                            if let Ok(mut focus) = state_clone.focus.try_lock() {
+                                let category = state_clone
+                                    .categories
+                                    .categorize(&ActivityContext::from(&window_event.current));
+                                focus.record_window(
+                                    &window_event.current.app_name,
+                                    &category,
+                                    Utc::now(),
+                                );
                             }
 
                             // Store the window change event
@@ -236,16 +377,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 }
                             }
                         }
+
+                        // Reopen the focus span once the user is back from idle
+                        if let Ok(idle) = state_clone.idle_detector.try_lock() {
+                            if idle.state() == ActivityState::Active {
+                                tracker.resume_span(Utc::now());
+                            }
+                        }
+                        persist_focus_spans(&state_clone, tracker);
+
                         // Return current window for other trackers
-                        (
-                            tracker.current_window().map(|w| w.app_name.clone()),
-                            tracker.current_window().cloned(),
-                        )
+                        tracker.current_window().cloned()
                     } else {
-                        (None, None)
+                        None
                     }
                 } else {
-                    (None, None)
+                    None
                 };
 
             // Check for meeting state changes
@@ -257,49 +404,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 if let Ok(mut idle) = state_clone.idle_detector.try_lock() {
                     idle.set_in_meeting(in_meeting);
                 }
+                if let Ok(mut focus) = state_clone.focus.try_lock() {
+                    let platform = detector_guard
+                        .state()
+                        .platform
+                        .as_ref()
+                        .map(|p| p.to_string())
+                        .unwrap_or_default();
+                    focus.set_in_meeting(in_meeting, &platform, Utc::now());
+                }
 
                 for event in meeting_events {
+                    // Record activity for idle detection; a missed meeting isn't any
+                    if event.event_type != MeetingEventType::MeetingMissed {
+                        if let Ok(mut idle) = state_clone.idle_detector.try_lock() {
+                            idle.record_activity("meeting");
+                        }
+                    }
+
+                    store_meeting_event(&state_clone, &event);
+                }
+                persist_meeting_spans(&state_clone, &mut detector_guard);
+            }
+
+            // Pick up clipboard changes; the clipboard is read on its own thread
+            if let Some(ref clipboard) = state_clone.clipboard {
+                clipboard.set_source(current_window.as_ref());
+                for clipboard_event in clipboard.drain() {
                     // Record activity for idle detection
                     if let Ok(mut idle) = state_clone.idle_detector.try_lock() {
-                        idle.record_activity("meeting");
+                        idle.record_activity("clipboard");
                     }
 
-                    // Store the meeting event
+                    // Store the clipboard change event
                     if let Ok(store) = state_clone.store.lock() {
-                        let event_json = serde_json::to_string(&event).unwrap_or_default();
+                        let event_json =
+                            serde_json::to_string(&clipboard_event).unwrap_or_default();
                         if let Err(e) = store.insert_event(
-                            EventSource::Meeting,
-                            &event.event_type.to_string(),
+                            EventSource::Clipboard,
+                            "clipboard_change",
                             &event_json,
                             None,
                         ) {
-                            warn!("Failed to store meeting event: {}", e);
-                        }
-                    }
-                }
-            }
-
-            // Check for clipboard changes
-            if let Ok(mut tracker_guard) = state_clone.clipboard_tracker.try_lock() {
-                if let Some(ref mut tracker) = *tracker_guard {
-                    if let Some(clipboard_event) = tracker.check_clipboard(current_app) {
-                        // Record activity for idle detection
-                        if let Ok(mut idle) = state_clone.idle_detector.try_lock() {
-                            idle.record_activity("clipboard");
-                        }
-
-                        // Store the clipboard change event
-                        if let Ok(store) = state_clone.store.lock() {
-                            let event_json =
-                                serde_json::to_string(&clipboard_event).unwrap_or_default();
-                            if let Err(e) = store.insert_event(
-                                EventSource::Clipboard,
-                                "clipboard_change",
-                                &event_json,
-                                None,
-                            ) {
-                                warn!("Failed to store clipboard event: {}", e);
-                            }
+                            warn!("Failed to store clipboard event: {}", e);
                         }
                     }
                 }
@@ -328,34 +475,103 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 warn!("Failed to store hotkey event: {}", e);
                             }
                         }
+
+                        if trigger.action == HotkeyAction::ToggleFocus {
+                            let stopped = state_clone.focus.lock().unwrap().toggle(Utc::now());
+                            match stopped {
+                                Some(session) => finish_focus_session(&state_clone, session),
+                                None => info!("Focus session started"),
+                            }
+                        }
                     }
                 }
             }
 
-            // Check idle state (every 10 seconds)
-            static mut IDLE_COUNTER: u32 = 0;
-            unsafe {
-                IDLE_COUNTER += 1;
-                if IDLE_COUNTER >= 10 {
-                    IDLE_COUNTER = 0;
-                    if let Ok(mut idle) = state_clone.idle_detector.try_lock() {
-                        if let Some(idle_event) = idle.check_idle() {
-                            if let Ok(store) = state_clone.store.lock() {
-                                let event_json =
-                                    serde_json::to_string(&idle_event).unwrap_or_default();
-                                let _ = store.insert_event(
-                                    EventSource::Shell, // Use shell as source for idle events
-                                    "idle_state_change",
-                                    &event_json,
-                                    None,
-                                );
+            // End focus sessions that reached their target
+            let finished = state_clone
+                .focus
+                .try_lock()
+                .ok()
+                .and_then(|mut focus| focus.check_target(Utc::now()));
+            if let Some(session) = finished {
+                finish_focus_session(&state_clone, session);
+            }
+        }
+    });
+
+    // Browser history collector (disable with SIPHON_DISABLE_BROWSER_HISTORY=1 or [browser] enabled = false)
+    if std::env::var("SIPHON_DISABLE_BROWSER_HISTORY").is_ok() || !config.browser.enabled {
+        info!("Browser history collection disabled");
+    } else {
+        let poll_interval = Duration::from_secs(config.browser.poll_interval_secs.max(5));
+        let mut collector = BrowserHistoryCollector::new(config.browser.clone());
+        if collector.profiles().is_empty() {
+            info!("No browser history found; browser collection idle");
+        } else {
+            if let Ok(store) = state.store.lock() {
+                let keys: Vec<String> = collector
+                    .profiles()
+                    .iter()
+                    .map(|p| p.cursor_key())
+                    .collect();
+                for key in keys {
+                    if let Ok(Some(cursor)) = store.get_cursor(&key) {
+                        collector.set_cursor(&key, cursor);
+                    }
+                }
+            }
+            info!("Browser history collection enabled");
+
+            let state_clone = Arc::clone(&state);
+            tokio::spawn(async move {
+                let mut ticker = interval(poll_interval);
+                loop {
+                    ticker.tick().await;
+                    // Copying history files is slow-ish disk I/O; keep it off the async workers
+                    let visits = tokio::task::block_in_place(|| collector.poll());
+
+                    if let Ok(store) = state_clone.store.lock() {
+                        for visit in &visits {
+                            let event_json = serde_json::to_string(visit).unwrap_or_default();
+                            if let Err(e) = store.insert_event_at(
+                                EventSource::Browser,
+                                "visit",
+                                &event_json,
+                                None,
+                                visit.visited_at,
+                            ) {
+                                warn!("Failed to store browser visit: {}", e);
+                            }
+                        }
+                        for (key, cursor) in collector.cursors() {
+                            if let Err(e) = store.set_cursor(key, *cursor) {
+                                warn!("Failed to save browser history cursor: {}", e);
                             }
                         }
                     }
                 }
-            }
+            });
         }
-    });
+    }
+
+    // Run scheduled jobs
+    {
+        let state_clone = Arc::clone(&state);
+        tokio::spawn(async move {
+            let mut ticker = interval(Duration::from_secs(1));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                // Each on a blocking thread of its own, so a slow cleanup or
+                // export doesn't hold up the idle check; a job still running
+                // isn't claimed again
+                for name in state_clone.jobs.claim_due(Utc::now()) {
+                    let state = Arc::clone(&state_clone);
+                    tokio::task::spawn_blocking(move || state.jobs.run_claimed(&state, &name));
+                }
+            }
+        });
+    }
 
     // Configure CORS for VS Code extension
     let cors = CorsLayer::new()
@@ -405,10 +621,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/watch", axum::routing::delete(api::remove_watch_path))
         // Idle/session endpoints
         .route("/session", get(api::get_session_info))
+        .route("/sessions", get(api::get_sessions))
+        .route("/sessions/:id", get(api::get_session_detail))
         // Window tracking
         .route("/window", get(api::get_active_window))
         // Meeting tracking
         .route("/meeting", get(api::get_meeting_state))
+        .route("/meeting/platforms", get(api::get_meeting_platforms))
+        .route("/meetings", get(api::get_meetings))
+        // Reports
+        .route("/reports/daily", get(api::get_daily_report))
+        .route("/reports/weekly", get(api::get_weekly_report))
+        .route("/tasks", get(api::get_tasks))
+        .route("/export/summary", get(api::export_summary))
+        .route("/export/daily", get(api::export_daily_report))
+        .route("/export/weekly", get(api::export_weekly_report))
+        .route("/export/obsidian", post(api::export_obsidian))
+        .route("/hotkeys", get(api::get_hotkeys))
+        .route("/jobs", get(api::get_jobs))
+        .route("/jobs/:name/run", post(api::run_job))
+        // Focus sessions
+        .route("/focus", get(api::get_focus))
+        .route("/focus/start", post(api::start_focus))
+        .route("/focus/stop", post(api::stop_focus))
+        .route("/focus-sessions", get(api::get_focus_sessions))
+        // Notes and annotations
+        .route("/notes", post(api::create_note))
+        .route("/notes/:id", patch(api::update_note))
+        // Clipboard history
+        .route("/clipboard", get(api::get_clipboard_history))
+        .route("/clipboard/:id", get(api::get_clipboard_entry))
+        .route("/clipboard/:id/restore", post(api::restore_clipboard_entry))
+        .route("/clipboard/:id/image", get(api::get_clipboard_image))
         // Summary/insights
         .route("/summary", get(api::get_session_summary))
         // Query endpoints
@@ -419,7 +663,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/storage", get(api::get_storage_info))
         .route("/storage/cleanup", post(api::cleanup_events))
         .layer(cors)
-        .with_state(state);
+        .with_state(Arc::clone(&state));
+    let shutdown_state = state;
 
     // Serve static UI files if directory exists
     if let Some(ui_path) = ui_dir {
@@ -431,7 +676,322 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Listening on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    on_shutdown(&shutdown_state);
+    info!("Siphon daemon stopped");
 
     Ok(())
 }
+
+/// Wait for Ctrl+C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.ok();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("Shutting down...");
+}
+
+/// Flush in-progress state so nothing open is lost when the daemon stops
+fn on_shutdown(state: &AppState) {
+    let now = Utc::now();
+
+    if let Ok(mut tracker_guard) = state.window_tracker.lock() {
+        if let Some(ref mut tracker) = *tracker_guard {
+            tracker.close_span(now);
+            persist_focus_spans(state, tracker);
+        }
+    }
+
+    if let Ok(mut detector) = state.meeting_detector.lock() {
+        end_meeting(state, &mut detector, now);
+    }
+
+    let session = state
+        .idle_detector
+        .lock()
+        .ok()
+        .and_then(|mut idle| idle.end_session());
+    persist_sessions(state, session.into_iter().collect());
+
+    let running = state
+        .focus
+        .lock()
+        .ok()
+        .and_then(|mut focus| focus.stop(now));
+    if let Some(session) = running {
+        finish_focus_session(state, session);
+    }
+}
+
+/// Record a lock, unlock, suspend or resume and bring the trackers in line
+///
+/// Lock and suspend close the window span and any meeting at the moment they
+/// happen and end the work session; unlock and resume restart the timers that
+/// can't see the time spent asleep.
+fn handle_power_event(state: &AppState, event: &PowerEvent) {
+    info!("Power event: {} ({})", event.kind, event.source);
+
+    if let Ok(store) = state.store.lock() {
+        let event_json = serde_json::to_string(event).unwrap_or_default();
+        if let Err(e) = store.insert_event_at(
+            EventSource::System,
+            &event.kind.to_string(),
+            &event_json,
+            None,
+            event.timestamp,
+        ) {
+            warn!("Failed to store {} event: {}", event.kind, e);
+        }
+    }
+
+    if let Ok(mut tracker_guard) = state.window_tracker.lock() {
+        if let Some(ref mut tracker) = *tracker_guard {
+            if event.kind.is_away() {
+                tracker.close_span(event.timestamp);
+                persist_focus_spans(state, tracker);
+            } else {
+                tracker.reset_change_timer();
+            }
+        }
+    }
+
+    if event.kind.is_away() {
+        let ended = state
+            .meeting_detector
+            .lock()
+            .is_ok_and(|mut detector| end_meeting(state, &mut detector, event.timestamp));
+        if ended {
+            if let Ok(mut focus) = state.focus.lock() {
+                focus.set_in_meeting(false, "", event.timestamp);
+            }
+        }
+    } else if let Ok(mut dedup) = state.dedup.lock() {
+        // Its window is measured in `Instant`s, which stood still during sleep
+        dedup.clear();
+    }
+
+    if let Ok(mut idle) = state.idle_detector.lock() {
+        if event.kind.is_away() {
+            idle.set_in_meeting(false);
+        }
+        if let Some(idle_event) = idle.record_power_event(event) {
+            store_idle_event(state, &idle_event);
+        }
+        persist_sessions(state, idle.drain_completed_sessions());
+    }
+}
+
+/// End any meeting in progress at `at` and store its end event and span;
+/// true if one was going on
+fn end_meeting(state: &AppState, detector: &mut MeetingDetector, at: DateTime<Utc>) -> bool {
+    let event = detector.end_meeting(at);
+    if let Some(ref event) = event {
+        store_meeting_event(state, event);
+    }
+    persist_meeting_spans(state, detector);
+    event.is_some()
+}
+
+/// Store a meeting event at the time it happened
+fn store_meeting_event(state: &AppState, event: &MeetingEvent) {
+    if let Ok(store) = state.store.lock() {
+        let event_json = serde_json::to_string(event).unwrap_or_default();
+        if let Err(e) = store.insert_event_at(
+            EventSource::Meeting,
+            &event.event_type.to_string(),
+            &event_json,
+            None,
+            event.timestamp,
+        ) {
+            warn!("Failed to store meeting event: {}", e);
+        }
+    }
+}
+
+/// Write meeting spans opened, closed or missed since the last call
+fn persist_meeting_spans(state: &AppState, detector: &mut MeetingDetector) {
+    let spans = detector.drain_span_updates();
+    if spans.is_empty() {
+        return;
+    }
+
+    if let Ok(store) = state.store.lock() {
+        for span in spans {
+            if let Err(e) = store.save_meeting_span(&span) {
+                warn!("Failed to store meeting span: {}", e);
+            }
+        }
+    }
+}
+
+/// Check for idle, ending the focus span on going idle, and store finished sessions
+fn check_idle(state: &AppState) -> JobResult {
+    let mut idle = state.idle_detector.lock().map_err(|e| e.to_string())?;
+    let mut message = String::new();
+    if let Some(idle_event) = idle.check_idle() {
+        // Going idle ends the focus span at the last activity
+        if idle_event.new_state != "active" {
+            let idle_since =
+                Utc::now() - chrono::Duration::seconds(idle_event.idle_duration_seconds as i64);
+            if let Ok(mut tracker_guard) = state.window_tracker.lock() {
+                if let Some(ref mut tracker) = *tracker_guard {
+                    tracker.close_span(idle_since);
+                }
+            }
+        }
+
+        store_idle_event(state, &idle_event);
+        message = format!("Now {}", idle_event.new_state);
+    }
+    persist_sessions(state, idle.drain_completed_sessions());
+    Ok(message)
+}
+
+/// Store reports for finished days and weeks before retention removes their events
+fn store_reports(state: &AppState) -> JobResult {
+    let generator = ReportGenerator::new(
+        state.summary_config.clone(),
+        Arc::clone(&state.categories),
+        state.retention_days,
+    );
+    let store = state.store.lock().map_err(|e| e.to_string())?;
+    let stored = generator
+        .store_completed(&store, &Local, Utc::now())
+        .map_err(|e| e.to_string())?;
+    Ok(format!("Stored {} report(s)", stored))
+}
+
+/// Remove events and clipboard images older than the retention period
+fn cleanup_retention(
+    store: &EventStore,
+    blobs: &BlobStore,
+    retention_days: u32,
+    max_blob_bytes: u64,
+) -> JobResult {
+    let deleted = store
+        .cleanup_old_events(retention_days)
+        .map_err(|e| e.to_string())?;
+    // Clipboard images follow the same retention as events
+    let removed = blobs
+        .cleanup(
+            Duration::from_secs(retention_days as u64 * 86400),
+            max_blob_bytes,
+        )
+        .map_err(|e| format!("Failed to clean up clipboard blobs: {}", e))?;
+    Ok(format!(
+        "Removed {} event(s) and {} clipboard image(s) older than {} days",
+        deleted, removed, retention_days
+    ))
+}
+
+/// Update the `## Siphon` block in yesterday's and today's daily notes
+fn export_daily_notes(state: &AppState) -> JobResult {
+    let today = Local::now().date_naive();
+    let generator = ReportGenerator::new(
+        state.summary_config.clone(),
+        Arc::clone(&state.categories),
+        state.retention_days,
+    );
+    let store = state.store.lock().map_err(|e| e.to_string())?;
+
+    let mut updated = 0;
+    for date in [today.pred_opt(), Some(today)].into_iter().flatten() {
+        let report = generator
+            .report(&store, ReportPeriod::Day(date), &Local, Utc::now())
+            .map_err(|e| e.to_string())?;
+        let block = export::daily_note_block(&report, &Local);
+        let (_, changed) = export::write_daily_note(&state.obsidian_config, date, &block)
+            .map_err(|e| e.to_string())?;
+        if changed {
+            updated += 1;
+        }
+    }
+    Ok(format!("Updated {} daily note(s)", updated))
+}
+
+/// Store an idle state change
+fn store_idle_event(state: &AppState, idle_event: &IdleEventData) {
+    if let Ok(store) = state.store.lock() {
+        let event_json = serde_json::to_string(idle_event).unwrap_or_default();
+        let _ = store.insert_event(
+            EventSource::Shell, // Use shell as source for idle events
+            "idle_state_change",
+            &event_json,
+            None,
+        );
+    }
+}
+
+/// Store work sessions that have ended
+fn persist_sessions(state: &AppState, sessions: Vec<SessionData>) {
+    if sessions.is_empty() {
+        return;
+    }
+
+    if let Ok(store) = state.store.lock() {
+        for session in sessions {
+            match store.save_session(&session) {
+                Ok(()) => info!(
+                    "Saved session {} ({}m, {}m active)",
+                    session.session_id, session.duration_minutes, session.active_minutes
+                ),
+                Err(e) => warn!("Failed to store session: {}", e),
+            }
+        }
+    }
+}
+
+/// Score and store a focus session that has just stopped
+fn finish_focus_session(state: &AppState, session: FocusSession) {
+    if let Ok(store) = state.store.lock() {
+        let generator = SummaryGenerator::with_categories(
+            state.summary_config.clone(),
+            Arc::clone(&state.categories),
+        );
+        match focus::complete_session(session, &store, &generator) {
+            Ok(session) => info!(
+                "Focus session ended after {}m ({} interruption(s), score {})",
+                session.duration_minutes(Utc::now()),
+                session.interruptions.len(),
+                session.focus_score.unwrap_or_default()
+            ),
+            Err(e) => warn!("Failed to store focus session: {}", e),
+        }
+    }
+}
+
+/// Write focus spans opened or closed since the last call
+fn persist_focus_spans(state: &AppState, tracker: &mut WindowTracker) {
+    let spans = tracker.drain_span_updates();
+    if spans.is_empty() {
+        return;
+    }
+
+    if let Ok(store) = state.store.lock() {
+        for span in spans {
+            if let Err(e) = store.save_focus_span(&span) {
+                warn!("Failed to store focus span: {}", e);
+            }
+        }
+    }
+}
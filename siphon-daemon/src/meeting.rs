@@ -2,19 +2,36 @@
 //!
 //! Detects when the user is in a video conference meeting based on active window.
 //! Supports Zoom, Google Meet, Microsoft Teams, and other common meeting apps.
+//! With a [`Calendar`], detected meetings take the scheduled title and
+//! attendee count, and scheduled meetings nobody joined are reported as missed.
+//! A [`MediaSignalSource`] adds microphone and camera use as evidence, which
+//! keeps a call going in a background tab and starts calls in apps we don't
+//! know by name while one is scheduled; on Linux it's
+//! [`linux::LinuxMediaSignals`], polled off-thread by [`BackgroundMediaSignals`].
+//!
+//! Each meeting is also kept as a [`MeetingSpan`] with its real start and end,
+//! which the daemon drains and persists like window focus spans.
 
 use chrono::{DateTime, Duration, Utc};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::Instant;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
+use crate::calendar::{Calendar, CalendarEntry};
 use crate::window::WindowInfo;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 /// Known meeting application patterns
 const MEETING_APPS: &[MeetingAppPattern] = &[
     MeetingAppPattern {
         app_name_contains: &["zoom.us", "Zoom"],
         title_patterns: &["Zoom Meeting", "Zoom Webinar"],
+        domains: &["zoom.us"],
+        join_paths: &[r"^/(j|s|w|wc|my)/"],
         platform: MeetingPlatform::Zoom,
     },
     MeetingAppPattern {
@@ -26,51 +43,76 @@ const MEETING_APPS: &[MeetingAppPattern] = &[
             "Microsoft Edge",
         ],
         title_patterns: &["Meet -", "Google Meet", "meet.google.com"],
+        domains: &["meet.google.com"],
+        join_paths: &[r"^/[a-z]{3}-[a-z]{4}-[a-z]{3}\b", r"^/lookup/"],
         platform: MeetingPlatform::GoogleMeet,
     },
     MeetingAppPattern {
         app_name_contains: &["Microsoft Teams", "Teams"],
         title_patterns: &["Meeting", "| Microsoft Teams"],
+        domains: &["teams.microsoft.com", "teams.live.com"],
+        join_paths: &[r"^/l/meetup-join/", r"^/meet/"],
         platform: MeetingPlatform::Teams,
     },
     MeetingAppPattern {
         app_name_contains: &["Slack"],
         title_patterns: &["Huddle", "Call"],
+        domains: &[],
+        join_paths: &[],
         platform: MeetingPlatform::Slack,
     },
     MeetingAppPattern {
         app_name_contains: &["Discord"],
         title_patterns: &["Voice Connected", "Video Call"],
+        domains: &[],
+        join_paths: &[],
         platform: MeetingPlatform::Discord,
     },
     MeetingAppPattern {
         app_name_contains: &["FaceTime"],
         title_patterns: &["FaceTime"],
+        domains: &["facetime.apple.com"],
+        join_paths: &[r"^/join"],
         platform: MeetingPlatform::FaceTime,
     },
     MeetingAppPattern {
         app_name_contains: &["Webex", "Cisco Webex"],
         title_patterns: &["Meeting", "Webex"],
+        domains: &["webex.com"],
+        join_paths: &[r"^/(meet|join)/", r"/j\.php", r"^/wbxmjs/joinservice/"],
         platform: MeetingPlatform::Webex,
     },
     MeetingAppPattern {
         app_name_contains: &["Around"],
         title_patterns: &["Around"],
+        domains: &["around.co"],
+        join_paths: &[r"^/r/"],
         platform: MeetingPlatform::Around,
     },
     MeetingAppPattern {
         app_name_contains: &["Loom"],
         title_patterns: &["Recording", "Loom"],
+        domains: &[],
+        join_paths: &[],
         platform: MeetingPlatform::Loom,
     },
 ];
 
+/// [`MEETING_APPS`] with their regexes compiled
+static BUILTIN_PATTERNS: LazyLock<Vec<MeetingPattern>> =
+    LazyLock::new(|| MEETING_APPS.iter().map(MeetingPattern::builtin).collect());
+
 /// Pattern for matching meeting applications
 struct MeetingAppPattern {
     /// Substrings to match in app name
     app_name_contains: &'static [&'static str],
     /// Substrings to match in window title
     title_patterns: &'static [&'static str],
+    /// Hosts of the platform's meeting pages and call links
+    domains: &'static [&'static str],
+    /// Regexes for the path (and query) of a meeting on those hosts; the
+    /// platform's chat, settings and marketing pages don't count
+    join_paths: &'static [&'static str],
     /// The platform this pattern matches
     platform: MeetingPlatform,
 }
@@ -89,6 +131,40 @@ pub enum MeetingPlatform {
     Around,
     Loom,
     Unknown,
+    /// A platform defined in the config, stored as its label
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl MeetingPlatform {
+    /// Built-in platform of a call link, from its host
+    pub fn from_url(url: &str) -> Option<Self> {
+        BUILTIN_PATTERNS
+            .iter()
+            .find(|pattern| pattern.matches_url(url))
+            .map(|pattern| pattern.platform.clone())
+    }
+
+    /// Platform for a config label; built-in names ("Zoom", "google_meet")
+    /// give the built-in platform, anything else a custom one
+    pub fn from_name(name: &str) -> Self {
+        let normalize = |s: &str| {
+            s.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let wanted = normalize(name);
+        MEETING_APPS
+            .iter()
+            .map(|pattern| &pattern.platform)
+            .find(|platform| {
+                normalize(&platform.to_string()) == wanted
+                    || normalize(&format!("{:?}", platform)) == wanted
+            })
+            .cloned()
+            .unwrap_or_else(|| MeetingPlatform::Custom(name.trim().to_string()))
+    }
 }
 
 impl std::fmt::Display for MeetingPlatform {
@@ -104,6 +180,7 @@ impl std::fmt::Display for MeetingPlatform {
             MeetingPlatform::Around => write!(f, "Around"),
             MeetingPlatform::Loom => write!(f, "Loom"),
             MeetingPlatform::Unknown => write!(f, "Unknown"),
+            MeetingPlatform::Custom(name) => write!(f, "{}", name),
         }
     }
 }
@@ -113,12 +190,153 @@ impl std::fmt::Display for MeetingPlatform {
 pub struct MeetingState {
     /// Whether currently in a meeting
     pub in_meeting: bool,
+    /// ID of the meeting's span
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meeting_id: Option<String>,
     /// The platform being used
     pub platform: Option<MeetingPlatform>,
     /// When the meeting started
     pub started_at: Option<DateTime<Utc>>,
-    /// Meeting title (from window title, may contain participant info)
+    /// Meeting title (from the calendar, else the window title)
     pub title: Option<String>,
+    /// Number of attendees on the matching calendar entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attendee_count: Option<u32>,
+    /// UID of the matching calendar entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_uid: Option<String>,
+    /// Signals seen so far during the meeting
+    #[serde(default)]
+    pub evidence: MeetingEvidence,
+    /// How sure we are, given the evidence
+    #[serde(default)]
+    pub confidence: MeetingConfidence,
+}
+
+/// Signals seen during a meeting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeetingEvidence {
+    /// A meeting app or call page was focused
+    pub window: bool,
+    /// Some app was capturing audio
+    pub microphone: bool,
+    /// Some app had the camera open
+    pub camera: bool,
+    /// The meeting matched a calendar entry
+    pub calendar: bool,
+    /// Apps that used the microphone or camera
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media_apps: Vec<String>,
+}
+
+impl MeetingEvidence {
+    /// Add signals seen later in the same meeting
+    fn merge(&mut self, other: &MeetingEvidence) {
+        self.window |= other.window;
+        self.microphone |= other.microphone;
+        self.camera |= other.camera;
+        self.calendar |= other.calendar;
+        for app in &other.media_apps {
+            if !self.media_apps.contains(app) {
+                self.media_apps.push(app.clone());
+            }
+        }
+    }
+
+    /// Confidence from the signals seen
+    ///
+    /// A meeting window or an open camera is good evidence on its own; the
+    /// microphone (dictation, voice notes) and the calendar only back it up.
+    pub fn confidence(&self) -> MeetingConfidence {
+        let score = 2 * self.window as u8
+            + 2 * self.camera as u8
+            + self.microphone as u8
+            + self.calendar as u8;
+        match score {
+            0..=1 => MeetingConfidence::Low,
+            2 => MeetingConfidence::Medium,
+            _ => MeetingConfidence::High,
+        }
+    }
+}
+
+/// How sure we are that a detected meeting is one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingConfidence {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+/// Apps using the microphone and camera right now
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaSignals {
+    /// Apps with an active audio capture stream
+    pub microphone: Vec<String>,
+    /// Apps with a camera open
+    pub camera: Vec<String>,
+}
+
+impl MediaSignals {
+    /// Apps using either, without repeats
+    fn apps(&self) -> Vec<String> {
+        let mut apps: Vec<String> = Vec::new();
+        for app in self.microphone.iter().chain(&self.camera) {
+            if !apps.contains(app) {
+                apps.push(app.clone());
+            }
+        }
+        apps
+    }
+}
+
+/// Where microphone and camera usage comes from; swapped out in tests
+pub trait MediaSignalSource: Send {
+    fn poll(&mut self) -> MediaSignals;
+}
+
+/// Polls another source on its own thread, so slow probes (subprocesses,
+/// `/proc` scans) stay off the caller's; `poll` returns the latest reading
+pub struct BackgroundMediaSignals {
+    latest: Arc<Mutex<MediaSignals>>,
+}
+
+impl BackgroundMediaSignals {
+    /// Poll `source` every `interval` until this is dropped
+    pub fn spawn(
+        mut source: impl MediaSignalSource + 'static,
+        interval: std::time::Duration,
+    ) -> Self {
+        let latest = Arc::new(Mutex::new(MediaSignals::default()));
+        let shared = Arc::clone(&latest);
+        let spawned = std::thread::Builder::new()
+            .name("media-signals".to_string())
+            .spawn(move || {
+                while Arc::strong_count(&shared) > 1 {
+                    let signals = source.poll();
+                    match shared.lock() {
+                        Ok(mut latest) => *latest = signals,
+                        Err(_) => return,
+                    }
+                    std::thread::sleep(interval);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Couldn't start media signal polling: {}", e);
+        }
+        Self { latest }
+    }
+}
+
+impl MediaSignalSource for BackgroundMediaSignals {
+    fn poll(&mut self) -> MediaSignals {
+        self.latest
+            .lock()
+            .map(|latest| latest.clone())
+            .unwrap_or_default()
+    }
 }
 
 /// Event emitted when meeting state changes
@@ -131,11 +349,58 @@ pub struct MeetingEvent {
     /// Meeting title
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
-    /// Duration in minutes (for meeting_end events)
+    /// Duration in minutes (for meeting_end events; scheduled length for meeting_missed)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration_minutes: Option<u32>,
-    /// Timestamp
+    /// Timestamp (scheduled start for meeting_missed events)
     pub timestamp: DateTime<Utc>,
+    /// Number of attendees on the matching calendar entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attendee_count: Option<u32>,
+    /// UID of the matching calendar entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_uid: Option<String>,
+    /// Signals seen (for meeting_start and meeting_end events)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<MeetingEvidence>,
+    /// How sure we are (for meeting_start and meeting_end events)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<MeetingConfidence>,
+    /// ID of the meeting's span
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meeting_id: Option<String>,
+}
+
+/// A meeting from start to end, as stored in the `meetings` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeetingSpan {
+    pub id: String,
+    pub platform: MeetingPlatform,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// None while the meeting is going on
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Number of attendees on the matching calendar entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attendee_count: Option<u32>,
+    /// UID of the matching calendar entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calendar_uid: Option<String>,
+    /// How sure we are; None for missed meetings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<MeetingConfidence>,
+    /// A scheduled meeting that nobody joined, spanning its scheduled time
+    pub missed: bool,
+}
+
+impl MeetingSpan {
+    /// Length in minutes, treating an open span as ending at `now`
+    pub fn duration_minutes(&self, now: DateTime<Utc>) -> u32 {
+        (self.ended_at.unwrap_or(now) - self.started_at)
+            .num_minutes()
+            .max(0) as u32
+    }
 }
 
 /// Types of meeting events
@@ -144,6 +409,8 @@ pub struct MeetingEvent {
 pub enum MeetingEventType {
     MeetingStart,
     MeetingEnd,
+    /// A scheduled meeting passed without one being detected
+    MeetingMissed,
 }
 
 impl std::fmt::Display for MeetingEventType {
@@ -151,17 +418,23 @@ impl std::fmt::Display for MeetingEventType {
         match self {
             MeetingEventType::MeetingStart => write!(f, "meeting_start"),
             MeetingEventType::MeetingEnd => write!(f, "meeting_end"),
+            MeetingEventType::MeetingMissed => write!(f, "meeting_missed"),
         }
     }
 }
 
-/// Configuration for meeting detection
-#[derive(Debug, Clone)]
+/// Configuration for meeting detection (the `[meeting]` config section)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct MeetingConfig {
     /// Minimum time in a meeting app to count as "in meeting" (prevents false positives)
     pub min_meeting_duration_secs: u64,
     /// Grace period after leaving meeting window before counting as "meeting ended"
     pub grace_period_secs: u64,
+    /// Extra platforms, checked before the built-in ones
+    pub platforms: Vec<MeetingPlatformConfig>,
+    /// Use microphone and camera activity as evidence (Linux only)
+    pub media_signals: bool,
 }
 
 impl Default for MeetingConfig {
@@ -169,10 +442,167 @@ impl Default for MeetingConfig {
         Self {
             min_meeting_duration_secs: 30,
             grace_period_secs: 60,
+            platforms: Vec::new(),
+            media_signals: false,
         }
     }
 }
 
+/// A `[[meeting.platforms]]` entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct MeetingPlatformConfig {
+    /// Platform label, e.g. "Jitsi"; a built-in name adds to that platform
+    pub name: String,
+    /// Substrings of the app name
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Regexes matched case-insensitively against the window title
+    #[serde(default)]
+    pub titles: Vec<String>,
+    /// Hosts (and their subdomains) of meeting pages and call links
+    #[serde(default)]
+    pub domains: Vec<String>,
+}
+
+/// How one platform's meeting windows and links are recognised
+#[derive(Debug, Clone)]
+pub struct MeetingPattern {
+    pub platform: MeetingPlatform,
+    /// Substrings of the app name
+    pub apps: Vec<String>,
+    /// Matched against the window title
+    pub titles: Vec<Regex>,
+    /// Hosts of the page in the window or of a calendar entry's call link
+    pub domains: Vec<String>,
+    /// Paths a URL on those hosts must match; empty for any page
+    pub join_paths: Vec<Regex>,
+    /// From the config file rather than built in. Built-in title patterns
+    /// only count in the platform's own apps.
+    pub custom: bool,
+}
+
+impl MeetingPattern {
+    fn builtin(pattern: &MeetingAppPattern) -> Self {
+        Self {
+            platform: pattern.platform.clone(),
+            apps: pattern
+                .app_name_contains
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            titles: pattern
+                .title_patterns
+                .iter()
+                .filter_map(|t| title_regex(&regex::escape(t)).ok())
+                .collect(),
+            domains: pattern.domains.iter().map(|s| s.to_string()).collect(),
+            join_paths: pattern
+                .join_paths
+                .iter()
+                .filter_map(|p| Regex::new(p).ok())
+                .collect(),
+            custom: false,
+        }
+    }
+
+    /// Build a pattern from config, skipping title regexes that don't compile
+    pub fn from_config(config: &MeetingPlatformConfig) -> Self {
+        let titles = config
+            .titles
+            .iter()
+            .filter_map(|t| match title_regex(t) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    warn!("Invalid title pattern for {}: {}", config.name, e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            platform: MeetingPlatform::from_name(&config.name),
+            apps: config.apps.clone(),
+            titles,
+            domains: config
+                .domains
+                .iter()
+                .map(|d| url_host(d).unwrap_or_else(|| d.trim().to_lowercase()))
+                .collect(),
+            join_paths: Vec::new(),
+            custom: true,
+        }
+    }
+
+    /// Whether a window is a meeting on this platform
+    fn matches(&self, window: &WindowInfo) -> bool {
+        if window
+            .url
+            .as_deref()
+            .is_some_and(|url| self.matches_url(url))
+        {
+            return true;
+        }
+
+        let app_name = window.app_name.to_lowercase();
+        let app_matches = self.matches_app(&app_name);
+        let title_matches = self.titles.iter().any(|re| re.is_match(&window.title));
+        if self.custom {
+            // Configured apps, titles and domains each identify the platform
+            return app_matches || title_matches;
+        }
+
+        // For browser-based meetings (Google Meet), require title match
+        // For native apps (Zoom, Teams), app name alone might be enough
+        app_matches && (title_matches || !is_browser(&app_name))
+    }
+
+    /// Whether a lowercased app name belongs to this platform
+    fn matches_app(&self, app_name: &str) -> bool {
+        self.apps
+            .iter()
+            .any(|a| app_name.contains(&a.to_lowercase()))
+    }
+
+    /// Whether a link points at this platform
+    pub fn matches_url(&self, url: &str) -> bool {
+        let Some(host) = url_host(url) else {
+            return false;
+        };
+        if !self.domains.iter().any(|d| host_in_domain(&host, d)) {
+            return false;
+        }
+        let path = url_path(url);
+        self.join_paths.is_empty() || self.join_paths.iter().any(|re| re.is_match(path))
+    }
+}
+
+fn title_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+/// Lowercased host of a URL
+fn url_host(url: &str) -> Option<String> {
+    let host = url
+        .split_once("://")?
+        .1
+        .split(['/', '?', '#', ':'])
+        .next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Path and query of a URL, `/` when it has neither
+fn url_path(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    match rest.find(['/', '?', '#']) {
+        Some(i) if rest[i..].starts_with('/') => &rest[i..],
+        _ => "/",
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains
+fn host_in_domain(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
 /// Detects when user is in a meeting
 pub struct MeetingDetector {
     config: MeetingConfig,
@@ -183,147 +613,354 @@ pub struct MeetingDetector {
     last_meeting_activity: Option<Instant>,
     /// Track if we've emitted a start event for current meeting
     emitted_start: bool,
+    /// Evidence gathered since meeting activity was first seen
+    pending_evidence: MeetingEvidence,
+    /// Microphone and camera usage
+    media: Option<Box<dyn MediaSignalSource>>,
+    /// Configured platforms followed by the built-in ones
+    patterns: Vec<MeetingPattern>,
+    /// Scheduled meetings to match against
+    calendar: Option<Calendar>,
+    /// Start and end of meetings detected in the last day
+    recent_meetings: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Calendar entries that ended before this have been checked for misses
+    missed_checked_until: DateTime<Utc>,
+    /// Entries starting before the detector was running can't be judged missed
+    watching_since: DateTime<Utc>,
+    /// Spans opened, closed or missed since last drained
+    span_updates: Vec<MeetingSpan>,
 }
 
 impl MeetingDetector {
     /// Create a new meeting detector
     pub fn new(config: MeetingConfig) -> Self {
+        let patterns = config
+            .platforms
+            .iter()
+            .map(MeetingPattern::from_config)
+            .chain(BUILTIN_PATTERNS.iter().cloned())
+            .collect();
         Self {
             config,
+            patterns,
             current_state: MeetingState::default(),
             potential_meeting_start: None,
             last_meeting_activity: None,
             emitted_start: false,
+            pending_evidence: MeetingEvidence::default(),
+            media: None,
+            calendar: None,
+            recent_meetings: Vec::new(),
+            missed_checked_until: Utc::now(),
+            watching_since: Utc::now(),
+            span_updates: Vec::new(),
         }
     }
 
+    /// Match detected meetings against scheduled ones
+    pub fn with_calendar(mut self, calendar: Calendar) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    /// Use microphone and camera activity as meeting evidence
+    pub fn with_media_signals(mut self, source: Box<dyn MediaSignalSource>) -> Self {
+        self.media = Some(source);
+        self
+    }
+
     /// Check window info and return any meeting events
     pub fn check_window(&mut self, window: Option<&WindowInfo>) -> Vec<MeetingEvent> {
         let mut events = Vec::new();
 
-        match window {
-            Some(w) => {
-                if let Some((platform, title)) = self.detect_meeting(w) {
-                    // We're in a meeting window
-                    self.last_meeting_activity = Some(Instant::now());
-
-                    if let Some(start) = self.potential_meeting_start {
-                        if !self.current_state.in_meeting {
-                            // Check if we've been in meeting long enough
-                            let elapsed = start.elapsed();
-                            if elapsed.as_secs() >= self.config.min_meeting_duration_secs {
-                                // Confirmed meeting
-                                self.current_state = MeetingState {
-                                    in_meeting: true,
-                                    platform: Some(platform.clone()),
-                                    started_at: Some(
-                                        Utc::now() - Duration::seconds(elapsed.as_secs() as i64),
-                                    ),
-                                    title: title.clone(),
-                                };
-                                self.emitted_start = true;
-
-                                info!("Meeting started: {} - {:?}", platform, title);
-                                events.push(MeetingEvent {
-                                    event_type: MeetingEventType::MeetingStart,
-                                    platform,
-                                    title,
-                                    duration_minutes: None,
-                                    timestamp: self.current_state.started_at.unwrap(),
-                                });
-                            }
-                        }
-                    } else {
-                        // First time seeing meeting activity
-                        self.potential_meeting_start = Some(Instant::now());
-                        debug!("Potential meeting detected: {:?}", platform);
-                    }
-                } else {
-                    // Not in a meeting window
-                    self.check_meeting_end(&mut events);
-                }
+        let detected = window.and_then(|w| self.detect_meeting(w));
+        let media = self
+            .media
+            .as_mut()
+            .map(|source| source.poll())
+            .unwrap_or_default();
+        let evidence = MeetingEvidence {
+            window: detected.is_some(),
+            microphone: !media.microphone.is_empty(),
+            camera: !media.camera.is_empty(),
+            calendar: false,
+            media_apps: media.apps(),
+        };
+
+        // The microphone or camera alone may be dictation or a voice memo:
+        // they start a meeting only while one is scheduled, and otherwise
+        // just keep a meeting going once a window has started it
+        let media_seen = evidence.microphone || evidence.camera;
+        let ongoing = self.current_state.in_meeting || self.potential_meeting_start.is_some();
+        let scheduled = self
+            .calendar
+            .as_ref()
+            .is_some_and(|c| c.find_match(Utc::now()).is_some());
+
+        if evidence.window || (media_seen && (ongoing || scheduled)) {
+            let (platform, title) = detected.unwrap_or_else(|| (self.media_platform(&media), None));
+            self.see_meeting(platform, title, evidence, &mut events);
+        } else {
+            // No sign of a meeting
+            self.check_meeting_end(&mut events);
+        }
+
+        self.check_missed(Utc::now(), &mut events);
+        events
+    }
+
+    /// Record meeting activity, starting a meeting once it has lasted long enough
+    fn see_meeting(
+        &mut self,
+        platform: MeetingPlatform,
+        title: Option<String>,
+        evidence: MeetingEvidence,
+        events: &mut Vec<MeetingEvent>,
+    ) {
+        self.last_meeting_activity = Some(Instant::now());
+        self.pending_evidence.merge(&evidence);
+
+        if self.current_state.in_meeting {
+            // A scheduled call picked up from the microphone may show its window later
+            let state = &mut self.current_state;
+            if state.platform == Some(MeetingPlatform::Unknown) {
+                state.platform = Some(platform);
             }
-            None => {
-                // No window info available
-                self.check_meeting_end(&mut events);
+            if state.title.is_none() {
+                state.title = title;
             }
+            state.evidence.merge(&evidence);
+            state.confidence = state.evidence.confidence();
+            return;
         }
 
-        events
+        let Some(start) = self.potential_meeting_start else {
+            // First time seeing meeting activity
+            self.potential_meeting_start = Some(Instant::now());
+            debug!("Potential meeting detected: {:?}", platform);
+            return;
+        };
+
+        // Check if we've been in meeting long enough
+        let elapsed = start.elapsed();
+        if elapsed.as_secs() < self.config.min_meeting_duration_secs {
+            return;
+        }
+
+        // Confirmed meeting; the calendar knows its real title
+        let started_at = Utc::now() - Duration::seconds(elapsed.as_secs() as i64);
+        let scheduled = self
+            .calendar
+            .as_ref()
+            .and_then(|c| c.find_match(started_at))
+            .cloned();
+        let title = scheduled
+            .as_ref()
+            .map(|e| e.title.clone())
+            .filter(|t| !t.is_empty())
+            .or(title);
+        let mut evidence = std::mem::take(&mut self.pending_evidence);
+        evidence.calendar = scheduled.is_some();
+        let confidence = evidence.confidence();
+        self.current_state = MeetingState {
+            in_meeting: true,
+            meeting_id: Some(uuid::Uuid::new_v4().to_string()),
+            platform: Some(platform.clone()),
+            started_at: Some(started_at),
+            title: title.clone(),
+            attendee_count: scheduled.as_ref().map(|e| e.attendee_count),
+            calendar_uid: scheduled.map(|e| e.uid),
+            evidence,
+            confidence,
+        };
+        self.emitted_start = true;
+        self.span_updates.extend(self.current_span(None));
+
+        info!(
+            "Meeting started: {} - {:?} ({:?} confidence)",
+            platform, title, confidence
+        );
+        events.push(MeetingEvent {
+            event_type: MeetingEventType::MeetingStart,
+            platform,
+            title,
+            duration_minutes: None,
+            timestamp: started_at,
+            attendee_count: self.current_state.attendee_count,
+            calendar_uid: self.current_state.calendar_uid.clone(),
+            evidence: Some(self.current_state.evidence.clone()),
+            confidence: Some(confidence),
+            meeting_id: self.current_state.meeting_id.clone(),
+        });
     }
 
     /// Check if meeting has ended (grace period expired)
     fn check_meeting_end(&mut self, events: &mut Vec<MeetingEvent>) {
         if let Some(last_activity) = self.last_meeting_activity {
             if last_activity.elapsed().as_secs() >= self.config.grace_period_secs {
-                // Meeting ended
-                if self.current_state.in_meeting && self.emitted_start {
-                    let duration = self
-                        .current_state
-                        .started_at
-                        .map(|start| (Utc::now() - start).num_minutes() as u32);
-
-                    info!(
-                        "Meeting ended: {:?} (duration: {:?} min)",
-                        self.current_state.platform, duration
-                    );
-
-                    events.push(MeetingEvent {
-                        event_type: MeetingEventType::MeetingEnd,
-                        platform: self
-                            .current_state
-                            .platform
-                            .clone()
-                            .unwrap_or(MeetingPlatform::Unknown),
-                        title: self.current_state.title.clone(),
-                        duration_minutes: duration,
-                        timestamp: Utc::now(),
-                    });
-                }
-
-                // Reset state
-                self.current_state = MeetingState::default();
-                self.potential_meeting_start = None;
-                self.last_meeting_activity = None;
-                self.emitted_start = false;
+                events.extend(self.end_meeting(Utc::now()));
             }
         }
     }
 
-    /// Detect if the current window is a meeting
-    fn detect_meeting(&self, window: &WindowInfo) -> Option<(MeetingPlatform, Option<String>)> {
-        let app_name = window.app_name.to_lowercase();
-        let title = window.title.to_lowercase();
+    /// End any meeting in progress at `at`, without waiting for the grace period
+    ///
+    /// Used when the screen locks or the machine goes to sleep.
+    pub fn end_meeting(&mut self, at: DateTime<Utc>) -> Option<MeetingEvent> {
+        let mut event = None;
+        if self.current_state.in_meeting && self.emitted_start {
+            let duration = self
+                .current_state
+                .started_at
+                .map(|start| (at - start).num_minutes().max(0) as u32);
 
-        for pattern in MEETING_APPS {
-            // Check app name
-            let app_matches = pattern
-                .app_name_contains
-                .iter()
-                .any(|p| app_name.contains(&p.to_lowercase()));
+            info!(
+                "Meeting ended: {:?} (duration: {:?} min)",
+                self.current_state.platform, duration
+            );
 
-            if !app_matches {
-                continue;
+            event = Some(MeetingEvent {
+                event_type: MeetingEventType::MeetingEnd,
+                platform: self
+                    .current_state
+                    .platform
+                    .clone()
+                    .unwrap_or(MeetingPlatform::Unknown),
+                title: self.current_state.title.clone(),
+                duration_minutes: duration,
+                timestamp: at,
+                attendee_count: self.current_state.attendee_count,
+                calendar_uid: self.current_state.calendar_uid.clone(),
+                evidence: Some(self.current_state.evidence.clone()),
+                confidence: Some(self.current_state.confidence),
+                meeting_id: self.current_state.meeting_id.clone(),
+            });
+            if let Some(start) = self.current_state.started_at {
+                self.recent_meetings.push((start, at));
             }
+            self.span_updates.extend(self.current_span(Some(at)));
+        }
 
-            // Check title patterns
-            let title_matches = pattern
-                .title_patterns
-                .iter()
-                .any(|p| title.contains(&p.to_lowercase()));
+        // Reset state
+        self.current_state = MeetingState::default();
+        self.potential_meeting_start = None;
+        self.last_meeting_activity = None;
+        self.emitted_start = false;
+        self.pending_evidence = MeetingEvidence::default();
 
-            // For browser-based meetings (Google Meet), require title match
-            // For native apps (Zoom, Teams), app name alone might be enough
-            let is_browser = ["chrome", "arc", "safari", "firefox", "edge"]
-                .iter()
-                .any(|b| app_name.contains(b));
+        event
+    }
 
-            if title_matches || (!is_browser && app_matches) {
-                return Some((pattern.platform.clone(), Some(window.title.clone())));
+    /// Report calendar entries that ended without a detected meeting
+    fn check_missed(&mut self, now: DateTime<Utc>, events: &mut Vec<MeetingEvent>) {
+        let Some(calendar) = self.calendar.as_mut() else {
+            return;
+        };
+        calendar.refresh(now);
+        let since = std::mem::replace(&mut self.missed_checked_until, now);
+        self.recent_meetings
+            .retain(|&(_, end)| now - end < Duration::days(1));
+        if !calendar.config().record_missed {
+            return;
+        }
+
+        let current = self
+            .current_state
+            .started_at
+            .filter(|_| self.current_state.in_meeting)
+            .map(|start| (start, now));
+        for entry in calendar.ended_between(since, now) {
+            if entry.start < self.watching_since {
+                continue;
             }
+            let attended = self
+                .recent_meetings
+                .iter()
+                .chain(current.iter())
+                .any(|&(start, end)| entry.overlaps(start, end));
+            if attended {
+                continue;
+            }
+
+            info!("Missed meeting: {} at {}", entry.title, entry.start);
+            let span = MeetingSpan {
+                id: uuid::Uuid::new_v4().to_string(),
+                platform: entry_platform(&self.patterns, entry),
+                title: Some(entry.title.clone()),
+                started_at: entry.start,
+                ended_at: Some(entry.end),
+                attendee_count: Some(entry.attendee_count),
+                calendar_uid: Some(entry.uid.clone()),
+                confidence: None,
+                missed: true,
+            };
+            events.push(MeetingEvent {
+                event_type: MeetingEventType::MeetingMissed,
+                platform: span.platform.clone(),
+                title: span.title.clone(),
+                duration_minutes: Some(entry.duration_minutes()),
+                timestamp: entry.start,
+                attendee_count: span.attendee_count,
+                calendar_uid: span.calendar_uid.clone(),
+                evidence: None,
+                confidence: None,
+                meeting_id: Some(span.id.clone()),
+            });
+            self.span_updates.push(span);
         }
+    }
+
+    /// Span of the meeting in progress, ending at `ended_at`
+    fn current_span(&self, ended_at: Option<DateTime<Utc>>) -> Option<MeetingSpan> {
+        let state = &self.current_state;
+        Some(MeetingSpan {
+            id: state.meeting_id.clone()?,
+            platform: state.platform.clone().unwrap_or(MeetingPlatform::Unknown),
+            title: state.title.clone(),
+            started_at: state.started_at?,
+            ended_at,
+            attendee_count: state.attendee_count,
+            calendar_uid: state.calendar_uid.clone(),
+            confidence: Some(state.confidence),
+            missed: false,
+        })
+    }
+
+    /// Take the spans opened, closed or missed since the last call, for persisting
+    pub fn drain_span_updates(&mut self) -> Vec<MeetingSpan> {
+        std::mem::take(&mut self.span_updates)
+    }
+
+    /// Detect if the current window is a meeting
+    fn detect_meeting(&self, window: &WindowInfo) -> Option<(MeetingPlatform, Option<String>)> {
+        self.patterns
+            .iter()
+            .find(|pattern| pattern.matches(window))
+            .map(|pattern| (pattern.platform.clone(), Some(window.title.clone())))
+    }
 
-        None
+    /// Platform of a native meeting app using the microphone or camera
+    ///
+    /// Browsers could be running any call, so they give Unknown.
+    fn media_platform(&self, media: &MediaSignals) -> MeetingPlatform {
+        media
+            .microphone
+            .iter()
+            .chain(&media.camera)
+            .map(|app| app.to_lowercase())
+            .filter(|app| !is_browser(app))
+            .find_map(|app| {
+                self.patterns
+                    .iter()
+                    .find(|pattern| pattern.matches_app(&app))
+                    .map(|pattern| pattern.platform.clone())
+            })
+            .unwrap_or(MeetingPlatform::Unknown)
+    }
+
+    /// Platform patterns in use, configured ones first
+    pub fn patterns(&self) -> &[MeetingPattern] {
+        &self.patterns
     }
 
     /// Get current meeting state
@@ -337,9 +974,36 @@ impl MeetingDetector {
     }
 }
 
+/// Whether a lowercased app name is a web browser
+fn is_browser(app_name: &str) -> bool {
+    ["chrome", "arc", "safari", "firefox", "edge"]
+        .iter()
+        .any(|b| app_name.contains(b))
+}
+
+/// Platform of a calendar entry, from its call link or location
+fn entry_platform(patterns: &[MeetingPattern], entry: &CalendarEntry) -> MeetingPlatform {
+    let location_links = entry
+        .location
+        .iter()
+        .flat_map(|location| location.split_whitespace());
+    let links: Vec<&str> = entry
+        .url
+        .as_deref()
+        .into_iter()
+        .chain(location_links)
+        .collect();
+    patterns
+        .iter()
+        .find(|pattern| links.iter().any(|link| pattern.matches_url(link)))
+        .map(|pattern| pattern.platform.clone())
+        .unwrap_or(MeetingPlatform::Unknown)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::calendar::CalendarConfig;
 
     #[test]
     fn test_meeting_config_default() {
@@ -415,4 +1079,473 @@ mod tests {
         let result = detector.detect_meeting(&window);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_end_meeting_at_sleep() {
+        let config = MeetingConfig {
+            min_meeting_duration_secs: 0,
+            grace_period_secs: 60,
+            ..Default::default()
+        };
+        let mut detector = MeetingDetector::new(config);
+        let window = WindowInfo {
+            app_name: "zoom.us".to_string(),
+            title: "Zoom Meeting".to_string(),
+            process_id: 1234,
+            bundle_id: None,
+            url: None,
+            bounds: None,
+        };
+
+        assert!(detector.end_meeting(Utc::now()).is_none());
+
+        detector.check_window(Some(&window));
+        let started = detector.check_window(Some(&window));
+        assert_eq!(started[0].event_type, MeetingEventType::MeetingStart);
+
+        let slept_at = Utc::now() + Duration::minutes(25);
+        let ended = detector.end_meeting(slept_at).unwrap();
+        assert_eq!(ended.event_type, MeetingEventType::MeetingEnd);
+        assert_eq!(ended.timestamp, slept_at);
+        assert_eq!(ended.duration_minutes, Some(25));
+        assert!(!detector.in_meeting());
+    }
+
+    #[test]
+    fn test_meeting_spans() {
+        let config = MeetingConfig {
+            min_meeting_duration_secs: 0,
+            ..Default::default()
+        };
+        let mut detector = MeetingDetector::new(config);
+        let zoom = window("zoom.us", "Zoom Meeting", None);
+
+        detector.check_window(Some(&zoom));
+        let started = detector.check_window(Some(&zoom));
+        let opened = detector.drain_span_updates();
+        assert_eq!(opened.len(), 1);
+        assert_eq!(opened[0].platform, MeetingPlatform::Zoom);
+        assert_eq!(Some(opened[0].started_at), detector.state().started_at);
+        assert!(opened[0].ended_at.is_none());
+        assert_eq!(started[0].meeting_id.as_ref(), Some(&opened[0].id));
+
+        let end = opened[0].started_at + Duration::minutes(40);
+        let ended = detector.end_meeting(end).unwrap();
+        let closed = detector.drain_span_updates();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].id, opened[0].id);
+        assert_eq!(closed[0].started_at, opened[0].started_at);
+        assert_eq!(closed[0].ended_at, Some(end));
+        assert_eq!(closed[0].duration_minutes(Utc::now()), 40);
+        assert!(!closed[0].missed);
+        assert_eq!(ended.meeting_id, Some(closed[0].id.clone()));
+
+        assert!(detector.drain_span_updates().is_empty());
+    }
+
+    fn entry(title: &str, start: DateTime<Utc>, minutes: i64) -> CalendarEntry {
+        CalendarEntry {
+            uid: format!("{}@example.com", title),
+            title: title.to_string(),
+            start,
+            end: start + Duration::minutes(minutes),
+            attendee_count: 4,
+            location: None,
+            url: Some("https://meet.google.com/abc-defg-hij".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_meeting_takes_calendar_title() {
+        let now = Utc::now();
+        let calendar = Calendar::from_entries(
+            CalendarConfig::default(),
+            vec![entry("Standup", now - Duration::minutes(2), 15)],
+        );
+        let mut detector = MeetingDetector::new(MeetingConfig {
+            min_meeting_duration_secs: 0,
+            grace_period_secs: 60,
+            ..Default::default()
+        })
+        .with_calendar(calendar);
+        let window = WindowInfo {
+            app_name: "zoom.us".to_string(),
+            title: "Zoom Meeting".to_string(),
+            process_id: 1234,
+            bundle_id: None,
+            url: None,
+            bounds: None,
+        };
+
+        detector.check_window(Some(&window));
+        let started = detector.check_window(Some(&window));
+        assert_eq!(started[0].title.as_deref(), Some("Standup"));
+        assert_eq!(started[0].attendee_count, Some(4));
+        assert_eq!(detector.state().title.as_deref(), Some("Standup"));
+
+        let ended = detector.end_meeting(Utc::now()).unwrap();
+        assert_eq!(ended.calendar_uid.as_deref(), Some("Standup@example.com"));
+    }
+
+    #[test]
+    fn test_missed_meetings() {
+        let start = Utc::now() + Duration::hours(1);
+        let calendar = Calendar::from_entries(
+            CalendarConfig::default(),
+            vec![
+                entry("Standup", start, 15),
+                entry("1:1", start + Duration::hours(1), 30),
+            ],
+        );
+        let mut detector = MeetingDetector::new(MeetingConfig::default()).with_calendar(calendar);
+
+        // Joined the standup a few minutes late
+        detector
+            .recent_meetings
+            .push((start + Duration::minutes(3), start + Duration::minutes(14)));
+
+        let mut events = Vec::new();
+        detector.check_missed(start + Duration::hours(3), &mut events);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, MeetingEventType::MeetingMissed);
+        assert_eq!(events[0].title.as_deref(), Some("1:1"));
+        assert_eq!(events[0].timestamp, start + Duration::hours(1));
+        assert_eq!(events[0].duration_minutes, Some(30));
+        assert_eq!(events[0].platform, MeetingPlatform::GoogleMeet);
+
+        let spans = detector.drain_span_updates();
+        assert_eq!(spans.len(), 1);
+        assert!(spans[0].missed);
+        assert_eq!(spans[0].started_at, start + Duration::hours(1));
+        assert_eq!(spans[0].ended_at, Some(start + Duration::minutes(90)));
+        assert_eq!(spans[0].calendar_uid.as_deref(), Some("1:1@example.com"));
+        assert_eq!(events[0].meeting_id, Some(spans[0].id.clone()));
+
+        // Each entry is only reported once
+        let mut again = Vec::new();
+        detector.check_missed(start + Duration::hours(4), &mut again);
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn test_platform_from_url() {
+        assert_eq!(
+            MeetingPlatform::from_url("https://us02web.zoom.us/j/123"),
+            Some(MeetingPlatform::Zoom)
+        );
+        assert_eq!(
+            MeetingPlatform::from_url("https://teams.microsoft.com/l/meetup-join/x"),
+            Some(MeetingPlatform::Teams)
+        );
+        assert_eq!(MeetingPlatform::from_url("https://notzoom.us/j/1"), None);
+        assert_eq!(MeetingPlatform::from_url("zoom.us"), None);
+
+        // Other pages on a meeting platform's hosts aren't meetings
+        assert_eq!(
+            MeetingPlatform::from_url("https://teams.microsoft.com/v2/#/conversations/19:abc"),
+            None
+        );
+        assert_eq!(MeetingPlatform::from_url("https://zoom.us/pricing"), None);
+        assert_eq!(
+            MeetingPlatform::from_url("https://meet.google.com/landing"),
+            None
+        );
+        assert_eq!(
+            MeetingPlatform::from_url("https://meet.google.com/abc-defg-hij?authuser=0"),
+            Some(MeetingPlatform::GoogleMeet)
+        );
+    }
+
+    #[test]
+    fn test_platform_chat_page_is_not_meeting() {
+        let detector = MeetingDetector::new(MeetingConfig::default());
+        let chat = window(
+            "Google Chrome",
+            "Chat | Microsoft Teams",
+            Some("https://teams.microsoft.com/v2/#/conversations/19:abc"),
+        );
+        assert!(detector.detect_meeting(&chat).is_none());
+
+        let call = window(
+            "Google Chrome",
+            "Join conversation",
+            Some("https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc"),
+        );
+        let (platform, _) = detector.detect_meeting(&call).unwrap();
+        assert_eq!(platform, MeetingPlatform::Teams);
+    }
+
+    /// Replays a fixed sequence of media readings, then repeats the last
+    struct MockSignals(std::collections::VecDeque<MediaSignals>);
+
+    impl MediaSignalSource for MockSignals {
+        fn poll(&mut self) -> MediaSignals {
+            if self.0.len() > 1 {
+                self.0.pop_front().unwrap()
+            } else {
+                self.0.front().cloned().unwrap_or_default()
+            }
+        }
+    }
+
+    fn mic() -> MediaSignals {
+        MediaSignals {
+            microphone: vec!["Google Chrome".to_string()],
+            camera: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_microphone_alone_does_not_start_meeting() {
+        let mut detector = MeetingDetector::new(MeetingConfig {
+            min_meeting_duration_secs: 0,
+            ..Default::default()
+        })
+        .with_media_signals(Box::new(MockSignals(vec![mic()].into())));
+        let editor = window("Visual Studio Code", "main.rs - siphon", None);
+
+        // Dictation or a voice memo, with nothing scheduled
+        for _ in 0..3 {
+            assert!(detector.check_window(Some(&editor)).is_empty());
+        }
+        assert!(!detector.in_meeting());
+    }
+
+    #[test]
+    fn test_microphone_starts_scheduled_meeting() {
+        let mic_and_camera = MediaSignals {
+            microphone: vec!["Google Chrome".to_string()],
+            camera: vec!["chrome".to_string()],
+        };
+        let source = MockSignals(vec![mic(), mic(), mic_and_camera].into());
+        let calendar = Calendar::from_entries(
+            CalendarConfig::default(),
+            vec![entry("Standup", Utc::now() - Duration::minutes(1), 15)],
+        );
+        let mut detector = MeetingDetector::new(MeetingConfig {
+            min_meeting_duration_secs: 0,
+            ..Default::default()
+        })
+        .with_calendar(calendar)
+        .with_media_signals(Box::new(source));
+        let editor = window("Visual Studio Code", "main.rs - siphon", None);
+
+        // A scheduled call in a background tab: no meeting window in sight
+        assert!(detector.check_window(Some(&editor)).is_empty());
+        let started = detector.check_window(Some(&editor));
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].platform, MeetingPlatform::Unknown);
+        assert_eq!(started[0].title.as_deref(), Some("Standup"));
+        assert_eq!(started[0].confidence, Some(MeetingConfidence::Medium));
+        let evidence = started[0].evidence.as_ref().unwrap();
+        assert!(evidence.microphone && evidence.calendar);
+
+        // Turning the camera on raises the confidence
+        detector.check_window(Some(&editor));
+        assert_eq!(detector.state().confidence, MeetingConfidence::High);
+        let ended = detector.end_meeting(Utc::now()).unwrap();
+        let evidence = ended.evidence.unwrap();
+        assert!(evidence.camera && evidence.microphone && !evidence.window);
+        assert_eq!(evidence.media_apps, vec!["Google Chrome", "chrome"]);
+    }
+
+    #[test]
+    fn test_microphone_extends_meeting() {
+        let source =
+            MockSignals(vec![MediaSignals::default(), MediaSignals::default(), mic()].into());
+        let mut detector = MeetingDetector::new(MeetingConfig {
+            min_meeting_duration_secs: 0,
+            grace_period_secs: 0,
+            ..Default::default()
+        })
+        .with_media_signals(Box::new(source));
+        let zoom = window("zoom.us", "Zoom Meeting", None);
+        let editor = window("Visual Studio Code", "main.rs - siphon", None);
+
+        detector.check_window(Some(&zoom));
+        assert_eq!(detector.check_window(Some(&zoom)).len(), 1);
+
+        // Switched away, still talking
+        assert!(detector.check_window(Some(&editor)).is_empty());
+        assert!(detector.in_meeting());
+        assert_eq!(detector.state().confidence, MeetingConfidence::High);
+    }
+
+    #[test]
+    fn test_background_media_signals() {
+        let mut signals = BackgroundMediaSignals::spawn(
+            MockSignals(vec![mic()].into()),
+            std::time::Duration::from_millis(10),
+        );
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        while signals.poll() != mic() && Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(signals.poll(), mic());
+    }
+
+    #[test]
+    fn test_evidence_confidence() {
+        let window = MeetingEvidence {
+            window: true,
+            ..Default::default()
+        };
+        assert_eq!(window.confidence(), MeetingConfidence::Medium);
+        let mic = MeetingEvidence {
+            microphone: true,
+            ..Default::default()
+        };
+        assert_eq!(mic.confidence(), MeetingConfidence::Low);
+        let scheduled_call = MeetingEvidence {
+            microphone: true,
+            calendar: true,
+            ..Default::default()
+        };
+        assert_eq!(scheduled_call.confidence(), MeetingConfidence::Medium);
+        let video_call = MeetingEvidence {
+            window: true,
+            microphone: true,
+            ..Default::default()
+        };
+        assert_eq!(video_call.confidence(), MeetingConfidence::High);
+
+        let media = MediaSignals {
+            microphone: vec!["ZOOM VoiceEngine".to_string()],
+            camera: Vec::new(),
+        };
+        let detector = MeetingDetector::new(MeetingConfig::default());
+        assert_eq!(detector.media_platform(&media), MeetingPlatform::Zoom);
+    }
+
+    fn jitsi_config() -> MeetingConfig {
+        MeetingConfig {
+            platforms: vec![
+                MeetingPlatformConfig {
+                    name: "Jitsi".to_string(),
+                    apps: vec!["Jitsi Meet".to_string()],
+                    titles: vec![r"^.+ \| Jitsi Meet$".to_string()],
+                    domains: vec!["meet.jit.si".to_string()],
+                },
+                MeetingPlatformConfig {
+                    name: "Huddle Room".to_string(),
+                    apps: Vec::new(),
+                    titles: vec!["huddle room".to_string(), "[unclosed".to_string()],
+                    domains: vec!["https://video.corp.example.com/rooms".to_string()],
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn window(app_name: &str, title: &str, url: Option<&str>) -> WindowInfo {
+        WindowInfo {
+            app_name: app_name.to_string(),
+            title: title.to_string(),
+            process_id: 1234,
+            bundle_id: None,
+            url: url.map(|u| u.to_string()),
+            bounds: None,
+        }
+    }
+
+    #[test]
+    fn test_detect_custom_platforms() {
+        let detector = MeetingDetector::new(jitsi_config());
+        let jitsi = MeetingPlatform::Custom("Jitsi".to_string());
+        let huddle_room = MeetingPlatform::Custom("Huddle Room".to_string());
+
+        // Native app by name
+        let (platform, _) = detector
+            .detect_meeting(&window("Jitsi Meet", "Jitsi Meet", None))
+            .unwrap();
+        assert_eq!(platform, jitsi);
+
+        // Browser tab by title regex, or by URL
+        let (platform, _) = detector
+            .detect_meeting(&window("Firefox", "Weekly sync | Jitsi Meet", None))
+            .unwrap();
+        assert_eq!(platform, jitsi);
+        let (platform, _) = detector
+            .detect_meeting(&window(
+                "Google Chrome",
+                "Weekly sync",
+                Some("https://meet.jit.si/WeeklySync"),
+            ))
+            .unwrap();
+        assert_eq!(platform, jitsi);
+        assert!(detector
+            .detect_meeting(&window("Firefox", "Jitsi Meet docs", None))
+            .is_none());
+
+        // Title-only pattern in any app; the bad regex is skipped
+        let (platform, _) = detector
+            .detect_meeting(&window("CorpVideo", "Huddle Room 4", None))
+            .unwrap();
+        assert_eq!(platform, huddle_room);
+        let (platform, _) = detector
+            .detect_meeting(&window(
+                "Firefox",
+                "Standup",
+                Some("https://eu.video.corp.example.com/r/42"),
+            ))
+            .unwrap();
+        assert_eq!(platform, huddle_room);
+
+        // Built-ins still work alongside
+        let (platform, _) = detector
+            .detect_meeting(&window("zoom.us", "Zoom Meeting", None))
+            .unwrap();
+        assert_eq!(platform, MeetingPlatform::Zoom);
+
+        assert_eq!(detector.patterns().len(), MEETING_APPS.len() + 2);
+        assert_eq!(detector.patterns()[1].titles.len(), 1);
+        assert_eq!(
+            detector.patterns()[1].domains,
+            vec!["video.corp.example.com"]
+        );
+    }
+
+    #[test]
+    fn test_platform_names() {
+        assert_eq!(MeetingPlatform::from_name("Zoom"), MeetingPlatform::Zoom);
+        assert_eq!(
+            MeetingPlatform::from_name("google_meet"),
+            MeetingPlatform::GoogleMeet
+        );
+        assert_eq!(
+            MeetingPlatform::from_name("Microsoft Teams"),
+            MeetingPlatform::Teams
+        );
+        let jitsi = MeetingPlatform::from_name("Jitsi");
+        assert_eq!(jitsi, MeetingPlatform::Custom("Jitsi".to_string()));
+        assert_eq!(jitsi.to_string(), "Jitsi");
+
+        // Custom platforms are stored as their label
+        assert_eq!(serde_json::to_string(&jitsi).unwrap(), r#""Jitsi""#);
+        assert_eq!(
+            serde_json::to_string(&MeetingPlatform::GoogleMeet).unwrap(),
+            r#""google_meet""#
+        );
+        let parsed: MeetingPlatform = serde_json::from_str(r#""Jitsi""#).unwrap();
+        assert_eq!(parsed, jitsi);
+        let parsed: MeetingPlatform = serde_json::from_str(r#""zoom""#).unwrap();
+        assert_eq!(parsed, MeetingPlatform::Zoom);
+    }
+
+    #[test]
+    fn test_missed_meeting_on_custom_platform() {
+        let start = Utc::now() + Duration::hours(1);
+        let mut standup = entry("Standup", start, 15);
+        standup.url = None;
+        standup.location = Some("Room 2 / https://meet.jit.si/standup".to_string());
+        let calendar = Calendar::from_entries(CalendarConfig::default(), vec![standup]);
+        let mut detector = MeetingDetector::new(jitsi_config()).with_calendar(calendar);
+
+        let mut events = Vec::new();
+        detector.check_missed(start + Duration::hours(1), &mut events);
+        assert_eq!(
+            events[0].platform,
+            MeetingPlatform::Custom("Jitsi".to_string())
+        );
+    }
 }
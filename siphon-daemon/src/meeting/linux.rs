@@ -0,0 +1,240 @@
+//! Microphone and camera usage on Linux
+//!
+//! - Microphone: capture streams listed by `pactl`, which talks to PulseAudio
+//!   and to PipeWire through pipewire-pulse. Paused (corked) streams, level
+//!   meters and streams recording an output's monitor (visualisers, screen
+//!   recorders) don't count.
+//! - Camera: processes with a `/dev/video*` device open, from `/proc/*/fd`.
+//!   Only our own user's processes are visible, which is all we care about.
+//!
+//! Both are read at most every few seconds and reused in between.
+
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use super::{MediaSignalSource, MediaSignals};
+
+/// How often readings are refreshed
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Microphone and camera users from PipeWire/PulseAudio and `/proc`
+pub struct LinuxMediaSignals {
+    proc_root: PathBuf,
+    last_poll: Option<Instant>,
+    last: MediaSignals,
+    /// Cleared once `pactl` is found missing, so we stop trying
+    pactl_available: bool,
+}
+
+impl LinuxMediaSignals {
+    pub fn new() -> Self {
+        Self::with_proc_root("/proc")
+    }
+
+    /// Read processes from another procfs mount
+    pub fn with_proc_root(proc_root: impl Into<PathBuf>) -> Self {
+        Self {
+            proc_root: proc_root.into(),
+            last_poll: None,
+            last: MediaSignals::default(),
+            pactl_available: true,
+        }
+    }
+
+    fn microphone_users(&mut self) -> Vec<String> {
+        if !self.pactl_available {
+            return Vec::new();
+        }
+        let outputs = match pactl(&["list", "source-outputs"]) {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    debug!("pactl not installed; microphone use won't be detected");
+                    self.pactl_available = false;
+                }
+                return Vec::new();
+            }
+        };
+        let sources = pactl(&["list", "sources"]).unwrap_or_default();
+        capture_apps(&outputs, &sources)
+    }
+}
+
+impl Default for LinuxMediaSignals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MediaSignalSource for LinuxMediaSignals {
+    fn poll(&mut self) -> MediaSignals {
+        if self
+            .last_poll
+            .is_some_and(|polled| polled.elapsed() < POLL_INTERVAL)
+        {
+            return self.last.clone();
+        }
+        self.last_poll = Some(Instant::now());
+
+        self.last = MediaSignals {
+            microphone: self.microphone_users(),
+            camera: camera_users(&self.proc_root),
+        };
+        self.last.clone()
+    }
+}
+
+/// Run `pactl -f json <args>`
+fn pactl(args: &[&str]) -> std::io::Result<String> {
+    let output = Command::new("pactl")
+        .args(["-f", "json"])
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        // No sound server, or a pactl too old for JSON output (< 16)
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[derive(Deserialize)]
+struct Source {
+    index: u32,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct SourceOutput {
+    source: u32,
+    #[serde(default)]
+    corked: bool,
+    #[serde(default)]
+    properties: HashMap<String, serde_json::Value>,
+}
+
+impl SourceOutput {
+    fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).and_then(|v| v.as_str())
+    }
+}
+
+/// Apps with a live capture stream, from `pactl -f json list source-outputs`
+/// and `pactl -f json list sources`
+pub fn capture_apps(source_outputs_json: &str, sources_json: &str) -> Vec<String> {
+    let Ok(outputs) = serde_json::from_str::<Vec<SourceOutput>>(source_outputs_json) else {
+        return Vec::new();
+    };
+    let monitors: HashSet<u32> = serde_json::from_str::<Vec<Source>>(sources_json)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| s.name.ends_with(".monitor"))
+        .map(|s| s.index)
+        .collect();
+
+    let mut apps: Vec<String> = outputs
+        .iter()
+        .filter(|o| !o.corked && !monitors.contains(&o.source))
+        .filter(|o| o.property("media.name") != Some("Peak detect"))
+        .filter_map(|o| {
+            o.property("application.name")
+                .or_else(|| o.property("application.process.binary"))
+                .map(|name| name.to_string())
+        })
+        .collect();
+    apps.sort();
+    apps.dedup();
+    apps
+}
+
+/// Names of processes with a `/dev/video*` device open
+pub fn camera_users(proc_root: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(proc_root) else {
+        return Vec::new();
+    };
+    let own_pid = std::process::id().to_string();
+
+    let mut apps: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name.chars().all(|c| c.is_ascii_digit()) && name != own_pid
+        })
+        .filter(|e| has_camera_open(&e.path()))
+        .filter_map(|e| std::fs::read_to_string(e.path().join("comm")).ok())
+        .map(|comm| comm.trim().to_string())
+        .collect();
+    apps.sort();
+    apps.dedup();
+    apps
+}
+
+fn has_camera_open(process: &Path) -> bool {
+    let Ok(fds) = std::fs::read_dir(process.join("fd")) else {
+        return false;
+    };
+    fds.filter_map(|fd| fd.ok())
+        .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+        .any(|target| target.to_string_lossy().starts_with("/dev/video"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCES: &str = r#"[
+        {"index": 55, "name": "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor"},
+        {"index": 56, "name": "alsa_input.pci-0000_00_1f.3.analog-stereo"}
+    ]"#;
+
+    const SOURCE_OUTPUTS: &str = r#"[
+        {"index": 101, "source": 56, "corked": false, "properties": {
+            "application.name": "Google Chrome", "application.process.id": "4242",
+            "media.name": "WebRTC"}},
+        {"index": 102, "source": 55, "corked": false, "properties": {
+            "application.name": "cava", "media.name": "cava"}},
+        {"index": 103, "source": 56, "corked": true, "properties": {
+            "application.name": "Audacity"}},
+        {"index": 104, "source": 56, "corked": false, "properties": {
+            "application.name": "PulseAudio Volume Control", "media.name": "Peak detect"}},
+        {"index": 105, "source": 56, "corked": false, "properties": {
+            "application.process.binary": "zoom"}}
+    ]"#;
+
+    #[test]
+    fn test_capture_apps() {
+        assert_eq!(
+            capture_apps(SOURCE_OUTPUTS, SOURCES),
+            vec!["Google Chrome", "zoom"]
+        );
+        assert!(capture_apps("[]", SOURCES).is_empty());
+        assert!(capture_apps("not json", SOURCES).is_empty());
+    }
+
+    #[test]
+    fn test_camera_users() {
+        let root = std::env::temp_dir().join(format!("siphon-proc-{}", uuid::Uuid::new_v4()));
+        let process = |pid: &str, comm: &str, fds: &[&str]| {
+            let dir = root.join(pid);
+            std::fs::create_dir_all(dir.join("fd")).unwrap();
+            std::fs::write(dir.join("comm"), format!("{}\n", comm)).unwrap();
+            for (i, target) in fds.iter().enumerate() {
+                std::os::unix::fs::symlink(target, dir.join("fd").join(i.to_string())).unwrap();
+            }
+        };
+        process("100", "zoom", &["/dev/null", "/dev/video0"]);
+        process("200", "firefox", &["/dev/null", "/dev/snd/pcmC0D0c"]);
+        process("300", "obs", &["/dev/video2"]);
+        std::fs::create_dir_all(root.join("self")).unwrap();
+
+        assert_eq!(camera_users(&root), vec!["obs", "zoom"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
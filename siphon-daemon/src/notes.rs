@@ -0,0 +1,158 @@
+//! Notes and annotations
+//!
+//! Freeform notes are stored as `note` events, optionally anchored to another
+//! event. Hotkey marks and triggers (mark moment, quick note) are annotated in
+//! place by filling in their `note` and `tags`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::hotkey::HotkeyTrigger;
+use crate::storage::Event;
+
+/// Longest note text accepted
+pub const MAX_NOTE_LENGTH: usize = 10_000;
+
+/// Event data of a `note` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteData {
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Event this note is about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+}
+
+/// Why an annotation couldn't be applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotateError {
+    /// Only notes and hotkey events can be annotated
+    NotAnnotatable(String),
+    /// The stored event data couldn't be parsed
+    Corrupt(String),
+}
+
+impl std::fmt::Display for AnnotateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnnotateError::NotAnnotatable(source) => {
+                write!(f, "{} events can't be annotated", source)
+            }
+            AnnotateError::Corrupt(e) => write!(f, "stored event data is invalid: {}", e),
+        }
+    }
+}
+
+/// Trim, drop a leading `#`, lowercase and dedupe tags
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// Apply a text and/or tag update to a note or hotkey event, returning the new event data
+///
+/// Fields left as `None` keep their current value.
+pub fn annotate(
+    event: &Event,
+    text: Option<&str>,
+    tags: Option<&[String]>,
+) -> Result<String, AnnotateError> {
+    let corrupt = |e: serde_json::Error| AnnotateError::Corrupt(e.to_string());
+    match event.source.as_str() {
+        "note" => {
+            let mut note: NoteData = serde_json::from_str(&event.event_data).map_err(corrupt)?;
+            if let Some(text) = text {
+                note.text = text.to_string();
+            }
+            if let Some(tags) = tags {
+                note.tags = normalize_tags(tags);
+            }
+            serde_json::to_string(&note).map_err(corrupt)
+        }
+        "hotkey" => {
+            let mut trigger: HotkeyTrigger =
+                serde_json::from_str(&event.event_data).map_err(corrupt)?;
+            if let Some(text) = text {
+                trigger.note = Some(text.to_string()).filter(|t| !t.is_empty());
+            }
+            if let Some(tags) = tags {
+                trigger.tags = normalize_tags(tags);
+            }
+            serde_json::to_string(&trigger).map_err(corrupt)
+        }
+        other => Err(AnnotateError::NotAnnotatable(other.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotkey::HotkeyAction;
+    use chrono::Utc;
+
+    fn event(source: &str, data: String) -> Event {
+        Event {
+            id: "id".to_string(),
+            timestamp: Utc::now(),
+            source: source.to_string(),
+            event_type: "x".to_string(),
+            event_data: data,
+            project: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = ["#Bug", " bug ", "", "Deploy", "#"].map(String::from);
+        assert_eq!(normalize_tags(&tags), vec!["bug", "deploy"]);
+    }
+
+    #[test]
+    fn test_annotate_note_keeps_unset_fields() {
+        let note = NoteData {
+            text: "first".to_string(),
+            tags: vec!["idea".to_string()],
+            event_id: Some("abc".to_string()),
+        };
+        let e = event("note", serde_json::to_string(&note).unwrap());
+
+        let data = annotate(&e, Some("second"), None).unwrap();
+        let updated: NoteData = serde_json::from_str(&data).unwrap();
+        assert_eq!(updated.text, "second");
+        assert_eq!(updated.tags, vec!["idea"]);
+        assert_eq!(updated.event_id.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_annotate_hotkey_mark() {
+        let trigger = HotkeyTrigger {
+            action: HotkeyAction::MarkMoment,
+            timestamp: Utc::now(),
+            note: None,
+            tags: Vec::new(),
+        };
+        let e = event("hotkey", serde_json::to_string(&trigger).unwrap());
+
+        let tags = ["#Release".to_string()];
+        let data = annotate(&e, Some("shipped v2"), Some(&tags)).unwrap();
+        let updated: HotkeyTrigger = serde_json::from_str(&data).unwrap();
+        assert_eq!(updated.note.as_deref(), Some("shipped v2"));
+        assert_eq!(updated.tags, vec!["release"]);
+        assert_eq!(updated.action, HotkeyAction::MarkMoment);
+    }
+
+    #[test]
+    fn test_other_events_are_not_annotatable() {
+        let e = event("shell", "{}".to_string());
+        assert_eq!(
+            annotate(&e, Some("x"), None),
+            Err(AnnotateError::NotAnnotatable("shell".to_string()))
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Screen lock, suspend and resume awareness
+//!
+//! `Instant` doesn't advance while the machine sleeps, so without these
+//! events the idle detector, meeting detector and deduplicator see a resume
+//! as a blink and carry sessions and spans straight across the gap. On Linux
+//! the events come from logind over D-Bus (see [`linux`]); elsewhere, and
+//! when logind isn't reachable, a jump in wall-clock time between ticks is
+//! treated as a suspend and resume.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::Receiver;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+/// A wall-clock gap between ticks longer than this means the machine slept
+pub const SLEEP_GAP_THRESHOLD: Duration = Duration::seconds(30);
+
+/// Lock and power state changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerEventKind {
+    Lock,
+    Unlock,
+    Suspend,
+    Resume,
+}
+
+impl PowerEventKind {
+    /// Whether the user is gone after this event
+    pub fn is_away(&self) -> bool {
+        matches!(self, PowerEventKind::Lock | PowerEventKind::Suspend)
+    }
+}
+
+impl std::fmt::Display for PowerEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PowerEventKind::Lock => write!(f, "lock"),
+            PowerEventKind::Unlock => write!(f, "unlock"),
+            PowerEventKind::Suspend => write!(f, "suspend"),
+            PowerEventKind::Resume => write!(f, "resume"),
+        }
+    }
+}
+
+/// Event data of a `system` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PowerEvent {
+    pub kind: PowerEventKind,
+    pub timestamp: DateTime<Utc>,
+    /// Where the event came from: `logind` or `clock_gap`
+    pub source: String,
+}
+
+impl PowerEvent {
+    pub fn new(kind: PowerEventKind, timestamp: DateTime<Utc>, source: &str) -> Self {
+        Self {
+            kind,
+            timestamp,
+            source: source.to_string(),
+        }
+    }
+}
+
+/// Receives lock and sleep events from the OS
+pub struct PowerMonitor {
+    receiver: Receiver<PowerEvent>,
+}
+
+impl PowerMonitor {
+    /// Start listening, or None when the platform gives us nothing to listen to
+    pub fn start() -> Option<Self> {
+        #[cfg(target_os = "linux")]
+        {
+            linux::start_logind_listener().map(|receiver| Self { receiver })
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            None
+        }
+    }
+
+    /// Events received since the last poll
+    pub fn poll_events(&self) -> Vec<PowerEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// Spots sleeps the OS didn't tell us about from gaps between ticks
+#[derive(Debug)]
+pub struct ClockGapDetector {
+    last_tick: DateTime<Utc>,
+}
+
+impl ClockGapDetector {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { last_tick: now }
+    }
+
+    /// Record a tick; a long gap since the last one becomes a suspend at the
+    /// last tick and a resume now
+    pub fn tick(&mut self, now: DateTime<Utc>) -> Vec<PowerEvent> {
+        let last_tick = std::mem::replace(&mut self.last_tick, now);
+        if now - last_tick > SLEEP_GAP_THRESHOLD {
+            vec![
+                PowerEvent::new(PowerEventKind::Suspend, last_tick, "clock_gap"),
+                PowerEvent::new(PowerEventKind::Resume, now, "clock_gap"),
+            ]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_gap_detector() {
+        let start = Utc::now();
+        let mut detector = ClockGapDetector::new(start);
+
+        assert!(detector.tick(start + Duration::seconds(1)).is_empty());
+        assert!(detector.tick(start + Duration::seconds(3)).is_empty());
+
+        let resumed_at = start + Duration::hours(2);
+        let events = detector.tick(resumed_at);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, PowerEventKind::Suspend);
+        assert_eq!(events[0].timestamp, start + Duration::seconds(3));
+        assert_eq!(events[1].kind, PowerEventKind::Resume);
+        assert_eq!(events[1].timestamp, resumed_at);
+
+        assert!(detector.tick(resumed_at + Duration::seconds(1)).is_empty());
+    }
+
+    #[test]
+    fn test_power_event_kind_names() {
+        assert_eq!(PowerEventKind::Suspend.to_string(), "suspend");
+        assert!(PowerEventKind::Lock.is_away());
+        assert!(!PowerEventKind::Resume.is_away());
+    }
+}
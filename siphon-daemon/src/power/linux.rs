@@ -0,0 +1,220 @@
+//! Lock and sleep signals from systemd-logind
+//!
+//! - `PrepareForSleep(true)` / `PrepareForSleep(false)` on the manager object
+//!   mark suspend and resume
+//! - `Lock` / `Unlock` on our session object mark the screen locking
+//!
+//! Signals are read on a background thread and handed over on a channel.
+
+use chrono::Utc;
+use std::sync::mpsc::{self, Receiver};
+use tracing::{debug, info};
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{MatchRule, Message};
+
+use super::{PowerEvent, PowerEventKind};
+
+const LOGIND_DESTINATION: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+/// Listen to logind on the system bus
+pub fn start_logind_listener() -> Option<Receiver<PowerEvent>> {
+    let conn = match Connection::system() {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Failed to connect to the system bus: {}", e);
+            return None;
+        }
+    };
+    match listen(conn) {
+        Ok(receiver) => {
+            info!("Listening for lock and sleep events from logind");
+            Some(receiver)
+        }
+        Err(e) => {
+            debug!("logind lock and sleep signals unavailable: {}", e);
+            None
+        }
+    }
+}
+
+/// Subscribe to logind's signals on `conn`
+pub fn listen(conn: Connection) -> zbus::Result<Receiver<PowerEvent>> {
+    // Match on logind's unique name so nobody else can fake a suspend
+    let owner = zbus::blocking::fdo::DBusProxy::new(&conn)?
+        .get_name_owner(LOGIND_DESTINATION.try_into()?)?;
+    let session_path = own_session_path(&conn);
+    if session_path.is_none() {
+        debug!("No logind session for this process; accepting locks from any session");
+    }
+
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .sender(owner.as_str())?
+        .path_namespace(LOGIND_PATH)?
+        .build();
+    let messages = MessageIterator::for_match_rule(rule, &conn, Some(64))?;
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("siphon-logind-power".to_string())
+        .spawn(move || {
+            for message in messages {
+                let Ok(message) = message else {
+                    continue;
+                };
+                if let Some(event) = parse_signal(&message, session_path.as_ref()) {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+            debug!("logind signal stream ended");
+        })
+        .map_err(|e| zbus::Error::InputOutput(std::sync::Arc::new(e)))?;
+
+    Ok(rx)
+}
+
+/// Object path of the session this process belongs to (or the user's display session)
+fn own_session_path(conn: &Connection) -> Option<OwnedObjectPath> {
+    let manager = zbus::blocking::Proxy::new(
+        conn,
+        LOGIND_DESTINATION,
+        LOGIND_PATH,
+        LOGIND_MANAGER_INTERFACE,
+    )
+    .ok()?;
+    manager.call("GetSession", &("auto",)).ok()
+}
+
+fn parse_signal(message: &Message, session_path: Option<&OwnedObjectPath>) -> Option<PowerEvent> {
+    let header = message.header();
+    let kind = match header.member()?.as_str() {
+        "PrepareForSleep" => {
+            if message.body().deserialize::<bool>().ok()? {
+                PowerEventKind::Suspend
+            } else {
+                PowerEventKind::Resume
+            }
+        }
+        member @ ("Lock" | "Unlock") => {
+            let path = header.path()?;
+            if session_path.is_some_and(|own| own.as_str() != path.as_str()) {
+                return None;
+            }
+            if member == "Lock" {
+                PowerEventKind::Lock
+            } else {
+                PowerEventKind::Unlock
+            }
+        }
+        _ => return None,
+    };
+    Some(PowerEvent::new(kind, Utc::now(), "logind"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::MockBus;
+    use std::time::Duration;
+
+    const SESSION_PATH: &str = "/org/freedesktop/login1/session/_31";
+    const SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+
+    struct MockManager;
+
+    #[zbus::interface(name = "org.freedesktop.login1.Manager")]
+    impl MockManager {
+        fn get_session(&self, _session_id: &str) -> OwnedObjectPath {
+            OwnedObjectPath::try_from(SESSION_PATH).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_logind_signals_over_mock_bus() {
+        let Some(bus) = MockBus::start() else {
+            eprintln!("dbus-daemon not installed, skipping");
+            return;
+        };
+        let server = zbus::blocking::connection::Builder::address(bus.address())
+            .unwrap()
+            .name(LOGIND_DESTINATION)
+            .unwrap()
+            .serve_at(LOGIND_PATH, MockManager)
+            .unwrap()
+            .build()
+            .unwrap();
+        let events = listen(bus.connect()).unwrap();
+
+        let prepare_for_sleep = |start: bool| {
+            server
+                .emit_signal(
+                    None::<&str>,
+                    LOGIND_PATH,
+                    LOGIND_MANAGER_INTERFACE,
+                    "PrepareForSleep",
+                    &(start,),
+                )
+                .unwrap();
+        };
+        let session_signal = |path: &str, member: &str| {
+            server
+                .emit_signal(None::<&str>, path, SESSION_INTERFACE, member, &())
+                .unwrap();
+        };
+
+        prepare_for_sleep(true);
+        session_signal(SESSION_PATH, "Lock");
+        // Another user's session
+        session_signal("/org/freedesktop/login1/session/_32", "Lock");
+        session_signal(SESSION_PATH, "Unlock");
+        prepare_for_sleep(false);
+
+        let kinds: Vec<_> = (0..4)
+            .map(|_| events.recv_timeout(Duration::from_secs(5)).unwrap().kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                PowerEventKind::Suspend,
+                PowerEventKind::Lock,
+                PowerEventKind::Unlock,
+                PowerEventKind::Resume
+            ]
+        );
+        assert!(events.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn test_signals_from_impostors_are_ignored() {
+        let Some(bus) = MockBus::start() else {
+            eprintln!("dbus-daemon not installed, skipping");
+            return;
+        };
+        let _server = zbus::blocking::connection::Builder::address(bus.address())
+            .unwrap()
+            .name(LOGIND_DESTINATION)
+            .unwrap()
+            .serve_at(LOGIND_PATH, MockManager)
+            .unwrap()
+            .build()
+            .unwrap();
+        let events = listen(bus.connect()).unwrap();
+
+        bus.connect()
+            .emit_signal(
+                None::<&str>,
+                LOGIND_PATH,
+                LOGIND_MANAGER_INTERFACE,
+                "PrepareForSleep",
+                &(true,),
+            )
+            .unwrap();
+        assert!(events.recv_timeout(Duration::from_millis(300)).is_err());
+    }
+}
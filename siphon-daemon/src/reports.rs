@@ -0,0 +1,592 @@
+//! Daily and weekly reports
+//!
+//! Reports cover a calendar day or an ISO week (Monday to Sunday) in the local
+//! timezone and compare against the period before. A report for a period that
+//! is over is stored in the `reports` table the first time it's generated and
+//! served from there afterwards, so it outlives the raw events that retention
+//! deletes.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::categories::CategoryRules;
+use crate::storage::EventStore;
+use crate::summary::{SessionSummary, SummaryConfig, SummaryGenerator};
+
+/// A calendar day or ISO week
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportPeriod {
+    Day(NaiveDate),
+    /// The week starting on this Monday
+    Week(NaiveDate),
+}
+
+impl ReportPeriod {
+    /// The week containing `date`
+    pub fn week_of(date: NaiveDate) -> Self {
+        let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        ReportPeriod::Week(monday)
+    }
+
+    /// Parse an ISO week such as `2026-W42`, or a date within the week
+    pub fn parse_week(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return Some(Self::week_of(date));
+        }
+        let (year, week) = value.split_once(['W', 'w'])?;
+        let year = year.trim_end_matches('-').parse().ok()?;
+        let week = week.parse().ok()?;
+        NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).map(ReportPeriod::Week)
+    }
+
+    pub fn kind(&self) -> ReportKind {
+        match self {
+            ReportPeriod::Day(_) => ReportKind::Daily,
+            ReportPeriod::Week(_) => ReportKind::Weekly,
+        }
+    }
+
+    /// `2026-10-18` for a day, `2026-W42` for a week
+    pub fn label(&self) -> String {
+        match self {
+            ReportPeriod::Day(date) => date.format("%Y-%m-%d").to_string(),
+            ReportPeriod::Week(monday) => {
+                let week = monday.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+
+    /// The days in the period
+    pub fn days(&self) -> Vec<NaiveDate> {
+        match *self {
+            ReportPeriod::Day(date) => vec![date],
+            ReportPeriod::Week(monday) => (0..7).map(|i| monday + Duration::days(i)).collect(),
+        }
+    }
+
+    /// The period just before this one
+    pub fn previous(&self) -> Self {
+        match *self {
+            ReportPeriod::Day(date) => ReportPeriod::Day(date - Duration::days(1)),
+            ReportPeriod::Week(monday) => ReportPeriod::Week(monday - Duration::weeks(1)),
+        }
+    }
+
+    /// Start and end of the period, from local midnight to local midnight
+    pub fn bounds<Tz: TimeZone>(&self, tz: &Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+        let days = self.days();
+        let first = days[0];
+        let after = days[days.len() - 1] + Duration::days(1);
+        (local_midnight(tz, first), local_midnight(tz, after))
+    }
+}
+
+/// Start of `date` in `tz`; where DST skips midnight, the first hour that exists
+fn local_midnight<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(midnight + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    Daily,
+    Weekly,
+}
+
+impl std::fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportKind::Daily => write!(f, "daily"),
+            ReportKind::Weekly => write!(f, "weekly"),
+        }
+    }
+}
+
+/// Headline numbers for a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportTotals {
+    /// Seconds of window focus
+    pub focused_seconds: u64,
+    pub event_count: usize,
+    pub meeting_count: usize,
+    pub meeting_minutes: u32,
+    /// Focus score (0-100)
+    pub focus_score: u32,
+    /// Focused seconds per activity category
+    pub time_by_category: HashMap<String, u64>,
+    /// Projects worked on, most time first
+    pub projects: Vec<String>,
+}
+
+impl ReportTotals {
+    fn from_summary(summary: &SessionSummary) -> Self {
+        Self {
+            focused_seconds: summary
+                .applications
+                .iter()
+                .map(|app| app.duration_seconds)
+                .sum(),
+            event_count: summary.total_events,
+            meeting_count: summary.meetings.len(),
+            meeting_minutes: summary.meetings.iter().map(|m| m.duration_minutes).sum(),
+            focus_score: summary.focus_score,
+            time_by_category: summary.time_by_category.clone(),
+            projects: summary.projects.iter().map(|p| p.name.clone()).collect(),
+        }
+    }
+}
+
+/// Totals for one day of a weekly report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayTotals {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub totals: ReportTotals,
+}
+
+/// The period a report is compared against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreviousPeriod {
+    pub period: String,
+    pub totals: ReportTotals,
+}
+
+/// Change from the previous period (this period minus the previous one)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportChanges {
+    pub focused_seconds: i64,
+    pub event_count: i64,
+    pub meeting_minutes: i64,
+    pub focus_score: i64,
+    /// Projects worked on in this period but not the previous one
+    pub new_projects: Vec<String>,
+    /// Projects worked on in the previous period but not this one
+    pub dropped_projects: Vec<String>,
+}
+
+impl ReportChanges {
+    fn between(current: &ReportTotals, previous: &ReportTotals) -> Self {
+        let missing_from = |from: &ReportTotals, other: &ReportTotals| {
+            from.projects
+                .iter()
+                .filter(|p| !other.projects.contains(p))
+                .cloned()
+                .collect()
+        };
+        Self {
+            focused_seconds: current.focused_seconds as i64 - previous.focused_seconds as i64,
+            event_count: current.event_count as i64 - previous.event_count as i64,
+            meeting_minutes: current.meeting_minutes as i64 - previous.meeting_minutes as i64,
+            focus_score: current.focus_score as i64 - previous.focus_score as i64,
+            new_projects: missing_from(current, previous),
+            dropped_projects: missing_from(previous, current),
+        }
+    }
+}
+
+/// A daily or weekly report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub kind: ReportKind,
+    /// `2026-10-18` or `2026-W42`
+    pub period: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Whether the period is over; only complete reports are stored
+    pub complete: bool,
+    pub generated_at: DateTime<Utc>,
+    pub totals: ReportTotals,
+    /// Full summary, None if nothing was recorded
+    pub summary: Option<SessionSummary>,
+    /// Totals per day (weekly reports only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<DayTotals>,
+    pub previous: PreviousPeriod,
+    pub changes: ReportChanges,
+}
+
+/// Generates reports, reusing stored ones
+pub struct ReportGenerator {
+    summaries: SummaryGenerator,
+    /// Events older than this many days are deleted
+    retention_days: u32,
+}
+
+impl ReportGenerator {
+    /// Create a report generator using the given summary settings,
+    /// categorisation rules and event retention
+    pub fn new(config: SummaryConfig, categories: Arc<CategoryRules>, retention_days: u32) -> Self {
+        let config = SummaryConfig {
+            // A quiet day still gets a summary
+            min_session_duration: 0,
+            ..config
+        };
+        Self {
+            summaries: SummaryGenerator::with_categories(config, categories),
+            retention_days,
+        }
+    }
+
+    /// The report for a period: the stored one if there is one, else a new
+    /// one, which is stored if it's worth keeping (see [`Self::keep`])
+    pub fn report<Tz: TimeZone>(
+        &self,
+        store: &EventStore,
+        period: ReportPeriod,
+        tz: &Tz,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<Report> {
+        if let Some(report) = store.get_report(period.kind(), &period.label())? {
+            return Ok(report);
+        }
+
+        let report = self.generate(store, period, tz, now);
+        if self.keep(&report, now) {
+            store.save_report(&report)?;
+        }
+        Ok(report)
+    }
+
+    /// Store reports for finished days and weeks that aren't stored yet, back
+    /// to the retention cutoff
+    ///
+    /// Run regularly so finished periods are kept before retention removes
+    /// their events. Returns how many reports were stored.
+    pub fn store_completed<Tz: TimeZone>(
+        &self,
+        store: &EventStore,
+        tz: &Tz,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<usize> {
+        let today = now.with_timezone(tz).date_naive();
+        let mut stored = 0;
+        for latest in [
+            ReportPeriod::Day(today).previous(),
+            ReportPeriod::week_of(today).previous(),
+        ] {
+            let mut period = latest;
+            while period.bounds(tz).0 >= self.retention_cutoff(now) {
+                if store.get_report(period.kind(), &period.label())?.is_none() {
+                    let report = self.generate(store, period, tz, now);
+                    if self.keep(&report, now) {
+                        store.save_report(&report)?;
+                        info!("Stored {} report for {}", report.kind, report.period);
+                        stored += 1;
+                    }
+                }
+                period = period.previous();
+            }
+        }
+        Ok(stored)
+    }
+
+    /// Oldest time whose events are still kept
+    fn retention_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(self.retention_days as i64)
+    }
+
+    /// Whether a report is final: the period is over, all its events are
+    /// still there, and there were some. A report missing events would be
+    /// stored wrong for good, and an empty one is cheap to make again.
+    fn keep(&self, report: &Report, now: DateTime<Utc>) -> bool {
+        report.complete
+            && report.start >= self.retention_cutoff(now)
+            && (report.totals.event_count > 0 || report.totals.focused_seconds > 0)
+    }
+
+    fn generate<Tz: TimeZone>(
+        &self,
+        store: &EventStore,
+        period: ReportPeriod,
+        tz: &Tz,
+        now: DateTime<Utc>,
+    ) -> Report {
+        let (start, end) = period.bounds(tz);
+        let summary = self.summary(store, start, end, now);
+        let totals = summary
+            .as_ref()
+            .map(ReportTotals::from_summary)
+            .unwrap_or_default();
+
+        let days = match period {
+            ReportPeriod::Day(_) => Vec::new(),
+            ReportPeriod::Week(_) => period
+                .days()
+                .into_iter()
+                .map(|date| DayTotals {
+                    date,
+                    totals: self.totals(store, ReportPeriod::Day(date), tz, now),
+                })
+                .collect(),
+        };
+
+        let previous_period = period.previous();
+        let previous = self.totals(store, previous_period, tz, now);
+
+        Report {
+            kind: period.kind(),
+            period: period.label(),
+            start,
+            end,
+            complete: end <= now,
+            generated_at: now,
+            changes: ReportChanges::between(&totals, &previous),
+            totals,
+            summary,
+            days,
+            previous: PreviousPeriod {
+                period: previous_period.label(),
+                totals: previous,
+            },
+        }
+    }
+
+    /// Totals for a period, from its stored report if there is one
+    fn totals<Tz: TimeZone>(
+        &self,
+        store: &EventStore,
+        period: ReportPeriod,
+        tz: &Tz,
+        now: DateTime<Utc>,
+    ) -> ReportTotals {
+        if let Ok(Some(report)) = store.get_report(period.kind(), &period.label()) {
+            return report.totals;
+        }
+        let (start, end) = period.bounds(tz);
+        self.summary(store, start, end, now)
+            .as_ref()
+            .map(ReportTotals::from_summary)
+            .unwrap_or_default()
+    }
+
+    /// Summary of `[start, end]`, up to `now` for a period still going on
+    fn summary(
+        &self,
+        store: &EventStore,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<SessionSummary> {
+        if start >= now {
+            return None;
+        }
+        self.summaries.generate_summary(store, start, end.min(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::EventSource;
+    use crate::window::{FocusSpan, WindowInfo};
+    use chrono::FixedOffset;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_periods() {
+        let week = ReportPeriod::parse_week("2026-W42").unwrap();
+        assert_eq!(week, ReportPeriod::Week(date("2026-10-12")));
+        assert_eq!(week.label(), "2026-W42");
+        assert_eq!(ReportPeriod::parse_week("2026-10-18"), Some(week));
+        assert_eq!(ReportPeriod::parse_week("2026w42"), Some(week));
+        assert!(ReportPeriod::parse_week("2026-W60").is_none());
+        assert!(ReportPeriod::parse_week("last week").is_none());
+        assert_eq!(week.previous().label(), "2026-W41");
+
+        // Week 1 can start in the previous calendar year
+        let first = ReportPeriod::parse_week("2026-W01").unwrap();
+        assert_eq!(first, ReportPeriod::Week(date("2025-12-29")));
+        assert_eq!(first.previous().label(), "2025-W52");
+
+        let day = ReportPeriod::Day(date("2026-10-18"));
+        assert_eq!(day.label(), "2026-10-18");
+        assert_eq!(day.previous().label(), "2026-10-17");
+
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let (start, end) = day.bounds(&tz);
+        assert_eq!(start.to_rfc3339(), "2026-10-17T22:00:00+00:00");
+        assert_eq!(end - start, Duration::days(1));
+        let (start, end) = week.bounds(&tz);
+        assert_eq!(start.to_rfc3339(), "2026-10-11T22:00:00+00:00");
+        assert_eq!(end - start, Duration::weeks(1));
+    }
+
+    #[test]
+    fn test_local_midnight_skipped_by_dst() {
+        // Chile moves clocks forward at midnight
+        let tz = chrono_tz::America::Santiago;
+        let start = local_midnight(&tz, date("2026-09-06"));
+        assert_eq!(start.to_rfc3339(), "2026-09-06T04:00:00+00:00");
+    }
+
+    fn record_day(store: &EventStore, start: DateTime<Utc>, project: &str, events: usize) {
+        for i in 0..events {
+            store
+                .insert_event_at(
+                    EventSource::Shell,
+                    "command",
+                    "{}",
+                    Some(project),
+                    start + Duration::minutes(i as i64),
+                )
+                .unwrap();
+        }
+        let window = WindowInfo {
+            app_name: "Code".to_string(),
+            title: "main.rs".to_string(),
+            process_id: 1,
+            bundle_id: None,
+            url: None,
+            bounds: None,
+        };
+        let mut span = FocusSpan::open(&window, start);
+        span.ended_at = Some(start + Duration::hours(2));
+        store.save_focus_span(&span).unwrap();
+    }
+
+    #[test]
+    fn test_daily_report_compares_and_is_stored() {
+        let store = EventStore::open_in_memory().unwrap();
+        let generator = ReportGenerator::new(
+            SummaryConfig::default(),
+            Arc::new(CategoryRules::default()),
+            30,
+        );
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let day = ReportPeriod::Day(date("2026-10-14"));
+        let (start, end) = day.bounds(&tz);
+        let (previous_start, _) = day.previous().bounds(&tz);
+
+        record_day(&store, previous_start + Duration::hours(9), "website", 5);
+        record_day(&store, start + Duration::hours(9), "siphon", 8);
+        record_day(&store, start + Duration::hours(14), "website", 2);
+        // Just past the local day
+        record_day(&store, end + Duration::minutes(5), "other", 1);
+
+        // While the day is going on, only the time so far counts
+        let midday = start + Duration::hours(12);
+        let partial = generator.report(&store, day, &tz, midday).unwrap();
+        assert!(!partial.complete);
+        assert_eq!(partial.totals.event_count, 8);
+        assert_eq!(partial.totals.focused_seconds, 2 * 3600);
+        assert!(store
+            .get_report(ReportKind::Daily, "2026-10-14")
+            .unwrap()
+            .is_none());
+
+        let now = end + Duration::days(3);
+        let report = generator.report(&store, day, &tz, now).unwrap();
+        assert!(report.complete);
+        assert_eq!(report.period, "2026-10-14");
+        assert_eq!(report.totals.event_count, 10);
+        assert_eq!(report.totals.focused_seconds, 4 * 3600);
+        assert_eq!(report.totals.projects, vec!["siphon", "website"]);
+        assert_eq!(report.previous.period, "2026-10-13");
+        assert_eq!(report.previous.totals.event_count, 5);
+        assert_eq!(report.changes.event_count, 5);
+        assert_eq!(report.changes.focused_seconds, 2 * 3600);
+        assert_eq!(report.changes.new_projects, vec!["siphon"]);
+        assert!(report.changes.dropped_projects.is_empty());
+
+        // Served from storage once the raw events are gone
+        store.cleanup_old_events(0).unwrap();
+        let stored = generator.report(&store, day, &tz, now).unwrap();
+        assert_eq!(stored.totals, report.totals);
+        assert_eq!(stored.generated_at, report.generated_at);
+    }
+
+    #[test]
+    fn test_weekly_report_and_store_completed() {
+        let store = EventStore::open_in_memory().unwrap();
+        let generator = ReportGenerator::new(
+            SummaryConfig::default(),
+            Arc::new(CategoryRules::default()),
+            30,
+        );
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let week = ReportPeriod::parse_week("2026-W42").unwrap();
+        let (start, _) = week.bounds(&tz);
+
+        record_day(&store, start + Duration::hours(9), "siphon", 3);
+        record_day(
+            &store,
+            start + Duration::days(2) + Duration::hours(9),
+            "siphon",
+            4,
+        );
+
+        // Tuesday of the following week: last week and its two busy days are
+        // stored, back to the retention cutoff
+        let now = start + Duration::days(8) + Duration::hours(10);
+        assert_eq!(generator.store_completed(&store, &tz, now).unwrap(), 3);
+        assert_eq!(generator.store_completed(&store, &tz, now).unwrap(), 0);
+        assert!(store
+            .get_report(ReportKind::Daily, "2026-10-14")
+            .unwrap()
+            .is_some());
+
+        let report = store
+            .get_report(ReportKind::Weekly, "2026-W42")
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.totals.event_count, 7);
+        assert_eq!(report.days.len(), 7);
+        assert_eq!(report.days[0].totals.event_count, 3);
+        assert_eq!(report.days[1].totals.event_count, 0);
+        assert_eq!(report.days[2].totals.event_count, 4);
+        assert_eq!(report.previous.period, "2026-W41");
+        assert!(store
+            .get_report(ReportKind::Daily, "2026-10-19")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_partial_and_empty_reports_are_not_stored() {
+        let store = EventStore::open_in_memory().unwrap();
+        let generator = ReportGenerator::new(
+            SummaryConfig::default(),
+            Arc::new(CategoryRules::default()),
+            7,
+        );
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let old_day = ReportPeriod::Day(date("2026-10-01"));
+        let (start, _) = old_day.bounds(&tz);
+        record_day(&store, start + Duration::hours(9), "siphon", 3);
+
+        // Past the retention cutoff, some of its events may be gone already
+        let now = start + Duration::days(10);
+        let report = generator.report(&store, old_day, &tz, now).unwrap();
+        assert!(report.complete);
+        assert!(store
+            .get_report(ReportKind::Daily, "2026-10-01")
+            .unwrap()
+            .is_none());
+
+        // Within retention, but nothing happened
+        let quiet_day = ReportPeriod::Day(date("2026-10-08"));
+        let report = generator.report(&store, quiet_day, &tz, now).unwrap();
+        assert_eq!(report.totals.event_count, 0);
+        assert!(store
+            .get_report(ReportKind::Daily, "2026-10-08")
+            .unwrap()
+            .is_none());
+        assert_eq!(generator.store_completed(&store, &tz, now).unwrap(), 0);
+    }
+}
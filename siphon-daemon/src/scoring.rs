@@ -0,0 +1,391 @@
+//! Focus score
+//!
+//! A score out of 100 is a base plus what each factor adds or takes away:
+//! window switching, the number of apps used, the share of productive time,
+//! and deep work (unbroken runs of coding and terminal focus). Every factor
+//! is reported with what was measured and its contribution, so a score can
+//! be explained. Penalties ramp up linearly between an "ok" and a "max"
+//! threshold; bonuses grow with the measured share up to a target. Weights
+//! and thresholds come from the `[focus_score]` config section.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Weights and thresholds for the focus score (the `[focus_score]` config section)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FocusScoreConfig {
+    /// Score before any factor applies
+    pub base: f64,
+    /// Most points lost to window switching
+    pub switching_weight: f64,
+    /// Switches per focused hour with no penalty
+    pub switches_per_hour_ok: f64,
+    /// Switches per focused hour for the full penalty
+    pub switches_per_hour_max: f64,
+    /// Most points lost to using many apps
+    pub apps_weight: f64,
+    pub apps_ok: usize,
+    pub apps_max: usize,
+    /// Most points gained from productive time
+    pub productive_weight: f64,
+    /// Share of productive time (or events) that earns the full bonus
+    pub productive_share_target: f64,
+    /// Most points gained from deep work
+    pub deep_work_weight: f64,
+    /// Share of focused time in deep work that earns the full bonus
+    pub deep_work_share_target: f64,
+    /// Shortest run of coding and terminal focus that counts as deep work
+    pub deep_work_min_minutes: u32,
+    /// Longer breaks than this end a run
+    pub deep_work_max_break_secs: u64,
+}
+
+impl Default for FocusScoreConfig {
+    fn default() -> Self {
+        Self {
+            base: 60.0,
+            switching_weight: 20.0,
+            switches_per_hour_ok: 15.0,
+            switches_per_hour_max: 30.0,
+            apps_weight: 10.0,
+            apps_ok: 5,
+            apps_max: 10,
+            productive_weight: 15.0,
+            productive_share_target: 0.5,
+            deep_work_weight: 25.0,
+            deep_work_share_target: 0.5,
+            deep_work_min_minutes: 25,
+            deep_work_max_break_secs: 60,
+        }
+    }
+}
+
+/// What was measured over a stretch of time
+#[derive(Debug, Clone, Default)]
+pub struct ScoreInput {
+    /// Length of the stretch
+    pub duration_seconds: u64,
+    /// Seconds with some window focused
+    pub focused_seconds: u64,
+    pub window_switches: usize,
+    pub app_count: usize,
+    pub productive_seconds: u64,
+    pub deep_work_seconds: u64,
+    pub event_count: usize,
+    /// Editor, file and shell command events
+    pub productive_events: usize,
+}
+
+/// A score and how it came about
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FocusScore {
+    /// 0-100
+    pub score: u32,
+    pub base: f64,
+    /// Empty when there was no activity (score 0)
+    pub factors: Vec<ScoreFactor>,
+}
+
+/// One factor of a focus score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreFactor {
+    /// e.g. `context_switching`
+    pub name: String,
+    /// What was measured, in `unit`
+    pub value: f64,
+    /// `switches_per_hour`, `apps`, `share` or `minutes`
+    pub unit: String,
+    /// Points added (positive) or taken away (negative)
+    pub contribution: f64,
+    /// Most points the factor can add or take away
+    pub weight: f64,
+    pub explanation: String,
+}
+
+/// Focus score for one hour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HourlyScore {
+    /// Start of the hour
+    pub hour: DateTime<Utc>,
+    /// None when nothing was recorded that hour
+    pub score: Option<u32>,
+}
+
+/// Scores activity with a configured model
+#[derive(Debug, Clone, Default)]
+pub struct FocusScorer {
+    config: FocusScoreConfig,
+}
+
+impl FocusScorer {
+    pub fn new(config: FocusScoreConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &FocusScoreConfig {
+        &self.config
+    }
+
+    /// Score a stretch of activity
+    pub fn score(&self, input: &ScoreInput) -> FocusScore {
+        let config = &self.config;
+        if input.event_count == 0 && input.focused_seconds == 0 {
+            return FocusScore {
+                score: 0,
+                base: config.base,
+                factors: Vec::new(),
+            };
+        }
+
+        // Switching is measured against time at the screen when we know it
+        let hours = if input.focused_seconds > 0 {
+            input.focused_seconds
+        } else {
+            input.duration_seconds
+        }
+        .max(60) as f64
+            / 3600.0;
+        let switches_per_hour = input.window_switches as f64 / hours;
+        let share = |seconds: u64| {
+            if input.focused_seconds == 0 {
+                0.0
+            } else {
+                seconds as f64 / input.focused_seconds as f64
+            }
+        };
+        let productive_time = share(input.productive_seconds);
+        let productive_events = if input.event_count == 0 {
+            0.0
+        } else {
+            input.productive_events as f64 / input.event_count as f64
+        };
+        let productive = productive_time.max(productive_events);
+        let deep_work = share(input.deep_work_seconds);
+
+        let factors = vec![
+            ScoreFactor {
+                name: "context_switching".to_string(),
+                value: round(switches_per_hour),
+                unit: "switches_per_hour".to_string(),
+                contribution: -config.switching_weight
+                    * ramp(
+                        switches_per_hour,
+                        config.switches_per_hour_ok,
+                        config.switches_per_hour_max,
+                    ),
+                weight: config.switching_weight,
+                explanation: format!(
+                    "{:.0} window switches per hour; penalty from {:.0}, full at {:.0}",
+                    switches_per_hour, config.switches_per_hour_ok, config.switches_per_hour_max
+                ),
+            },
+            ScoreFactor {
+                name: "app_count".to_string(),
+                value: input.app_count as f64,
+                unit: "apps".to_string(),
+                contribution: -config.apps_weight
+                    * ramp(
+                        input.app_count as f64,
+                        config.apps_ok as f64,
+                        config.apps_max as f64,
+                    ),
+                weight: config.apps_weight,
+                explanation: format!(
+                    "{} apps used; penalty above {}, full at {}",
+                    input.app_count, config.apps_ok, config.apps_max
+                ),
+            },
+            ScoreFactor {
+                name: "productive_time".to_string(),
+                value: round(productive),
+                unit: "share".to_string(),
+                contribution: config.productive_weight
+                    * progress(productive, config.productive_share_target),
+                weight: config.productive_weight,
+                explanation: format!(
+                    "{} of focused time and {} of events productive; full bonus at {}",
+                    percent(productive_time),
+                    percent(productive_events),
+                    percent(config.productive_share_target)
+                ),
+            },
+            ScoreFactor {
+                name: "deep_work".to_string(),
+                value: (input.deep_work_seconds / 60) as f64,
+                unit: "minutes".to_string(),
+                contribution: config.deep_work_weight
+                    * progress(deep_work, config.deep_work_share_target),
+                weight: config.deep_work_weight,
+                explanation: format!(
+                    "{} of {} focused minutes in coding or terminal runs of {}+ minutes; full bonus at {}",
+                    input.deep_work_seconds / 60,
+                    input.focused_seconds / 60,
+                    config.deep_work_min_minutes,
+                    percent(config.deep_work_share_target)
+                ),
+            },
+        ];
+        let factors: Vec<ScoreFactor> = factors
+            .into_iter()
+            .map(|factor| ScoreFactor {
+                contribution: round(factor.contribution) + 0.0,
+                ..factor
+            })
+            .collect();
+
+        let total = config.base + factors.iter().map(|f| f.contribution).sum::<f64>();
+        FocusScore {
+            score: total.round().clamp(0.0, 100.0) as u32,
+            base: config.base,
+            factors,
+        }
+    }
+}
+
+/// 0 at or below `ok`, rising to 1 at `max`
+fn ramp(value: f64, ok: f64, max: f64) -> f64 {
+    if value <= ok {
+        0.0
+    } else if value >= max || max <= ok {
+        1.0
+    } else {
+        (value - ok) / (max - ok)
+    }
+}
+
+/// `value` as a fraction of `target`, at most 1
+fn progress(value: f64, target: f64) -> f64 {
+    if target <= 0.0 {
+        return 1.0;
+    }
+    (value / target).clamp(0.0, 1.0)
+}
+
+/// Round to one decimal place
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn percent(share: f64) -> String {
+    format!("{:.0}%", share * 100.0)
+}
+
+/// Merge runs of deep-work focus into blocks of at least `min_minutes`
+///
+/// `runs` are the start and end of each deep-work focus span, in any order;
+/// gaps of up to `max_break_secs` between them don't end a block.
+pub fn deep_work_blocks(
+    runs: &[(DateTime<Utc>, DateTime<Utc>)],
+    min_minutes: u32,
+    max_break_secs: u64,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut runs = runs.to_vec();
+    runs.sort();
+    let max_break = chrono::Duration::seconds(max_break_secs as i64);
+
+    let mut blocks: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (start, end) in runs {
+        match blocks.last_mut() {
+            Some(block) if start - block.1 <= max_break => block.1 = block.1.max(end),
+            _ => blocks.push((start, end)),
+        }
+    }
+    blocks.retain(|(start, end)| *end - *start >= chrono::Duration::minutes(min_minutes as i64));
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn contribution(score: &FocusScore, name: &str) -> f64 {
+        score
+            .factors
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| f.contribution)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_no_activity_scores_zero() {
+        let score = FocusScorer::default().score(&ScoreInput::default());
+        assert_eq!(score.score, 0);
+        assert!(score.factors.is_empty());
+    }
+
+    #[test]
+    fn test_score_breakdown() {
+        let hour = 3600;
+        let input = ScoreInput {
+            duration_seconds: 2 * hour,
+            focused_seconds: 2 * hour,
+            // 45 per hour: full switching penalty
+            window_switches: 90,
+            // Halfway between 5 and 10
+            app_count: 7,
+            productive_seconds: hour,
+            deep_work_seconds: hour / 2,
+            event_count: 10,
+            productive_events: 2,
+        };
+        let score = FocusScorer::default().score(&input);
+
+        assert_eq!(contribution(&score, "context_switching"), -20.0);
+        assert_eq!(contribution(&score, "app_count"), -4.0);
+        // Half of focused time is productive: the full bonus
+        assert_eq!(contribution(&score, "productive_time"), 15.0);
+        // A quarter in deep work, half the target
+        assert_eq!(contribution(&score, "deep_work"), 12.5);
+        assert_eq!(score.score, 64);
+
+        let deep = score
+            .factors
+            .iter()
+            .find(|f| f.name == "deep_work")
+            .unwrap();
+        assert_eq!(deep.value, 30.0);
+        assert_eq!(deep.unit, "minutes");
+        assert!(deep.explanation.starts_with("30 of 120 focused minutes"));
+    }
+
+    #[test]
+    fn test_configured_weights() {
+        let config: FocusScoreConfig = toml::from_str(
+            "base = 100\nswitching_weight = 50\nswitches_per_hour_max = 20\ndeep_work_weight = 0",
+        )
+        .unwrap();
+        assert_eq!(config.switches_per_hour_ok, 15.0);
+
+        let input = ScoreInput {
+            focused_seconds: 3600,
+            window_switches: 20,
+            app_count: 2,
+            event_count: 1,
+            ..Default::default()
+        };
+        let score = FocusScorer::new(config).score(&input);
+        assert_eq!(contribution(&score, "context_switching"), -50.0);
+        assert_eq!(score.score, 50);
+    }
+
+    #[test]
+    fn test_deep_work_blocks() {
+        let start = Utc::now();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let runs = [
+            // Editor and terminal back to back, with a 30s look elsewhere
+            (at(10), at(20)),
+            (at(0), at(10)),
+            (at(20) + Duration::seconds(30), at(40)),
+            // Too short on its own
+            (at(60), at(75)),
+        ];
+
+        assert_eq!(deep_work_blocks(&runs, 25, 60), vec![(at(0), at(40))]);
+        // Without the grace the first block breaks at minute 20
+        assert!(deep_work_blocks(&runs, 25, 0).is_empty());
+    }
+}
@@ -1,11 +1,17 @@
 //! SQLite event storage
 
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use uuid::Uuid;
 
+use crate::focus::FocusSession;
+use crate::idle::SessionData;
+use crate::meeting::{MeetingPlatform, MeetingSpan};
+use crate::reports::{Report, ReportKind};
+use crate::window::FocusSpan;
+
 /// Event source types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -19,6 +25,8 @@ pub enum EventSource {
     Clipboard,
     Hotkey,
     Meeting,
+    Note,
+    System,
 }
 
 impl std::fmt::Display for EventSource {
@@ -33,6 +41,8 @@ impl std::fmt::Display for EventSource {
             EventSource::Clipboard => write!(f, "clipboard"),
             EventSource::Hotkey => write!(f, "hotkey"),
             EventSource::Meeting => write!(f, "meeting"),
+            EventSource::Note => write!(f, "note"),
+            EventSource::System => write!(f, "system"),
         }
     }
 }
@@ -71,6 +81,17 @@ pub struct Event {
     pub project: Option<String>,
 }
 
+/// Filters for clipboard history queries
+#[derive(Debug, Clone, Default)]
+pub struct ClipboardFilter {
+    /// Content type name, e.g. "code" or "url"
+    pub content_type: Option<String>,
+    /// Source app, case-insensitive
+    pub source_app: Option<String>,
+    /// Substring of the stored text, case-insensitive
+    pub search: Option<String>,
+}
+
 /// Event store backed by SQLite
 pub struct EventStore {
     conn: Connection,
@@ -80,8 +101,11 @@ pub struct EventStore {
 impl EventStore {
     /// Create a new event store, initializing the database if needed
     pub fn new() -> Result<Self> {
-        let db_path = Self::default_db_path();
+        Self::open(Self::default_db_path())
+    }
 
+    /// Open an event store at a specific path
+    pub fn open(db_path: PathBuf) -> Result<Self> {
         // Create parent directory if it doesn't exist
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).ok();
@@ -93,6 +117,17 @@ impl EventStore {
         Ok(store)
     }
 
+    /// Open a throwaway in-memory store
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        let store = Self {
+            conn: Connection::open_in_memory()?,
+            db_path: PathBuf::from(":memory:"),
+        };
+        store.init_schema()?;
+        Ok(store)
+    }
+
     /// Get the default database path (~/.siphon/events.db)
     fn default_db_path() -> PathBuf {
         dirs::home_dir()
@@ -135,6 +170,110 @@ impl EventStore {
             [],
         )?;
 
+        // Window focus spans (ended_at is NULL while the window is still focused)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS focus_spans (
+                id TEXT PRIMARY KEY,
+                app_name TEXT NOT NULL,
+                title TEXT NOT NULL,
+                url TEXT,
+                bundle_id TEXT,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                duration_ms INTEGER
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_focus_spans_started_at ON focus_spans(started_at)",
+            [],
+        )?;
+
+        // Meetings (ended_at is NULL while the meeting is going on; missed
+        // meetings span their scheduled time)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS meetings (
+                id TEXT PRIMARY KEY,
+                platform TEXT NOT NULL,
+                title TEXT,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                duration_minutes INTEGER,
+                attendee_count INTEGER,
+                calendar_uid TEXT,
+                confidence TEXT,
+                missed INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_meetings_started_at ON meetings(started_at)",
+            [],
+        )?;
+
+        // Daily and weekly reports for finished periods (the report as JSON);
+        // kept past retention so old reports don't need the raw events
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS reports (
+                kind TEXT NOT NULL,
+                period TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT NOT NULL,
+                generated_at TEXT NOT NULL,
+                report TEXT NOT NULL,
+                PRIMARY KEY (kind, period)
+            )",
+            [],
+        )?;
+
+        // Completed work sessions (idle periods and category time as JSON)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                started_at TEXT NOT NULL,
+                ended_at TEXT NOT NULL,
+                duration_minutes INTEGER NOT NULL,
+                active_minutes INTEGER NOT NULL,
+                event_count INTEGER NOT NULL,
+                had_meetings INTEGER NOT NULL,
+                idle_periods TEXT NOT NULL,
+                time_by_category TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_sessions_started_at ON sessions(started_at)",
+            [],
+        )?;
+
+        // Completed focus sessions (interruptions as a JSON array)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS focus_sessions (
+                id TEXT PRIMARY KEY,
+                started_at TEXT NOT NULL,
+                ended_at TEXT NOT NULL,
+                goal TEXT,
+                target_minutes INTEGER,
+                interruptions TEXT NOT NULL,
+                focus_score INTEGER
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_focus_sessions_started_at ON focus_sessions(started_at)",
+            [],
+        )?;
+
+        // Incremental read positions for collectors (e.g. last browser visit id per profile)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS collector_cursors (
+                name TEXT PRIMARY KEY,
+                value INTEGER NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -145,9 +284,21 @@ impl EventStore {
         event_type: &str,
         event_data: &str,
         project: Option<&str>,
+    ) -> Result<String> {
+        self.insert_event_at(source, event_type, event_data, project, Utc::now())
+    }
+
+    /// Insert a new event with an explicit timestamp
+    pub fn insert_event_at(
+        &self,
+        source: EventSource,
+        event_type: &str,
+        event_data: &str,
+        project: Option<&str>,
+        timestamp: DateTime<Utc>,
     ) -> Result<String> {
         let id = Uuid::new_v4().to_string();
-        let timestamp = Utc::now().to_rfc3339();
+        let timestamp = timestamp.to_rfc3339();
 
         self.conn.execute(
             "INSERT INTO events (id, timestamp, source, event_type, event_data, project)
@@ -165,6 +316,31 @@ impl EventStore {
         Ok(id)
     }
 
+    /// Get events between two times, newest first
+    pub fn get_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Event>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, source, event_type, event_data, project
+             FROM events
+             WHERE timestamp >= ?1 AND timestamp <= ?2
+             ORDER BY timestamp DESC
+             LIMIT ?3",
+        )?;
+
+        let events = stmt
+            .query_map(
+                params![start.to_rfc3339(), end.to_rfc3339(), limit.unwrap_or(1000)],
+                Self::row_to_event,
+            )?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(events)
+    }
+
     /// Get events within a time range
     pub fn get_events_since(&self, since: DateTime<Utc>, limit: Option<u32>) -> Result<Vec<Event>> {
         let limit = limit.unwrap_or(1000);
@@ -179,21 +355,117 @@ impl EventStore {
         )?;
 
         let events = stmt
-            .query_map(params![since_str, limit], |row| {
-                Ok(Event {
-                    id: row.get(0)?,
-                    timestamp: row
-                        .get::<_, String>(1)?
-                        .parse()
-                        .unwrap_or_else(|_| Utc::now()),
-                    source: row.get(2)?,
-                    event_type: row.get(3)?,
-                    event_data: row.get(4)?,
-                    project: row.get(5)?,
-                })
-            })?
+            .query_map(params![since_str, limit], Self::row_to_event)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(events)
+    }
+
+    /// Map an `id, timestamp, source, event_type, event_data, project` row
+    fn row_to_event(row: &rusqlite::Row) -> Result<Event> {
+        Ok(Event {
+            id: row.get(0)?,
+            timestamp: row
+                .get::<_, String>(1)?
+                .parse()
+                .unwrap_or_else(|_| Utc::now()),
+            source: row.get(2)?,
+            event_type: row.get(3)?,
+            event_data: row.get(4)?,
+            project: row.get(5)?,
+        })
+    }
+
+    /// Get clipboard changes, newest first, plus the total matching the filter
+    pub fn get_clipboard_events(
+        &self,
+        filter: &ClipboardFilter,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<Event>, i64)> {
+        const WHERE: &str = "source = 'clipboard' AND event_type = 'clipboard_change'
+             AND (?1 IS NULL OR json_extract(event_data, '$.content.content_type') = ?1)
+             AND (?2 IS NULL OR lower(json_extract(event_data, '$.source_app')) = lower(?2))
+             AND (?3 IS NULL OR json_extract(event_data, '$.content.text') LIKE ?3 ESCAPE '\\')";
+
+        let search = filter.search.as_ref().map(|q| {
+            let escaped = q
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{}%", escaped)
+        });
+        let filter_params = params![filter.content_type, filter.source_app, search];
+
+        let total: i64 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM events WHERE {}", WHERE),
+            filter_params,
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, timestamp, source, event_type, event_data, project
+             FROM events WHERE {}
+             ORDER BY timestamp DESC
+             LIMIT ?4 OFFSET ?5",
+            WHERE
+        ))?;
+        let events = stmt
+            .query_map(
+                params![
+                    filter.content_type,
+                    filter.source_app,
+                    search,
+                    limit,
+                    offset
+                ],
+                Self::row_to_event,
+            )?
             .collect::<Result<Vec<_>>>()?;
 
+        Ok((events, total))
+    }
+
+    /// Get a single event by id
+    pub fn get_event(&self, id: &str) -> Result<Option<Event>> {
+        self.conn
+            .query_row(
+                "SELECT id, timestamp, source, event_type, event_data, project
+                 FROM events WHERE id = ?1",
+                params![id],
+                Self::row_to_event,
+            )
+            .optional()
+    }
+
+    /// Replace an event's JSON data, returning whether the event exists
+    pub fn update_event_data(&self, id: &str, event_data: &str) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE events SET event_data = ?1 WHERE id = ?2",
+            params![event_data, id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Find clipboard changes by full id or id prefix (at most two, to detect ambiguity)
+    pub fn find_clipboard_events(&self, id_or_prefix: &str) -> Result<Vec<Event>> {
+        let prefix = format!("{}%", id_or_prefix.replace('%', "").replace('_', "\\_"));
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, source, event_type, event_data, project
+             FROM events
+             WHERE source = 'clipboard' AND event_type = 'clipboard_change'
+               AND (id = ?1 OR id LIKE ?2 ESCAPE '\\')
+             ORDER BY id = ?1 DESC
+             LIMIT 2",
+        )?;
+        let events = stmt
+            .query_map(params![id_or_prefix, prefix], Self::row_to_event)?
+            .collect::<Result<Vec<_>>>()?;
+
+        // An exact match wins over other ids sharing the prefix
+        if events.first().is_some_and(|e| e.id == id_or_prefix) {
+            return Ok(events.into_iter().take(1).collect());
+        }
         Ok(events)
     }
 
@@ -237,6 +509,22 @@ impl EventStore {
             "DELETE FROM events WHERE timestamp < ?1",
             params![cutoff_str],
         )?;
+        self.conn.execute(
+            "DELETE FROM focus_spans WHERE ended_at IS NOT NULL AND ended_at < ?1",
+            params![cutoff_str],
+        )?;
+        self.conn.execute(
+            "DELETE FROM meetings WHERE ended_at IS NOT NULL AND ended_at < ?1",
+            params![cutoff_str],
+        )?;
+        self.conn.execute(
+            "DELETE FROM focus_sessions WHERE ended_at < ?1",
+            params![cutoff_str],
+        )?;
+        self.conn.execute(
+            "DELETE FROM sessions WHERE ended_at < ?1",
+            params![cutoff_str],
+        )?;
 
         Ok(deleted)
     }
@@ -298,4 +586,677 @@ impl EventStore {
 
         Ok(counts)
     }
+
+    /// Insert or update a focus span
+    pub fn save_focus_span(&self, span: &FocusSpan) -> Result<()> {
+        let duration_ms = span
+            .ended_at
+            .map(|end| (end - span.started_at).num_milliseconds().max(0));
+
+        self.conn.execute(
+            "INSERT INTO focus_spans (id, app_name, title, url, bundle_id, started_at, ended_at, duration_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(id) DO UPDATE SET ended_at = excluded.ended_at, duration_ms = excluded.duration_ms",
+            params![
+                span.id,
+                span.app_name,
+                span.title,
+                span.url,
+                span.bundle_id,
+                span.started_at.to_rfc3339(),
+                span.ended_at.map(|t| t.to_rfc3339()),
+                duration_ms
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get focus spans overlapping a time range (including the open span, if any)
+    pub fn get_focus_spans(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FocusSpan>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, app_name, title, url, bundle_id, started_at, ended_at
+             FROM focus_spans
+             WHERE started_at < ?2 AND (ended_at IS NULL OR ended_at > ?1)
+             ORDER BY started_at ASC",
+        )?;
+
+        let spans = stmt
+            .query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+                Ok(FocusSpan {
+                    id: row.get(0)?,
+                    app_name: row.get(1)?,
+                    title: row.get(2)?,
+                    url: row.get(3)?,
+                    bundle_id: row.get(4)?,
+                    started_at: row
+                        .get::<_, String>(5)?
+                        .parse()
+                        .unwrap_or_else(|_| Utc::now()),
+                    ended_at: row
+                        .get::<_, Option<String>>(6)?
+                        .and_then(|t| t.parse().ok()),
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(spans)
+    }
+
+    /// Close spans left open by a crash or kill, ending them at the last event seen after they started
+    pub fn close_dangling_focus_spans(&self) -> Result<usize> {
+        let closed = self.conn.execute(
+            "UPDATE focus_spans
+             SET ended_at = COALESCE(
+                 (SELECT MAX(timestamp) FROM events WHERE events.timestamp >= focus_spans.started_at),
+                 started_at
+             )
+             WHERE ended_at IS NULL",
+            [],
+        )?;
+        // Keep duration_ms consistent with the new end times
+        self.conn.execute(
+            "UPDATE focus_spans
+             SET duration_ms = CAST((julianday(ended_at) - julianday(started_at)) * 86400000 AS INTEGER)
+             WHERE duration_ms IS NULL AND ended_at IS NOT NULL",
+            [],
+        )?;
+
+        Ok(closed)
+    }
+
+    /// Insert or update a meeting span
+    pub fn save_meeting_span(&self, span: &MeetingSpan) -> Result<()> {
+        let duration_minutes = span
+            .ended_at
+            .map(|_| span.duration_minutes(Utc::now()) as i64);
+
+        self.conn.execute(
+            "INSERT INTO meetings
+             (id, platform, title, started_at, ended_at, duration_minutes, attendee_count,
+              calendar_uid, confidence, missed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(id) DO UPDATE SET
+                 platform = excluded.platform, title = excluded.title,
+                 ended_at = excluded.ended_at, duration_minutes = excluded.duration_minutes,
+                 confidence = excluded.confidence",
+            params![
+                span.id,
+                serde_name(&span.platform),
+                span.title,
+                span.started_at.to_rfc3339(),
+                span.ended_at.map(|t| t.to_rfc3339()),
+                duration_minutes,
+                span.attendee_count,
+                span.calendar_uid,
+                span.confidence.map(|c| serde_name(&c)),
+                span.missed
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get meetings overlapping a time range (including one in progress, if any)
+    pub fn get_meeting_spans(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<MeetingSpan>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, platform, title, started_at, ended_at, attendee_count, calendar_uid,
+                    confidence, missed
+             FROM meetings
+             WHERE started_at < ?2 AND (ended_at IS NULL OR ended_at > ?1)
+             ORDER BY started_at ASC",
+        )?;
+
+        let spans = stmt
+            .query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+                Ok(MeetingSpan {
+                    id: row.get(0)?,
+                    platform: from_serde_name(row.get(1)?).unwrap_or(MeetingPlatform::Unknown),
+                    title: row.get(2)?,
+                    started_at: row
+                        .get::<_, String>(3)?
+                        .parse()
+                        .unwrap_or_else(|_| Utc::now()),
+                    ended_at: row
+                        .get::<_, Option<String>>(4)?
+                        .and_then(|t| t.parse().ok()),
+                    attendee_count: row.get(5)?,
+                    calendar_uid: row.get(6)?,
+                    confidence: row.get::<_, Option<String>>(7)?.and_then(from_serde_name),
+                    missed: row.get(8)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(spans)
+    }
+
+    /// Close meetings left open by a crash or kill, ending them at the last event seen after they started
+    pub fn close_dangling_meeting_spans(&self) -> Result<usize> {
+        let closed = self.conn.execute(
+            "UPDATE meetings
+             SET ended_at = COALESCE(
+                 (SELECT MAX(timestamp) FROM events WHERE events.timestamp >= meetings.started_at),
+                 started_at
+             )
+             WHERE ended_at IS NULL",
+            [],
+        )?;
+        self.conn.execute(
+            "UPDATE meetings
+             SET duration_minutes = CAST((julianday(ended_at) - julianday(started_at)) * 1440 AS INTEGER)
+             WHERE duration_minutes IS NULL AND ended_at IS NOT NULL",
+            [],
+        )?;
+
+        Ok(closed)
+    }
+
+    /// Store a daily or weekly report, replacing any earlier one for the period
+    pub fn save_report(&self, report: &Report) -> Result<()> {
+        let report_json = serde_json::to_string(report).unwrap_or_default();
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO reports (kind, period, started_at, ended_at, generated_at, report)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                report.kind.to_string(),
+                report.period,
+                report.start.to_rfc3339(),
+                report.end.to_rfc3339(),
+                report.generated_at.to_rfc3339(),
+                report_json
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the stored report for a period, e.g. `weekly` and `2026-W42`
+    pub fn get_report(&self, kind: ReportKind, period: &str) -> Result<Option<Report>> {
+        let report_json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT report FROM reports WHERE kind = ?1 AND period = ?2",
+                params![kind.to_string(), period],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(report_json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Store a completed work session
+    pub fn save_session(&self, session: &SessionData) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sessions
+             (id, started_at, ended_at, duration_minutes, active_minutes, event_count,
+              had_meetings, idle_periods, time_by_category)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                session.session_id,
+                session.started_at.to_rfc3339(),
+                session.ended_at.unwrap_or_else(Utc::now).to_rfc3339(),
+                session.duration_minutes as i64,
+                session.active_minutes as i64,
+                session.event_count as i64,
+                session.had_meetings,
+                serde_json::to_string(&session.idle_periods).unwrap_or_default(),
+                serde_json::to_string(&session.time_by_category).unwrap_or_default()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get work sessions overlapping a time range, oldest first
+    pub fn get_sessions(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SessionData>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, started_at, ended_at, duration_minutes, active_minutes, event_count,
+                    had_meetings, idle_periods, time_by_category
+             FROM sessions
+             WHERE started_at < ?2 AND ended_at > ?1
+             ORDER BY started_at ASC",
+        )?;
+
+        let sessions = stmt
+            .query_map(
+                params![start.to_rfc3339(), end.to_rfc3339()],
+                Self::row_to_session,
+            )?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(sessions)
+    }
+
+    /// Get a work session by id
+    pub fn get_session(&self, id: &str) -> Result<Option<SessionData>> {
+        self.conn
+            .query_row(
+                "SELECT id, started_at, ended_at, duration_minutes, active_minutes, event_count,
+                        had_meetings, idle_periods, time_by_category
+                 FROM sessions WHERE id = ?1",
+                params![id],
+                Self::row_to_session,
+            )
+            .optional()
+    }
+
+    fn row_to_session(row: &rusqlite::Row) -> Result<SessionData> {
+        Ok(SessionData {
+            session_id: row.get(0)?,
+            started_at: row
+                .get::<_, String>(1)?
+                .parse()
+                .unwrap_or_else(|_| Utc::now()),
+            ended_at: row.get::<_, String>(2)?.parse().ok(),
+            duration_minutes: row.get::<_, i64>(3)? as u64,
+            active_minutes: row.get::<_, i64>(4)? as u64,
+            event_count: row.get::<_, i64>(5)? as u64,
+            had_meetings: row.get(6)?,
+            idle_periods: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
+            time_by_category: serde_json::from_str(&row.get::<_, String>(8)?).unwrap_or_default(),
+        })
+    }
+
+    /// Store a completed focus session
+    pub fn save_focus_session(&self, session: &FocusSession) -> Result<()> {
+        let interruptions = serde_json::to_string(&session.interruptions).unwrap_or_default();
+        self.conn.execute(
+            "INSERT OR REPLACE INTO focus_sessions
+             (id, started_at, ended_at, goal, target_minutes, interruptions, focus_score)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                session.id,
+                session.started_at.to_rfc3339(),
+                session.ended_at.unwrap_or_else(Utc::now).to_rfc3339(),
+                session.goal,
+                session.target_minutes,
+                interruptions,
+                session.focus_score
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get completed focus sessions, newest first
+    pub fn get_focus_sessions(&self, limit: u32) -> Result<Vec<FocusSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, started_at, ended_at, goal, target_minutes, interruptions, focus_score
+             FROM focus_sessions
+             ORDER BY started_at DESC
+             LIMIT ?1",
+        )?;
+
+        let sessions = stmt
+            .query_map(params![limit], |row| {
+                Ok(FocusSession {
+                    id: row.get(0)?,
+                    started_at: row
+                        .get::<_, String>(1)?
+                        .parse()
+                        .unwrap_or_else(|_| Utc::now()),
+                    ended_at: row.get::<_, String>(2)?.parse().ok(),
+                    goal: row.get(3)?,
+                    target_minutes: row.get(4)?,
+                    interruptions: serde_json::from_str(&row.get::<_, String>(5)?)
+                        .unwrap_or_default(),
+                    focus_score: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(sessions)
+    }
+
+    /// Get a collector's saved read position
+    pub fn get_cursor(&self, name: &str) -> Result<Option<i64>> {
+        self.conn
+            .query_row(
+                "SELECT value FROM collector_cursors WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Save a collector's read position
+    pub fn set_cursor(&self, name: &str, value: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO collector_cursors (name, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![name, value, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+}
+
+/// Serialized name of an enum stored as text, e.g. "google_meet"
+fn serde_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// Enum value back from its [`serde_name`]
+fn from_serde_name<T: serde::de::DeserializeOwned>(name: String) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(name)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::WindowInfo;
+
+    fn window(app_name: &str) -> WindowInfo {
+        WindowInfo {
+            app_name: app_name.to_string(),
+            title: "title".to_string(),
+            process_id: 1,
+            bundle_id: None,
+            url: None,
+            bounds: None,
+        }
+    }
+
+    #[test]
+    fn test_focus_span_round_trip() {
+        let store = EventStore::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::minutes(30);
+
+        let mut span = FocusSpan::open(&window("Code"), start);
+        store.save_focus_span(&span).unwrap();
+
+        // Open spans are returned for any range they overlap
+        let spans = store.get_focus_spans(start, Utc::now()).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert!(spans[0].ended_at.is_none());
+
+        span.ended_at = Some(start + chrono::Duration::minutes(5));
+        store.save_focus_span(&span).unwrap();
+
+        let spans = store.get_focus_spans(start, Utc::now()).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].ended_at, span.ended_at);
+
+        // A range after the span closed no longer includes it
+        let later = start + chrono::Duration::minutes(10);
+        assert!(store.get_focus_spans(later, Utc::now()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_close_dangling_focus_spans() {
+        let store = EventStore::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::minutes(30);
+        let last_seen = start + chrono::Duration::minutes(12);
+
+        store
+            .save_focus_span(&FocusSpan::open(&window("Code"), start))
+            .unwrap();
+        store
+            .insert_event_at(EventSource::Shell, "command", "{}", None, last_seen)
+            .unwrap();
+
+        assert_eq!(store.close_dangling_focus_spans().unwrap(), 1);
+        let spans = store.get_focus_spans(start, Utc::now()).unwrap();
+        assert_eq!(spans[0].ended_at, Some(last_seen));
+    }
+
+    fn meeting(platform: MeetingPlatform, start: DateTime<Utc>) -> MeetingSpan {
+        MeetingSpan {
+            id: Uuid::new_v4().to_string(),
+            platform,
+            title: Some("Standup".to_string()),
+            started_at: start,
+            ended_at: None,
+            attendee_count: Some(4),
+            calendar_uid: Some("standup@example.com".to_string()),
+            confidence: Some(crate::meeting::MeetingConfidence::High),
+            missed: false,
+        }
+    }
+
+    #[test]
+    fn test_meeting_span_round_trip() {
+        let store = EventStore::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::minutes(60);
+
+        let mut span = meeting(MeetingPlatform::GoogleMeet, start);
+        store.save_meeting_span(&span).unwrap();
+        let custom = meeting(
+            MeetingPlatform::Custom("Jitsi".to_string()),
+            start + chrono::Duration::minutes(40),
+        );
+        store.save_meeting_span(&custom).unwrap();
+
+        span.ended_at = Some(start + chrono::Duration::minutes(25));
+        store.save_meeting_span(&span).unwrap();
+
+        let spans = store.get_meeting_spans(start, Utc::now()).unwrap();
+        assert_eq!(spans, vec![span.clone(), custom]);
+
+        let later = start + chrono::Duration::minutes(30);
+        let spans = store.get_meeting_spans(later, Utc::now()).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            spans[0].platform,
+            MeetingPlatform::Custom("Jitsi".to_string())
+        );
+    }
+
+    #[test]
+    fn test_close_dangling_meeting_spans() {
+        let store = EventStore::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::minutes(30);
+        let last_seen = start + chrono::Duration::minutes(12);
+
+        store
+            .save_meeting_span(&meeting(MeetingPlatform::Zoom, start))
+            .unwrap();
+        store
+            .insert_event_at(EventSource::Meeting, "meeting_start", "{}", None, start)
+            .unwrap();
+        store
+            .insert_event_at(EventSource::Shell, "command", "{}", None, last_seen)
+            .unwrap();
+
+        assert_eq!(store.close_dangling_meeting_spans().unwrap(), 1);
+        let spans = store.get_meeting_spans(start, Utc::now()).unwrap();
+        assert_eq!(spans[0].ended_at, Some(last_seen));
+    }
+
+    fn insert_clipboard(store: &EventStore, text: &str, content_type: &str, app: &str) -> String {
+        let data = serde_json::json!({
+            "content": {
+                "text": text,
+                "content_type": content_type,
+                "original_length": text.len(),
+                "was_redacted": false,
+                "content_hash": 0
+            },
+            "timestamp": Utc::now(),
+            "source_app": app
+        });
+        store
+            .insert_event(
+                EventSource::Clipboard,
+                "clipboard_change",
+                &data.to_string(),
+                None,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_clipboard_filters_and_pagination() {
+        let store = EventStore::open_in_memory().unwrap();
+        insert_clipboard(&store, "fn main() {}", "code", "Code");
+        insert_clipboard(&store, "https://docs.rs", "url", "Firefox");
+        insert_clipboard(&store, "100% done_ok", "text", "Slack");
+        insert_clipboard(&store, "let x = 1;", "code", "Code");
+        store
+            .insert_event(EventSource::Shell, "command", "{}", None)
+            .unwrap();
+
+        let all = ClipboardFilter::default();
+        let (events, total) = store.get_clipboard_events(&all, 2, 0).unwrap();
+        assert_eq!(total, 4);
+        assert_eq!(events.len(), 2);
+        let (rest, _) = store.get_clipboard_events(&all, 10, 2).unwrap();
+        assert_eq!(rest.len(), 2);
+
+        let code = ClipboardFilter {
+            content_type: Some("code".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(store.get_clipboard_events(&code, 10, 0).unwrap().1, 2);
+
+        let firefox = ClipboardFilter {
+            source_app: Some("firefox".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(store.get_clipboard_events(&firefox, 10, 0).unwrap().1, 1);
+
+        // LIKE wildcards in the search are literal
+        let search = |q: &str| ClipboardFilter {
+            search: Some(q.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            store
+                .get_clipboard_events(&search("DOCS"), 10, 0)
+                .unwrap()
+                .1,
+            1
+        );
+        assert_eq!(
+            store.get_clipboard_events(&search("0%"), 10, 0).unwrap().1,
+            1
+        );
+        assert_eq!(
+            store.get_clipboard_events(&search("e_o"), 10, 0).unwrap().1,
+            1
+        );
+        assert_eq!(
+            store.get_clipboard_events(&search("_"), 10, 0).unwrap().1,
+            1
+        );
+    }
+
+    #[test]
+    fn test_find_clipboard_events_by_prefix() {
+        let store = EventStore::open_in_memory().unwrap();
+        let id = insert_clipboard(&store, "hello", "text", "Notes");
+        let shell_id = store
+            .insert_event(EventSource::Shell, "command", "{}", None)
+            .unwrap();
+
+        assert_eq!(store.find_clipboard_events(&id).unwrap()[0].id, id);
+        assert_eq!(store.find_clipboard_events(&id[..8]).unwrap().len(), 1);
+        assert!(store.find_clipboard_events(&shell_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let store = EventStore::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(3);
+        let session = SessionData {
+            session_id: "session-1".to_string(),
+            started_at: start,
+            ended_at: Some(start + chrono::Duration::hours(1)),
+            duration_minutes: 60,
+            event_count: 42,
+            idle_periods: vec![crate::idle::IdlePeriod {
+                started_at: start + chrono::Duration::minutes(20),
+                ended_at: start + chrono::Duration::minutes(30),
+                duration_seconds: 600,
+            }],
+            time_by_category: [("coding".to_string(), 2400)].into_iter().collect(),
+            had_meetings: true,
+            active_minutes: 50,
+        };
+        store.save_session(&session).unwrap();
+
+        let loaded = store.get_session("session-1").unwrap().unwrap();
+        assert_eq!(loaded.ended_at, session.ended_at);
+        assert_eq!(loaded.idle_periods.len(), 1);
+        assert_eq!(loaded.time_by_category.get("coding"), Some(&2400));
+        assert!(loaded.had_meetings);
+        assert!(store.get_session("missing").unwrap().is_none());
+
+        // Overlapping ranges include the session, later ones don't
+        let overlapping = start + chrono::Duration::minutes(30);
+        assert_eq!(
+            store.get_sessions(overlapping, Utc::now()).unwrap().len(),
+            1
+        );
+        let after = start + chrono::Duration::hours(2);
+        assert!(store.get_sessions(after, Utc::now()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_focus_session_round_trip() {
+        use crate::focus::{Interruption, InterruptionKind};
+
+        let store = EventStore::open_in_memory().unwrap();
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let session = FocusSession {
+            id: "s1".to_string(),
+            started_at: start,
+            ended_at: Some(start + chrono::Duration::minutes(50)),
+            goal: Some("review PRs".to_string()),
+            target_minutes: Some(50),
+            interruptions: vec![Interruption {
+                kind: InterruptionKind::Communication,
+                timestamp: start + chrono::Duration::minutes(10),
+                source: "Slack".to_string(),
+            }],
+            focus_score: Some(85),
+        };
+        store.save_focus_session(&session).unwrap();
+
+        let mut later = session.clone();
+        later.id = "s2".to_string();
+        later.started_at = start + chrono::Duration::minutes(55);
+        store.save_focus_session(&later).unwrap();
+
+        let sessions = store.get_focus_sessions(10).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, "s2");
+        assert_eq!(sessions[1], session);
+        assert_eq!(store.get_focus_sessions(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_and_update_event() {
+        let store = EventStore::open_in_memory().unwrap();
+        let id = store
+            .insert_event(EventSource::Note, "note", r#"{"text":"a"}"#, Some("siphon"))
+            .unwrap();
+
+        let event = store.get_event(&id).unwrap().unwrap();
+        assert_eq!(event.source, "note");
+        assert_eq!(event.project.as_deref(), Some("siphon"));
+
+        assert!(store.update_event_data(&id, r#"{"text":"b"}"#).unwrap());
+        assert_eq!(
+            store.get_event(&id).unwrap().unwrap().event_data,
+            r#"{"text":"b"}"#
+        );
+
+        assert!(store.get_event("missing").unwrap().is_none());
+        assert!(!store.update_event_data("missing", "{}").unwrap());
+    }
 }
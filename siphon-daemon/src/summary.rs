@@ -3,12 +3,22 @@
 //! Aggregates events from a time period into structured summaries.
 //! Provides the foundation for AI-powered insights.
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, DurationRound, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::debug;
 
+use crate::categories::{ActivityContext, CategoryRules};
+use crate::clipboard::ClipboardEntry;
+use crate::hotkey::HotkeyTrigger;
+use crate::idle::{ActivityCategory, IdleConfig};
+use crate::meeting::MeetingSpan;
+use crate::notes::NoteData;
+use crate::scoring::{self, FocusScore, FocusScoreConfig, FocusScorer, HourlyScore, ScoreInput};
 use crate::storage::{Event, EventStore};
+use crate::tasks::{TaskClusterer, TaskSegment};
+use crate::window::FocusSpan;
 
 /// Summary of a work session
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,12 +37,24 @@ pub struct SessionSummary {
     pub projects: Vec<ProjectSummary>,
     /// Applications used
     pub applications: Vec<AppUsageSummary>,
-    /// Key activities detected
+    /// Focused seconds per activity category
+    #[serde(default)]
+    pub time_by_category: HashMap<String, u64>,
+    /// Key activities detected: every note and mark, and the most recent others
     pub key_activities: Vec<ActivitySummary>,
+    /// What was worked on, as labelled task segments
+    #[serde(default)]
+    pub tasks: Vec<TaskSegment>,
     /// Meetings attended
     pub meetings: Vec<MeetingSummary>,
     /// Focus score (0-100)
     pub focus_score: u32,
+    /// How the focus score was reached
+    #[serde(default)]
+    pub focus_breakdown: FocusScore,
+    /// Focus score for each hour, for plotting
+    #[serde(default)]
+    pub hourly_focus: Vec<HourlyScore>,
     /// Suggested summary text (for AI to enhance)
     pub summary_text: String,
 }
@@ -43,6 +65,9 @@ pub struct ProjectSummary {
     pub name: String,
     pub event_count: usize,
     pub duration_minutes: u32,
+    /// Time attributed from the event timeline and editor/terminal focus
+    #[serde(default)]
+    pub duration_seconds: u64,
     pub file_changes: usize,
     pub commands_run: usize,
 }
@@ -52,7 +77,13 @@ pub struct ProjectSummary {
 pub struct AppUsageSummary {
     pub app_name: String,
     pub duration_minutes: u32,
+    /// Exact focused time, summed from focus spans
+    #[serde(default)]
+    pub duration_seconds: u64,
     pub window_switches: usize,
+    /// Category the app spent most of its time in
+    #[serde(default)]
+    pub category: String,
 }
 
 /// Summary of a detected activity
@@ -72,6 +103,34 @@ pub struct MeetingSummary {
     pub started_at: DateTime<Utc>,
 }
 
+/// What was being worked on around a meeting
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeetingContext {
+    /// Activity in the half hour before it started
+    pub before: Option<ContextActivity>,
+    /// Activity in the half hour after it ended (None while it's going on)
+    pub after: Option<ContextActivity>,
+}
+
+/// Main app and project over a stretch of time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextActivity {
+    /// App focused the longest
+    pub app_name: Option<String>,
+    pub category: Option<String>,
+    /// Project worked on closest to the meeting
+    pub project: Option<String>,
+}
+
+/// Most events read for one summary; a busy week stays well under this
+pub const MAX_SUMMARY_EVENTS: u32 = 200_000;
+
+/// How far either side of a meeting to look for context
+const MEETING_CONTEXT_MINUTES: i64 = 30;
+
+/// Most key activities kept besides notes and marks
+const MAX_KEY_ACTIVITIES: usize = 20;
+
 /// Configuration for summary generation
 #[derive(Debug, Clone)]
 pub struct SummaryConfig {
@@ -81,6 +140,10 @@ pub struct SummaryConfig {
     pub include_clipboard: bool,
     /// Include browser URLs in summary
     pub include_urls: bool,
+    /// Longest gap between events still counted as time on the project
+    pub idle_threshold: std::time::Duration,
+    /// Weights and thresholds for the focus score
+    pub focus_score: FocusScoreConfig,
 }
 
 impl Default for SummaryConfig {
@@ -89,6 +152,8 @@ impl Default for SummaryConfig {
             min_session_duration: 15,
             include_clipboard: false, // Privacy by default
             include_urls: true,
+            idle_threshold: IdleConfig::default().idle_threshold,
+            focus_score: FocusScoreConfig::default(),
         }
     }
 }
@@ -96,12 +161,24 @@ impl Default for SummaryConfig {
 /// Generates session summaries from event data
 pub struct SummaryGenerator {
     config: SummaryConfig,
+    categories: Arc<CategoryRules>,
+    scorer: FocusScorer,
 }
 
 impl SummaryGenerator {
     /// Create a new summary generator
     pub fn new(config: SummaryConfig) -> Self {
-        Self { config }
+        Self::with_categories(config, Arc::new(CategoryRules::default()))
+    }
+
+    /// Create a summary generator using the given categorisation rules
+    pub fn with_categories(config: SummaryConfig, categories: Arc<CategoryRules>) -> Self {
+        let scorer = FocusScorer::new(config.focus_score.clone());
+        Self {
+            config,
+            categories,
+            scorer,
+        }
     }
 
     /// Generate a summary for a time range
@@ -112,11 +189,9 @@ impl SummaryGenerator {
         end_time: DateTime<Utc>,
     ) -> Option<SessionSummary> {
         // Get events in range
-        let events = store.get_events_since(start_time, None).ok()?;
-        let events: Vec<_> = events
-            .into_iter()
-            .filter(|e| e.timestamp <= end_time)
-            .collect();
+        let events = store
+            .get_events_between(start_time, end_time, Some(MAX_SUMMARY_EVENTS))
+            .ok()?;
 
         if events.is_empty() {
             return None;
@@ -140,20 +215,33 @@ impl SummaryGenerator {
             *events_by_source.entry(event.source.clone()).or_insert(0) += 1;
         }
 
+        // Extract application usage from focus spans
+        let spans = store
+            .get_focus_spans(start_time, end_time)
+            .unwrap_or_default();
+        let spans_end = end_time.min(Utc::now());
+
         // Extract projects
-        let projects = self.extract_projects(&events);
+        let projects = self.extract_projects(&events, &spans, start_time, spans_end);
 
-        // Extract application usage
-        let applications = self.extract_app_usage(&events);
+        let applications = self.extract_app_usage(&spans, start_time, spans_end);
+        let time_by_category = self.extract_category_time(&spans, start_time, spans_end);
 
         // Extract key activities
         let key_activities = self.extract_key_activities(&events);
 
+        // Cluster events into tasks
+        let tasks = TaskClusterer::default().cluster(&events, &spans);
+
         // Extract meetings
-        let meetings = self.extract_meetings(&events);
+        let meeting_spans = store
+            .get_meeting_spans(start_time, end_time)
+            .unwrap_or_default();
+        let meetings = self.extract_meetings(&meeting_spans, Utc::now());
 
-        // Calculate focus score
-        let focus_score = self.calculate_focus_score(&events, &applications, duration_minutes);
+        // Score focus over the whole range and hour by hour
+        let focus_breakdown = self.score_focus(&events, &spans, start_time, spans_end);
+        let hourly_focus = self.hourly_focus(&events, &spans, start_time, spans_end);
 
         // Generate summary text
         let summary_text = self.generate_summary_text(
@@ -172,13 +260,34 @@ impl SummaryGenerator {
             events_by_source,
             projects,
             applications,
+            time_by_category,
             key_activities,
+            tasks,
             meetings,
-            focus_score,
+            focus_score: focus_breakdown.score,
+            focus_breakdown,
+            hourly_focus,
             summary_text,
         })
     }
 
+    /// Focus score for a time range, without the minimum-duration cutoff
+    pub fn focus_score(
+        &self,
+        store: &EventStore,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> u32 {
+        let events = store
+            .get_events_between(start_time, end_time, Some(MAX_SUMMARY_EVENTS))
+            .unwrap_or_default();
+        let spans = store
+            .get_focus_spans(start_time, end_time)
+            .unwrap_or_default();
+        self.score_focus(&events, &spans, start_time, end_time.min(Utc::now()))
+            .score
+    }
+
     /// Generate a summary for the last N hours
     pub fn generate_recent_summary(
         &self,
@@ -190,8 +299,14 @@ impl SummaryGenerator {
         self.generate_summary(store, start_time, end_time)
     }
 
-    /// Extract project summaries from events
-    fn extract_projects(&self, events: &[Event]) -> Vec<ProjectSummary> {
+    /// Extract project summaries from events, timed from the event timeline
+    fn extract_projects(
+        &self,
+        events: &[Event],
+        spans: &[FocusSpan],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<ProjectSummary> {
         let mut projects: HashMap<String, ProjectSummary> = HashMap::new();
 
         for event in events {
@@ -200,6 +315,7 @@ impl SummaryGenerator {
                     name: project.clone(),
                     event_count: 0,
                     duration_minutes: 0,
+                    duration_seconds: 0,
                     file_changes: 0,
                     commands_run: 0,
                 });
@@ -215,70 +331,196 @@ impl SummaryGenerator {
             }
         }
 
-        // Estimate duration based on event distribution
-        let total_events: usize = projects.values().map(|p| p.event_count).sum();
-        for project in projects.values_mut() {
-            if total_events > 0 {
-                // Rough estimate: proportional to event count
-                project.duration_minutes =
-                    ((project.event_count as f64 / total_events as f64) * 60.0) as u32;
+        for (name, millis) in self.project_time(events, spans, start, end) {
+            if let Some(project) = projects.get_mut(&name) {
+                project.duration_seconds = (millis / 1000) as u64;
+                project.duration_minutes = (project.duration_seconds / 60) as u32;
             }
         }
 
         let mut result: Vec<_> = projects.into_values().collect();
-        result.sort_by(|a, b| b.event_count.cmp(&a.event_count));
+        result.sort_by(|a, b| {
+            b.duration_seconds
+                .cmp(&a.duration_seconds)
+                .then(b.event_count.cmp(&a.event_count))
+                .then_with(|| a.name.cmp(&b.name))
+        });
         result
     }
 
-    /// Extract application usage from window events
-    fn extract_app_usage(&self, events: &[Event]) -> Vec<AppUsageSummary> {
-        let mut apps: HashMap<String, AppUsageSummary> = HashMap::new();
+    /// Milliseconds per project in `[start, end]`
+    ///
+    /// A gap shorter than the idle threshold between two consecutive project
+    /// events counts towards the first one's project. Editor and terminal focus
+    /// that no such gap covers counts towards the project last worked on by
+    /// the end of it.
+    fn project_time(
+        &self,
+        events: &[Event],
+        spans: &[FocusSpan],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> HashMap<String, i64> {
+        let mut timeline: Vec<(DateTime<Utc>, &str)> = events
+            .iter()
+            .filter_map(|e| e.project.as_deref().map(|p| (e.timestamp, p)))
+            .collect();
+        timeline.sort_by_key(|&(at, _)| at);
+        let idle_threshold =
+            Duration::from_std(self.config.idle_threshold).unwrap_or(Duration::minutes(5));
 
-        for event in events {
-            if event.source == "window" {
-                // Parse window event data
-                if let Ok(data) = serde_json::from_str::<serde_json::Value>(&event.event_data) {
-                    if let Some(current) = data.get("current") {
-                        if let Some(app_name) = current.get("app_name").and_then(|v| v.as_str()) {
-                            let entry =
-                                apps.entry(app_name.to_string()).or_insert(AppUsageSummary {
-                                    app_name: app_name.to_string(),
-                                    duration_minutes: 0,
-                                    window_switches: 0,
-                                });
-                            entry.window_switches += 1;
-
-                            // Estimate duration from previous window duration
-                            if let Some(duration) =
-                                data.get("previous_duration_ms").and_then(|v| v.as_u64())
-                            {
-                                entry.duration_minutes += (duration / 60000) as u32;
-                            }
-                        }
-                    }
+        let mut time: HashMap<String, i64> = HashMap::new();
+        let mut covered = Vec::new();
+        for pair in timeline.windows(2) {
+            let ((from, project), (to, _)) = (pair[0], pair[1]);
+            if to > from && to - from < idle_threshold {
+                *time.entry(project.to_string()).or_insert(0) += (to - from).num_milliseconds();
+                covered.push((from, to));
+            }
+        }
+
+        for span in spans {
+            let category = self.categories.categorize(&ActivityContext::from(span));
+            if !matches!(
+                category,
+                ActivityCategory::Coding | ActivityCategory::Terminal
+            ) {
+                continue;
+            }
+            let span_start = span.started_at.max(start);
+            let span_end = span.ended_at.unwrap_or(end).min(end);
+            for (from, to) in uncovered(span_start, span_end, &covered) {
+                let project = timeline.iter().rev().find(|&&(at, _)| at <= to);
+                if let Some(&(_, project)) = project {
+                    *time.entry(project.to_string()).or_insert(0) += (to - from).num_milliseconds();
                 }
             }
         }
 
+        time
+    }
+
+    /// Extract application usage from focus spans, clipped to `[start, end]`
+    fn extract_app_usage(
+        &self,
+        spans: &[FocusSpan],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<AppUsageSummary> {
+        let mut apps: HashMap<String, AppUsageSummary> = HashMap::new();
+        let mut app_categories: HashMap<String, HashMap<ActivityCategory, u64>> = HashMap::new();
+
+        for span in spans {
+            let seconds = span.seconds_within(start, end);
+            let entry = apps
+                .entry(span.app_name.clone())
+                .or_insert(AppUsageSummary {
+                    app_name: span.app_name.clone(),
+                    duration_minutes: 0,
+                    duration_seconds: 0,
+                    window_switches: 0,
+                    category: String::new(),
+                });
+            entry.window_switches += 1;
+            entry.duration_seconds += seconds;
+
+            let category = self.categories.categorize(&ActivityContext::from(span));
+            *app_categories
+                .entry(span.app_name.clone())
+                .or_default()
+                .entry(category)
+                .or_insert(0) += seconds;
+        }
+
+        // Round once at the end so short focuses still add up
+        for app in apps.values_mut() {
+            app.duration_minutes = (app.duration_seconds / 60) as u32;
+            app.category = app_categories
+                .get(&app.app_name)
+                .and_then(|cats| cats.iter().max_by_key(|(_, secs)| **secs))
+                .map(|(cat, _)| cat.to_string())
+                .unwrap_or_else(|| ActivityCategory::Other.to_string());
+        }
+
         let mut result: Vec<_> = apps.into_values().collect();
-        result.sort_by(|a, b| b.duration_minutes.cmp(&a.duration_minutes));
+        result.sort_by(|a, b| {
+            b.duration_seconds
+                .cmp(&a.duration_seconds)
+                .then_with(|| a.app_name.cmp(&b.app_name))
+        });
         result
     }
 
+    /// Sum focused seconds per category; a browser span counts towards its site's category
+    fn extract_category_time(
+        &self,
+        spans: &[FocusSpan],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> HashMap<String, u64> {
+        let mut time: HashMap<String, u64> = HashMap::new();
+        for span in spans {
+            let seconds = span.seconds_within(start, end);
+            if seconds == 0 {
+                continue;
+            }
+            let category = self.categories.categorize(&ActivityContext::from(span));
+            *time.entry(category.to_string()).or_insert(0) += seconds;
+        }
+        time
+    }
+
     /// Extract key activities from events
     fn extract_key_activities(&self, events: &[Event]) -> Vec<ActivitySummary> {
         let mut activities = Vec::new();
 
         for event in events {
-            // Detect hotkey triggers
+            // Detect hotkey triggers, with the note if one was added
             if event.source == "hotkey" {
+                let note = serde_json::from_str::<HotkeyTrigger>(&event.event_data)
+                    .ok()
+                    .and_then(|t| t.note);
                 activities.push(ActivitySummary {
                     activity_type: "mark".to_string(),
-                    description: "Marked an important moment".to_string(),
+                    description: match note {
+                        Some(note) => format!("Marked: {}", preview(&note, 80)),
+                        None => "Marked an important moment".to_string(),
+                    },
                     timestamp: event.timestamp,
                 });
             }
 
+            // Freeform notes
+            if event.source == "note" {
+                if let Ok(note) = serde_json::from_str::<NoteData>(&event.event_data) {
+                    let tags: String = note.tags.iter().map(|t| format!(" #{}", t)).collect();
+                    activities.push(ActivitySummary {
+                        activity_type: "note".to_string(),
+                        description: format!("Note: {}{}", preview(&note.text, 80), tags),
+                        timestamp: event.timestamp,
+                    });
+                }
+            }
+
+            // Clipboard copies, only when opted in
+            if self.config.include_clipboard
+                && event.source == "clipboard"
+                && event.event_type == "clipboard_change"
+            {
+                if let Some(entry) = ClipboardEntry::from_event(event) {
+                    let preview: String = entry.text.chars().take(60).collect();
+                    activities.push(ActivitySummary {
+                        activity_type: "clipboard".to_string(),
+                        description: format!(
+                            "Copied {}: {}",
+                            entry.content_type.as_str(),
+                            preview.trim()
+                        ),
+                        timestamp: event.timestamp,
+                    });
+                }
+            }
+
             // Detect git commits
             if event.source == "shell" && event.event_type == "command" {
                 if let Ok(data) = serde_json::from_str::<serde_json::Value>(&event.event_data) {
@@ -310,91 +552,216 @@ impl SummaryGenerator {
             }
         }
 
-        // Limit to most recent activities
-        activities.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        activities.truncate(20);
+        // Limit to most recent activities, but keep every note and mark:
+        // they were written down on purpose, and exports list them all
+        activities.sort_by_key(|a| std::cmp::Reverse(a.timestamp));
+        let mut others = 0;
+        activities.retain(|a| {
+            if a.activity_type == "note" || a.activity_type == "mark" {
+                return true;
+            }
+            others += 1;
+            others <= MAX_KEY_ACTIVITIES
+        });
         activities
     }
 
-    /// Extract meeting summaries
-    fn extract_meetings(&self, events: &[Event]) -> Vec<MeetingSummary> {
-        let mut meetings = Vec::new();
+    /// Meetings held in the range, from the stored meeting spans
+    ///
+    /// Missed meetings are left out; one still going on counts up to `now`.
+    fn extract_meetings(&self, spans: &[MeetingSpan], now: DateTime<Utc>) -> Vec<MeetingSummary> {
+        spans
+            .iter()
+            .filter(|span| !span.missed)
+            .map(|span| MeetingSummary {
+                platform: serde_json::to_value(&span.platform)
+                    .ok()
+                    .and_then(|v| v.as_str().map(|s| s.to_string()))
+                    .unwrap_or_else(|| "unknown".to_string()),
+                title: span.title.clone(),
+                duration_minutes: span.duration_minutes(now),
+                started_at: span.started_at,
+            })
+            .collect()
+    }
 
-        for event in events {
-            if event.source == "meeting" && event.event_type == "meeting_end" {
-                if let Ok(data) = serde_json::from_str::<serde_json::Value>(&event.event_data) {
-                    let platform = data
-                        .get("platform")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown")
-                        .to_string();
-                    let title = data
-                        .get("title")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string());
-                    let duration_minutes = data
-                        .get("duration_minutes")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(0) as u32;
-
-                    meetings.push(MeetingSummary {
-                        platform,
-                        title,
-                        duration_minutes,
-                        started_at: event.timestamp,
-                    });
-                }
-            }
-        }
+    /// Activity before and after a meeting, e.g. which project was picked up again
+    pub fn meeting_context(
+        &self,
+        store: &EventStore,
+        meeting: &MeetingSpan,
+        now: DateTime<Utc>,
+    ) -> MeetingContext {
+        let window = Duration::minutes(MEETING_CONTEXT_MINUTES);
+        let start = meeting.started_at;
+        let before = self.context_activity(store, start - window, start, true);
+        let after = meeting
+            .ended_at
+            .filter(|&end| end < now)
+            .and_then(|end| self.context_activity(store, end, (end + window).min(now), false));
 
-        meetings
+        MeetingContext { before, after }
     }
 
-    /// Calculate focus score (0-100)
-    fn calculate_focus_score(
+    /// Main app and project in `[from, to]`; the project nearest `to` if
+    /// `closest_to_end`, else nearest `from`
+    fn context_activity(
         &self,
-        events: &[Event],
-        apps: &[AppUsageSummary],
-        duration_minutes: u32,
-    ) -> u32 {
-        if duration_minutes == 0 || events.is_empty() {
-            return 0;
-        }
+        store: &EventStore,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        closest_to_end: bool,
+    ) -> Option<ContextActivity> {
+        let spans = store.get_focus_spans(from, to).unwrap_or_default();
+        let app = self.extract_app_usage(&spans, from, to).into_iter().next();
 
-        let mut score = 100u32;
+        // Newest first
+        let events = store.get_events_between(from, to, None).unwrap_or_default();
+        let mut projects = events.iter().filter_map(|e| e.project.clone());
+        let project = if closest_to_end {
+            projects.next()
+        } else {
+            projects.next_back()
+        };
 
-        // Penalize excessive context switching
-        let window_switches: usize = apps.iter().map(|a| a.window_switches).sum();
-        let switches_per_hour = (window_switches as f64 / duration_minutes as f64) * 60.0;
-        if switches_per_hour > 30.0 {
-            score = score.saturating_sub(20);
-        } else if switches_per_hour > 15.0 {
-            score = score.saturating_sub(10);
+        if app.is_none() && project.is_none() {
+            return None;
         }
+        Some(ContextActivity {
+            app_name: app.as_ref().map(|a| a.app_name.clone()),
+            category: app.map(|a| a.category),
+            project,
+        })
+    }
+
+    /// Focus score for `[start, end]`, with what each factor contributed
+    fn score_focus(
+        &self,
+        events: &[Event],
+        spans: &[FocusSpan],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> FocusScore {
+        let blocks = self.deep_work_blocks(spans, start, end);
+        let input = self.score_input(events, spans, &blocks, start, end);
+        self.scorer.score(&input)
+    }
+
+    /// Focus score for each hour of `[start, end]`
+    ///
+    /// Deep work is found over the whole range, so a block running across
+    /// the hour counts towards both hours.
+    fn hourly_focus(
+        &self,
+        events: &[Event],
+        spans: &[FocusSpan],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<HourlyScore> {
+        let blocks = self.deep_work_blocks(spans, start, end);
+        let mut hours = Vec::new();
+        let mut hour = start.duration_trunc(Duration::hours(1)).unwrap_or(start);
+        while hour < end {
+            let next = hour + Duration::hours(1);
+            let (from, to) = (hour.max(start), next.min(end));
+            let hour_events = events
+                .iter()
+                .filter(|e| e.timestamp >= from && e.timestamp < to);
+            let hour_spans: Vec<FocusSpan> = spans
+                .iter()
+                .filter(|s| s.seconds_within(from, to) > 0)
+                .cloned()
+                .collect();
 
-        // Penalize too many apps (fragmented attention)
-        if apps.len() > 10 {
-            score = score.saturating_sub(15);
-        } else if apps.len() > 5 {
-            score = score.saturating_sub(5);
+            let input = self.score_input(hour_events, &hour_spans, &blocks, from, to);
+            let active = input.event_count > 0 || input.focused_seconds > 0;
+            hours.push(HourlyScore {
+                hour,
+                score: active.then(|| self.scorer.score(&input).score),
+            });
+            hour = next;
         }
+        hours
+    }
 
-        // Bonus for productive activities
-        let productive_events = events
+    /// Unbroken runs of coding and terminal focus long enough to count as deep work
+    fn deep_work_blocks(
+        &self,
+        spans: &[FocusSpan],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let runs: Vec<_> = spans
             .iter()
-            .filter(|e| {
-                e.source == "editor"
-                    || (e.source == "shell" && e.event_type == "command")
-                    || e.source == "filesystem"
+            .filter(|span| {
+                matches!(
+                    self.categories.categorize(&ActivityContext::from(*span)),
+                    ActivityCategory::Coding | ActivityCategory::Terminal
+                )
             })
-            .count();
+            .map(|span| {
+                (
+                    span.started_at.max(start),
+                    span.ended_at.unwrap_or(end).min(end),
+                )
+            })
+            .filter(|(from, to)| from < to)
+            .collect();
+        let config = self.scorer.config();
+        scoring::deep_work_blocks(
+            &runs,
+            config.deep_work_min_minutes,
+            config.deep_work_max_break_secs,
+        )
+    }
+
+    /// Measure what the focus score is based on in `[start, end]`
+    fn score_input<'a>(
+        &self,
+        events: impl IntoIterator<Item = &'a Event>,
+        spans: &[FocusSpan],
+        deep_work_blocks: &[(DateTime<Utc>, DateTime<Utc>)],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ScoreInput {
+        let apps = self.extract_app_usage(spans, start, end);
 
-        let productivity_ratio = productive_events as f64 / events.len() as f64;
-        if productivity_ratio > 0.5 {
-            score = (score + 10).min(100);
+        // Focused time in productive categories counts too, so reading docs
+        // in a browser isn't worth less than running commands
+        let category_time = self.extract_category_time(spans, start, end);
+        let productive_seconds = category_time
+            .iter()
+            .filter(|(category, _)| {
+                self.categories
+                    .is_productive(&ActivityCategory::from((*category).clone()))
+            })
+            .map(|(_, seconds)| *seconds)
+            .sum();
+
+        let (mut event_count, mut productive_events) = (0, 0);
+        for event in events {
+            event_count += 1;
+            if event.source == "editor"
+                || (event.source == "shell" && event.event_type == "command")
+                || event.source == "filesystem"
+            {
+                productive_events += 1;
+            }
         }
 
-        score
+        ScoreInput {
+            duration_seconds: (end - start).num_seconds().max(0) as u64,
+            focused_seconds: apps.iter().map(|a| a.duration_seconds).sum(),
+            window_switches: apps.iter().map(|a| a.window_switches).sum(),
+            app_count: apps.len(),
+            productive_seconds,
+            deep_work_seconds: deep_work_blocks
+                .iter()
+                .map(|&(from, to)| (to.min(end) - from.max(start)).num_seconds().max(0) as u64)
+                .sum(),
+            event_count,
+            productive_events,
+        }
     }
 
     /// Generate human-readable summary text
@@ -452,9 +819,43 @@ impl SummaryGenerator {
     }
 }
 
+/// Parts of `[start, end]` outside `covered`, which is sorted and non-overlapping
+fn uncovered(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    covered: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut pieces = Vec::new();
+    let mut cursor = start;
+    for &(from, to) in covered {
+        if from >= end {
+            break;
+        }
+        if from > cursor {
+            pieces.push((cursor, from));
+        }
+        cursor = cursor.max(to);
+    }
+    if cursor < end {
+        pieces.push((cursor, end));
+    }
+    pieces
+}
+
+/// First line of `text`, cut to `max_chars`
+fn preview(text: &str, max_chars: usize) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > max_chars {
+        format!("{}…", line.chars().take(max_chars).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::meeting::MeetingPlatform;
 
     #[test]
     fn test_summary_config_default() {
@@ -462,14 +863,352 @@ mod tests {
         assert_eq!(config.min_session_duration, 15);
         assert!(!config.include_clipboard);
         assert!(config.include_urls);
+        assert_eq!(config.idle_threshold.as_secs(), 300);
     }
 
     #[test]
     fn test_focus_score_calculation() {
         let generator = SummaryGenerator::new(SummaryConfig::default());
 
-        // Empty events should return 0
-        let score = generator.calculate_focus_score(&[], &[], 0);
-        assert_eq!(score, 0);
+        // No activity should score 0
+        let now = Utc::now();
+        let score = generator.score_focus(&[], &[], now, now);
+        assert_eq!(score.score, 0);
+        assert!(score.factors.is_empty());
+    }
+
+    fn span(app_name: &str, start: DateTime<Utc>, seconds: i64) -> FocusSpan {
+        FocusSpan {
+            id: uuid::Uuid::new_v4().to_string(),
+            app_name: app_name.to_string(),
+            title: String::new(),
+            url: None,
+            bundle_id: None,
+            started_at: start,
+            ended_at: Some(start + Duration::seconds(seconds)),
+        }
+    }
+
+    fn project_event(project: &str, at: DateTime<Utc>) -> Event {
+        Event {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: at,
+            source: "shell".to_string(),
+            event_type: "command".to_string(),
+            event_data: "{}".to_string(),
+            project: Some(project.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_notes_survive_busy_periods() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::hours(8);
+
+        let mut events = vec![Event {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: start,
+            source: "note".to_string(),
+            event_type: "note".to_string(),
+            event_data: r#"{"text": "Parser is slow"}"#.to_string(),
+            project: None,
+        }];
+        for minute in 1..=30 {
+            events.push(Event {
+                event_data: r#"{"command": "git commit -m wip"}"#.to_string(),
+                ..project_event("siphon", start + Duration::minutes(minute))
+            });
+        }
+
+        let activities = generator.extract_key_activities(&events);
+        let commits = activities
+            .iter()
+            .filter(|a| a.activity_type == "commit")
+            .count();
+        assert_eq!(commits, MAX_KEY_ACTIVITIES);
+        // The oldest of all, yet still there
+        let last = activities.last().unwrap();
+        assert_eq!(last.activity_type, "note");
+        assert_eq!(last.description, "Note: Parser is slow");
+    }
+
+    fn project_seconds(projects: &[ProjectSummary], name: &str) -> u64 {
+        projects
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.duration_seconds)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_project_time_from_interleaved_events() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::hours(8);
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+
+        // siphon for 20 minutes, website for 8, back to siphon for 8, then
+        // a long break before one last website command
+        let events: Vec<_> = [
+            ("siphon", 0),
+            ("siphon", 4),
+            ("siphon", 8),
+            ("siphon", 12),
+            ("siphon", 16),
+            ("website", 20),
+            ("website", 24),
+            ("siphon", 28),
+            ("siphon", 32),
+            ("website", 36),
+            ("website", 300),
+        ]
+        .into_iter()
+        .rev()
+        .map(|(project, minutes)| project_event(project, at(minutes)))
+        .collect();
+
+        let projects = generator.extract_projects(&events, &[], start, at(480));
+        assert_eq!(project_seconds(&projects, "siphon"), 28 * 60);
+        assert_eq!(project_seconds(&projects, "website"), 8 * 60);
+        assert_eq!(projects[0].name, "siphon");
+        assert_eq!(projects[0].duration_minutes, 28);
+        assert_eq!(projects[1].event_count, 4);
+    }
+
+    #[test]
+    fn test_project_time_is_not_capped_at_an_hour() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::hours(8);
+        // A command every two minutes for six hours
+        let events: Vec<_> = (0..=180)
+            .map(|i| project_event("siphon", start + Duration::minutes(2 * i)))
+            .collect();
+
+        let projects = generator.extract_projects(&events, &[], start, start + Duration::hours(8));
+        assert_eq!(projects[0].duration_minutes, 360);
+    }
+
+    #[test]
+    fn test_project_time_fills_in_editor_focus() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::hours(8);
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let events = vec![
+            project_event("website", at(95)),
+            project_event("siphon", at(2)),
+            project_event("siphon", at(0)),
+        ];
+        let spans = vec![
+            // Editing after the last siphon command, without more events
+            span("Code", at(0), 60 * 60),
+            // Browsing doesn't count towards any project
+            span("Safari", at(60), 30 * 60),
+            // Terminal open before the website command that ends it
+            span("Terminal", at(90), 10 * 60),
+        ];
+
+        let projects = generator.extract_projects(&events, &spans, start, at(480));
+        assert_eq!(project_seconds(&projects, "siphon"), 60 * 60);
+        assert_eq!(project_seconds(&projects, "website"), 10 * 60);
+    }
+
+    #[test]
+    fn test_uncovered_pieces() {
+        let base = Utc::now();
+        let at = |minutes: i64| base + Duration::minutes(minutes);
+        let covered = [(at(0), at(5)), (at(10), at(20)), (at(40), at(50))];
+        assert_eq!(
+            uncovered(at(2), at(45), &covered),
+            vec![(at(5), at(10)), (at(20), at(40))]
+        );
+        assert_eq!(uncovered(at(60), at(70), &covered), vec![(at(60), at(70))]);
+        assert!(uncovered(at(11), at(19), &covered).is_empty());
+    }
+
+    #[test]
+    fn test_meetings_use_real_start_and_skip_missed() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::hours(2);
+        let meeting = |platform, missed, ended_at| MeetingSpan {
+            id: uuid::Uuid::new_v4().to_string(),
+            platform,
+            title: Some("Planning".to_string()),
+            started_at: start,
+            ended_at,
+            attendee_count: None,
+            calendar_uid: None,
+            confidence: None,
+            missed,
+        };
+        let spans = vec![
+            meeting(
+                MeetingPlatform::GoogleMeet,
+                false,
+                Some(start + Duration::minutes(45)),
+            ),
+            meeting(
+                MeetingPlatform::Zoom,
+                true,
+                Some(start + Duration::hours(1)),
+            ),
+            meeting(MeetingPlatform::Zoom, false, None),
+        ];
+
+        let now = start + Duration::minutes(90);
+        let meetings = generator.extract_meetings(&spans, now);
+        assert_eq!(meetings.len(), 2);
+        assert_eq!(meetings[0].platform, "google_meet");
+        assert_eq!(meetings[0].started_at, start);
+        assert_eq!(meetings[0].duration_minutes, 45);
+        assert_eq!(meetings[1].duration_minutes, 90);
+    }
+
+    #[test]
+    fn test_meeting_context() {
+        use crate::storage::EventSource;
+
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let store = EventStore::open_in_memory().unwrap();
+        let start = Utc::now() - Duration::hours(2);
+        let end = start + Duration::minutes(30);
+
+        let event = |project: &str, at| {
+            store
+                .insert_event_at(EventSource::Shell, "command", "{}", Some(project), at)
+                .unwrap();
+        };
+        event("siphon", start - Duration::minutes(20));
+        event("website", start - Duration::minutes(5));
+        event("siphon", end + Duration::minutes(4));
+        event("website", end + Duration::minutes(20));
+        for span in [
+            span("Code", start - Duration::minutes(25), 20 * 60),
+            span("zoom.us", start - Duration::minutes(2), 32 * 60),
+            span("Terminal", end + Duration::minutes(1), 15 * 60),
+        ] {
+            store.save_focus_span(&span).unwrap();
+        }
+
+        let mut meeting = MeetingSpan {
+            id: uuid::Uuid::new_v4().to_string(),
+            platform: MeetingPlatform::Zoom,
+            title: None,
+            started_at: start,
+            ended_at: Some(end),
+            attendee_count: None,
+            calendar_uid: None,
+            confidence: None,
+            missed: false,
+        };
+        let context = generator.meeting_context(&store, &meeting, Utc::now());
+        let before = context.before.unwrap();
+        assert_eq!(before.app_name.as_deref(), Some("Code"));
+        assert_eq!(before.project.as_deref(), Some("website"));
+        let after = context.after.unwrap();
+        assert_eq!(after.app_name.as_deref(), Some("Terminal"));
+        assert_eq!(after.project.as_deref(), Some("siphon"));
+
+        // Nothing comes after a meeting that's still going on
+        meeting.ended_at = None;
+        let context = generator.meeting_context(&store, &meeting, Utc::now());
+        assert!(context.before.is_some());
+        assert!(context.after.is_none());
+    }
+
+    #[test]
+    fn test_app_usage_sums_short_spans_to_the_second() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::hours(1);
+
+        // 40 focuses of 45s each: 30 minutes that per-switch flooring reported as 0
+        let spans: Vec<_> = (0..40)
+            .map(|i| span("Code", start + Duration::seconds(i * 90), 45))
+            .collect();
+
+        let apps = generator.extract_app_usage(&spans, start, Utc::now());
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].duration_seconds, 1800);
+        assert_eq!(apps[0].duration_minutes, 30);
+        assert_eq!(apps[0].window_switches, 40);
+    }
+
+    #[test]
+    fn test_category_time_uses_site_not_browser() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::hours(1);
+
+        let mut docs = span("Google Chrome", start, 600);
+        docs.url = Some("https://docs.rs/serde".to_string());
+        let mut video = span("Google Chrome", start + Duration::minutes(10), 300);
+        video.url = Some("https://www.youtube.com/watch?v=1".to_string());
+        let spans = vec![
+            docs,
+            video,
+            span("Slack", start + Duration::minutes(15), 120),
+        ];
+
+        let time = generator.extract_category_time(&spans, start, Utc::now());
+        assert_eq!(time.get("coding"), Some(&600));
+        assert_eq!(time.get("entertainment"), Some(&300));
+        assert_eq!(time.get("communication"), Some(&120));
+
+        let apps = generator.extract_app_usage(&spans, start, Utc::now());
+        let chrome = apps.iter().find(|a| a.app_name == "Google Chrome").unwrap();
+        assert_eq!(chrome.category, "coding");
+    }
+
+    #[test]
+    fn test_app_usage_includes_open_span() {
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc::now() - Duration::minutes(10);
+        let mut open = span("Slack", start, 0);
+        open.ended_at = None;
+
+        let apps = generator.extract_app_usage(&[open], start, start + Duration::minutes(10));
+        assert_eq!(apps[0].duration_seconds, 600);
+    }
+
+    #[test]
+    fn test_hourly_focus_and_deep_work() {
+        use chrono::TimeZone;
+
+        let generator = SummaryGenerator::new(SummaryConfig::default());
+        let start = Utc.with_ymd_and_hms(2026, 10, 12, 9, 0, 0).unwrap();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        // A 30 minute coding block across 10:00, then Slack
+        let spans = vec![
+            span("Code", at(40), 30 * 60),
+            span("Slack", at(70), 40 * 60),
+        ];
+
+        let hourly = generator.hourly_focus(&[], &spans, start, at(180));
+        let scores: Vec<_> = hourly.iter().map(|h| h.score).collect();
+        // 10:00 has 10 of 50 minutes in deep work and productive
+        assert_eq!(scores, vec![Some(100), Some(76), None]);
+        assert_eq!(hourly[1].hour, at(60));
+
+        let score = generator.score_focus(&[], &spans, start, at(180));
+        let deep = score
+            .factors
+            .iter()
+            .find(|f| f.name == "deep_work")
+            .unwrap();
+        assert_eq!(deep.value, 30.0);
+        assert_eq!(score.score, 94);
+
+        // Too short to count once the minimum is raised
+        let config = SummaryConfig {
+            focus_score: FocusScoreConfig {
+                deep_work_min_minutes: 45,
+                ..FocusScoreConfig::default()
+            },
+            ..SummaryConfig::default()
+        };
+        let score = SummaryGenerator::new(config).score_focus(&[], &spans, start, at(180));
+        let deep = score
+            .factors
+            .iter()
+            .find(|f| f.name == "deep_work")
+            .unwrap();
+        assert_eq!(deep.contribution, 0.0);
     }
 }
@@ -0,0 +1,491 @@
+//! Task segments
+//!
+//! Splits activity into stretches of work on one thing and labels each one,
+//! e.g. "fix/auth-bug: auth middleware + jest". A new segment starts after a
+//! long gap, or when the git branch (from shell events) or the project
+//! changes. Labels come from the branch or project, the area of the code
+//! touched most (editor and file events), the main tool run, and window
+//! titles when there's nothing else to go on.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::storage::{EditorEventData, Event, ShellEventData};
+use crate::triggers::{detect_topic, extract_command_base};
+use crate::watcher::FileEventData;
+use crate::window::FocusSpan;
+
+/// Branches that say nothing about the task at hand
+const TRUNK_BRANCHES: &[&str] = &["main", "master", "develop", "trunk", "HEAD"];
+
+/// Commands that move around rather than do the work
+const NAVIGATION_COMMANDS: &[&str] = &[
+    "cd", "ls", "ll", "la", "pwd", "clear", "cat", "less", "more", "echo", "exit", "history",
+    "which", "man", "code", "vim", "nvim", "z", "j",
+];
+
+/// Launchers stripped from a command to find the tool, e.g. `npx jest`
+const LAUNCHERS: &[&[&str]] = &[
+    &["sudo"],
+    &["npx"],
+    &["bunx"],
+    &["pnpm", "exec"],
+    &["pnpm", "dlx"],
+    &["yarn", "dlx"],
+    &["poetry", "run"],
+    &["uv", "run"],
+    &["python", "-m"],
+    &["python3", "-m"],
+];
+
+/// Commands whose next word is a package script
+const RUN_SCRIPT: &[&str] = &["npm run", "yarn run", "pnpm run", "bun run"];
+
+/// Directory and file names too generic to describe an area of the code
+const GENERIC_PATH_PARTS: &[&str] = &[
+    "src", "lib", "app", "pkg", "internal", "cmd", "source", "index", "mod", "main", "init",
+    "__init__",
+];
+
+/// Configuration for task clustering
+#[derive(Debug, Clone)]
+pub struct TaskConfig {
+    /// A gap longer than this between events starts a new segment
+    pub max_gap: Duration,
+    /// Segments with fewer events are dropped as noise
+    pub min_events: usize,
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::minutes(15),
+            min_events: 2,
+        }
+    }
+}
+
+/// A stretch of work on one task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSegment {
+    /// e.g. "fix/auth-bug: auth middleware + jest"
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub duration_minutes: u32,
+    pub event_count: usize,
+    /// Files edited or changed, most touched first
+    pub files: Vec<String>,
+    /// Tools run, most used first, e.g. "cargo test" or "jest"
+    pub commands: Vec<String>,
+    /// Window titles focused longest, for segments without files
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<String>,
+}
+
+/// What one event says about the task
+#[derive(Default)]
+struct Signal<'a> {
+    at: DateTime<Utc>,
+    project: Option<&'a str>,
+    branch: Option<String>,
+    file: Option<String>,
+    command: Option<String>,
+}
+
+impl<'a> Signal<'a> {
+    fn from_event(event: &'a Event) -> Self {
+        let mut signal = Signal {
+            at: event.timestamp,
+            project: event.project.as_deref(),
+            ..Default::default()
+        };
+        match event.source.as_str() {
+            "shell" => {
+                if let Ok(data) = serde_json::from_str::<ShellEventData>(&event.event_data) {
+                    signal.branch = data.git_branch.filter(|b| !b.is_empty() && b != "HEAD");
+                    signal.command = Some(data.command);
+                }
+            }
+            "editor" => {
+                if let Ok(data) = serde_json::from_str::<EditorEventData>(&event.event_data) {
+                    signal.file = Some(data.file_path);
+                }
+            }
+            "filesystem" => {
+                if let Ok(data) = serde_json::from_str::<FileEventData>(&event.event_data) {
+                    if !data.is_directory {
+                        signal.file = Some(data.file_path);
+                    }
+                }
+            }
+            _ => {}
+        }
+        signal
+    }
+}
+
+/// Events gathered into one segment
+struct Segment<'a> {
+    signals: Vec<Signal<'a>>,
+    project: Option<&'a str>,
+    branch: Option<String>,
+}
+
+impl<'a> Segment<'a> {
+    fn start(signal: Signal<'a>) -> Self {
+        Self {
+            project: signal.project,
+            branch: signal.branch.clone(),
+            signals: vec![signal],
+        }
+    }
+
+    /// Whether `signal` belongs to a different task
+    fn breaks_at(&self, signal: &Signal, max_gap: Duration) -> bool {
+        let last = self.signals.last().map(|s| s.at).unwrap_or(signal.at);
+        let new_branch = matches!(
+            (&self.branch, &signal.branch),
+            (Some(current), Some(next)) if current != next
+        );
+        let new_project = matches!(
+            (self.project, signal.project),
+            (Some(current), Some(next)) if current != next
+        );
+        signal.at - last > max_gap || new_branch || new_project
+    }
+
+    fn push(&mut self, signal: Signal<'a>) {
+        self.project = self.project.or(signal.project);
+        if self.branch.is_none() {
+            self.branch = signal.branch.clone();
+        }
+        self.signals.push(signal);
+    }
+}
+
+/// Clusters events into task segments
+pub struct TaskClusterer {
+    config: TaskConfig,
+}
+
+impl TaskClusterer {
+    pub fn new(config: TaskConfig) -> Self {
+        Self { config }
+    }
+
+    /// Task segments for `events` (in any order), oldest first, with window
+    /// titles taken from `spans`
+    pub fn cluster(&self, events: &[Event], spans: &[FocusSpan]) -> Vec<TaskSegment> {
+        let mut events: Vec<&Event> = events.iter().collect();
+        events.sort_by_key(|e| e.timestamp);
+
+        let mut segments: Vec<Segment> = Vec::new();
+        for signal in events.into_iter().map(Signal::from_event) {
+            match segments.last_mut() {
+                Some(segment) if !segment.breaks_at(&signal, self.config.max_gap) => {
+                    segment.push(signal)
+                }
+                _ => segments.push(Segment::start(signal)),
+            }
+        }
+
+        segments
+            .into_iter()
+            .filter(|segment| segment.signals.len() >= self.config.min_events)
+            .map(|segment| task_segment(segment, spans))
+            .collect()
+    }
+}
+
+impl Default for TaskClusterer {
+    fn default() -> Self {
+        Self::new(TaskConfig::default())
+    }
+}
+
+fn task_segment(segment: Segment, spans: &[FocusSpan]) -> TaskSegment {
+    let started_at = segment.signals[0].at;
+    let ended_at = segment.signals[segment.signals.len() - 1].at;
+
+    let files = ranked(segment.signals.iter().filter_map(|s| s.file.clone()));
+    let areas = ranked(
+        segment
+            .signals
+            .iter()
+            .filter_map(|s| s.file.as_deref().and_then(file_area)),
+    );
+    let commands: Vec<&str> = segment
+        .signals
+        .iter()
+        .filter_map(|s| s.command.as_deref())
+        .collect();
+    let tools = ranked(
+        commands
+            .iter()
+            .map(|c| command_family(c))
+            .filter(|family| is_work_command(family)),
+    );
+    let windows = window_titles(spans, started_at, ended_at);
+
+    let prefix = segment
+        .branch
+        .as_deref()
+        .filter(|b| !TRUNK_BRANCHES.contains(b))
+        .or(segment.project);
+    let mut parts: Vec<String> = areas
+        .into_iter()
+        .take(1)
+        .chain(tools.first().cloned())
+        .collect();
+    if parts.is_empty() {
+        parts.extend(detect_topic(commands.iter().copied()));
+    }
+    if parts.is_empty() && prefix.is_none() {
+        parts.extend(windows.first().cloned());
+    }
+    let label = match (prefix, parts.is_empty()) {
+        (Some(prefix), true) => prefix.to_string(),
+        (Some(prefix), false) => format!("{}: {}", prefix, parts.join(" + ")),
+        (None, false) => parts.join(" + "),
+        (None, true) => "Untitled".to_string(),
+    };
+
+    TaskSegment {
+        label,
+        project: segment.project.map(String::from),
+        branch: segment.branch,
+        started_at,
+        ended_at,
+        duration_minutes: (ended_at - started_at).num_minutes().max(0) as u32,
+        event_count: segment.signals.len(),
+        files: files.into_iter().take(5).collect(),
+        commands: tools.into_iter().take(5).collect(),
+        windows: windows.into_iter().take(3).collect(),
+    }
+}
+
+/// Distinct values, most frequent first (ties in order of first appearance)
+fn ranked(values: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for value in values {
+        match counts.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts.into_iter().map(|(value, _)| value).collect()
+}
+
+/// The tool a command runs, e.g. `npx jest --watch` gives `jest` and
+/// `npm run lint` gives `npm run lint`
+pub fn command_family(command: &str) -> String {
+    let mut words: Vec<&str> = command
+        .split_whitespace()
+        .skip_while(|word| word.contains('=') && !word.starts_with('-'))
+        .collect();
+    while let Some(launcher) = LAUNCHERS
+        .iter()
+        .find(|launcher| words.len() > launcher.len() && words.starts_with(launcher))
+    {
+        words.drain(..launcher.len());
+    }
+    let base = extract_command_base(&words.join(" "));
+    match words.get(2) {
+        // Package scripts are named after what they do
+        Some(script) if RUN_SCRIPT.contains(&base.as_str()) && !script.starts_with('-') => {
+            format!("{} {}", base, script)
+        }
+        _ => base,
+    }
+}
+
+/// Whether a command family is work rather than moving around or git bookkeeping
+fn is_work_command(family: &str) -> bool {
+    !family.is_empty() && !family.starts_with("git") && !NAVIGATION_COMMANDS.contains(&family)
+}
+
+/// The part of the code a file belongs to, e.g. `src/auth/middleware.ts`
+/// gives "auth middleware"
+pub fn file_area(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    let describes = |part: &str| !part.is_empty() && !GENERIC_PATH_PARTS.contains(&part);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().replace(['_', '-'], " "))
+        .filter(|s| describes(s) && !s.starts_with('.'));
+    let dir = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|s| s.to_string_lossy().into_owned())
+        .filter(|d| describes(d) && !d.starts_with('.'));
+
+    match (dir, stem) {
+        (Some(dir), Some(stem)) if dir != stem => Some(format!("{} {}", dir, stem)),
+        (_, Some(stem)) => Some(stem),
+        (dir, None) => dir,
+    }
+}
+
+/// Titles of the windows focused longest in `[start, end]`
+fn window_titles(spans: &[FocusSpan], start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<String> {
+    let mut seconds: HashMap<&str, u64> = HashMap::new();
+    for span in spans {
+        let title = span.title.trim();
+        let focused = span.seconds_within(start, end);
+        if !title.is_empty() && focused > 0 {
+            *seconds.entry(title).or_insert(0) += focused;
+        }
+    }
+    let mut titles: Vec<_> = seconds.into_iter().collect();
+    titles.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    titles
+        .into_iter()
+        .map(|(title, _)| title.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-14T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + Duration::minutes(minutes)
+    }
+
+    fn event(source: &str, data: serde_json::Value, project: &str, minutes: i64) -> Event {
+        Event {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: at(minutes),
+            source: source.to_string(),
+            event_type: "test".to_string(),
+            event_data: data.to_string(),
+            project: Some(project.to_string()),
+        }
+    }
+
+    fn shell(command: &str, branch: &str, minutes: i64) -> Event {
+        let data = serde_json::json!({
+            "command": command,
+            "exit_code": 0,
+            "duration_ms": 10,
+            "cwd": "/home/user/webapp",
+            "git_branch": branch
+        });
+        event("shell", data, "webapp", minutes)
+    }
+
+    fn edit(path: &str, minutes: i64) -> Event {
+        let data = serde_json::json!({ "action": "file_save", "file_path": path });
+        event("editor", data, "webapp", minutes)
+    }
+
+    #[test]
+    fn test_command_family() {
+        assert_eq!(command_family("npx jest --watch src/auth"), "jest");
+        assert_eq!(command_family("CI=1 pnpm exec vitest run"), "vitest");
+        assert_eq!(command_family("cargo test -p siphon"), "cargo test");
+        assert_eq!(command_family("sudo docker compose up"), "docker compose");
+        assert_eq!(command_family("npx"), "npx");
+        assert_eq!(command_family("npm run lint -- --fix"), "npm run lint");
+        assert_eq!(command_family("npm run"), "npm run");
+    }
+
+    #[test]
+    fn test_file_area() {
+        assert_eq!(
+            file_area("/home/user/webapp/src/auth/middleware.ts").as_deref(),
+            Some("auth middleware")
+        );
+        assert_eq!(file_area("src/auth/index.ts").as_deref(), Some("auth"));
+        assert_eq!(file_area("src/summary.rs").as_deref(), Some("summary"));
+        assert_eq!(
+            file_area("src/rate_limit.rs").as_deref(),
+            Some("rate limit")
+        );
+        assert_eq!(file_area("src/main.rs"), None);
+    }
+
+    #[test]
+    fn test_segments_split_on_branch_and_gap() {
+        let events = vec![
+            shell("git checkout -b fix/auth-bug", "fix/auth-bug", 0),
+            edit("/home/user/webapp/src/auth/middleware.ts", 2),
+            shell("npx jest src/auth", "fix/auth-bug", 4),
+            edit("/home/user/webapp/src/auth/middleware.ts", 6),
+            edit("/home/user/webapp/src/auth/session.ts", 7),
+            shell("npx jest src/auth", "fix/auth-bug", 8),
+            // Review on another branch
+            shell("git checkout feat/billing", "feat/billing", 10),
+            shell("npm run lint", "feat/billing", 12),
+            // Back after lunch on the same branch
+            shell("cargo build", "feat/billing", 90),
+            shell("cargo build", "feat/billing", 95),
+        ];
+
+        let segments = TaskClusterer::default().cluster(&events, &[]);
+        assert_eq!(segments.len(), 3);
+
+        assert_eq!(segments[0].label, "fix/auth-bug: auth middleware + jest");
+        assert_eq!(segments[0].branch.as_deref(), Some("fix/auth-bug"));
+        assert_eq!(segments[0].project.as_deref(), Some("webapp"));
+        assert_eq!(segments[0].event_count, 6);
+        assert_eq!(segments[0].duration_minutes, 8);
+        assert_eq!(
+            segments[0].files[0],
+            "/home/user/webapp/src/auth/middleware.ts"
+        );
+        assert_eq!(segments[0].commands, vec!["jest"]);
+
+        assert_eq!(segments[1].label, "feat/billing: npm run lint");
+        assert_eq!(segments[2].label, "feat/billing: cargo build");
+        assert_eq!(segments[2].started_at, at(90));
+    }
+
+    #[test]
+    fn test_segments_split_on_project_and_fall_back_to_titles() {
+        let window = |title: &str, minutes: i64, seconds: i64| FocusSpan {
+            id: uuid::Uuid::new_v4().to_string(),
+            app_name: "Firefox".to_string(),
+            title: title.to_string(),
+            url: None,
+            bundle_id: None,
+            started_at: at(minutes),
+            ended_at: Some(at(minutes) + Duration::seconds(seconds)),
+        };
+        let mut events = vec![
+            edit("/home/user/webapp/src/main.rs", 0),
+            edit("/home/user/webapp/src/main.rs", 3),
+            event("editor", serde_json::json!({}), "docs", 5),
+            event("editor", serde_json::json!({}), "docs", 6),
+        ];
+        // A lone event is noise
+        events.push(event("editor", serde_json::json!({}), "other", 60));
+        // Window events carry no project and stay in the current segment
+        let mut untagged = event("window", serde_json::json!({}), "", 7);
+        untagged.project = None;
+        events.push(untagged);
+        let spans = vec![
+            window("Writing guide - Docs", 5, 90),
+            window("Inbox", 6, 30),
+        ];
+
+        let segments = TaskClusterer::default().cluster(&events, &spans);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].label, "webapp");
+        assert_eq!(segments[0].files, vec!["/home/user/webapp/src/main.rs"]);
+        assert_eq!(segments[1].label, "docs");
+        assert_eq!(segments[1].event_count, 3);
+        assert_eq!(segments[1].windows, vec!["Writing guide - Docs", "Inbox"]);
+    }
+}
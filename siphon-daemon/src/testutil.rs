@@ -0,0 +1,55 @@
+//! Shared test helpers
+
+#[cfg(target_os = "linux")]
+pub use self::dbus::MockBus;
+
+#[cfg(target_os = "linux")]
+mod dbus {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+
+    /// A private `dbus-daemon` standing in for the system bus
+    pub struct MockBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl MockBus {
+        /// Start a bus, or None when `dbus-daemon` isn't installed
+        pub fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address=1"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+
+        pub fn address(&self) -> &str {
+            &self.address
+        }
+
+        /// A new client connection to the bus
+        pub fn connect(&self) -> zbus::blocking::Connection {
+            zbus::blocking::connection::Builder::address(self.address())
+                .unwrap()
+                .build()
+                .unwrap()
+        }
+    }
+
+    impl Drop for MockBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+}
@@ -292,7 +292,7 @@ impl TriggerDetector {
             context: TriggerContext {
                 recent_commands: recent_cmds,
                 failure_count,
-                topic: detect_topic_from_commands(&self.recent_commands),
+                topic: detect_topic(self.recent_commands.iter().map(|c| c.command.as_str())),
                 project: project.map(String::from),
             },
             action_taken: None,
@@ -340,7 +340,7 @@ impl TriggerDetector {
 }
 
 /// Extract base command (first word or two)
-fn extract_command_base(command: &str) -> String {
+pub(crate) fn extract_command_base(command: &str) -> String {
     let parts: Vec<&str> = command.split_whitespace().collect();
     match parts.len() {
         0 => String::new(),
@@ -398,7 +398,7 @@ fn is_significant_command(command_base: &str) -> bool {
 }
 
 /// Detect topic from recent commands
-fn detect_topic_from_commands(commands: &VecDeque<CommandResult>) -> Option<String> {
+pub(crate) fn detect_topic<'a>(commands: impl IntoIterator<Item = &'a str>) -> Option<String> {
     let topics = [
         ("docker", "docker"),
         ("kubectl", "kubernetes"),
@@ -410,11 +410,7 @@ fn detect_topic_from_commands(commands: &VecDeque<CommandResult>) -> Option<Stri
         ("test", "testing"),
     ];
 
-    let all_commands: String = commands
-        .iter()
-        .map(|c| c.command.as_str())
-        .collect::<Vec<_>>()
-        .join(" ");
+    let all_commands: String = commands.into_iter().collect::<Vec<_>>().join(" ");
     let cmd_lower = all_commands.to_lowercase();
 
     for (pattern, topic) in topics {
@@ -0,0 +1,331 @@
+//! Local secret vault
+//!
+//! When tokenised redaction is on, the daemon stores each replaced secret
+//! here, keyed by its placeholder (`[SECRET:gh_3fa1b2]`). Values are
+//! encrypted with ChaCha20-Poly1305 under `~/.siphon/vault.key`, a key
+//! separate from the one used to derive placeholders. Nothing in the HTTP
+//! API reads the vault; only `siphon-ctl secrets reveal` does, straight
+//! from disk.
+//!
+//! This file is shared with `siphon-ctl` via `#[path]`, so it only depends
+//! on external crates.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub type VaultResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Returned by [`SecretVault::store`] when the placeholder already stands
+/// for a different secret: placeholder tags are short, so two secrets can
+/// share one
+#[derive(Debug)]
+pub struct PlaceholderCollision(pub String);
+
+impl std::fmt::Display for PlaceholderCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} already stands for a different secret", self.0)
+    }
+}
+
+impl std::error::Error for PlaceholderCollision {}
+
+/// A stored secret, without its value
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultEntry {
+    pub placeholder: String,
+    pub kind: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// How many times the secret was redacted
+    pub uses: u64,
+}
+
+/// Encrypted placeholder → secret store
+pub struct SecretVault {
+    conn: Connection,
+    cipher: ChaCha20Poly1305,
+}
+
+impl SecretVault {
+    /// Default vault database (`~/.siphon/vault.db`)
+    pub fn default_db_path() -> PathBuf {
+        siphon_dir().join("vault.db")
+    }
+
+    /// Default vault key (`~/.siphon/vault.key`)
+    pub fn default_key_path() -> PathBuf {
+        siphon_dir().join("vault.key")
+    }
+
+    /// Open the vault at the default location, creating the key if needed
+    pub fn open_default() -> VaultResult<Self> {
+        Self::open(&Self::default_db_path(), &Self::default_key_path())
+    }
+
+    /// Open a vault database with the key at `key_path`, creating the key if needed
+    pub fn open(db_path: &Path, key_path: &Path) -> VaultResult<Self> {
+        let key = load_or_create_key(key_path)?;
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(db_path)?;
+        restrict_permissions(db_path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS secrets (
+                placeholder TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                nonce BLOB NOT NULL,
+                ciphertext BLOB NOT NULL,
+                first_seen TEXT NOT NULL,
+                last_seen TEXT NOT NULL,
+                uses INTEGER NOT NULL DEFAULT 1
+            );",
+        )?;
+        Ok(Self {
+            conn,
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        })
+    }
+
+    /// Record a secret under its placeholder; repeats only bump the use count
+    ///
+    /// Fails with [`PlaceholderCollision`], keeping the stored secret, if the
+    /// placeholder is already taken by a different one.
+    pub fn store(&self, placeholder: &str, kind: &str, secret: &str) -> VaultResult<()> {
+        let now = Utc::now().to_rfc3339();
+        match self.reveal(placeholder)? {
+            Some(stored) if stored != secret => {
+                return Err(Box::new(PlaceholderCollision(placeholder.to_string())));
+            }
+            Some(_) => {
+                self.conn.execute(
+                    "UPDATE secrets SET last_seen = ?1, uses = uses + 1 WHERE placeholder = ?2",
+                    params![now, placeholder],
+                )?;
+                return Ok(());
+            }
+            None => {}
+        }
+
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        // The placeholder is authenticated too, so rows can't be swapped
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: secret.as_bytes(),
+                    aad: placeholder.as_bytes(),
+                },
+            )
+            .map_err(|_| "failed to encrypt secret")?;
+        self.conn.execute(
+            "INSERT INTO secrets (placeholder, kind, nonce, ciphertext, first_seen, last_seen)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![placeholder, kind, nonce.as_slice(), ciphertext, now],
+        )?;
+        Ok(())
+    }
+
+    /// Decrypt the secret behind a placeholder
+    pub fn reveal(&self, placeholder: &str) -> VaultResult<Option<String>> {
+        let row: Option<(Vec<u8>, Vec<u8>)> = self
+            .conn
+            .query_row(
+                "SELECT nonce, ciphertext FROM secrets WHERE placeholder = ?1",
+                params![placeholder],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((nonce, ciphertext)) = row else {
+            return Ok(None);
+        };
+        if nonce.len() != 12 {
+            return Err("corrupt vault entry".into());
+        }
+
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: placeholder.as_bytes(),
+                },
+            )
+            .map_err(|_| "failed to decrypt secret (wrong vault key?)")?;
+        Ok(Some(String::from_utf8(plaintext)?))
+    }
+
+    /// All stored placeholders, most recently seen first
+    pub fn list(&self) -> VaultResult<Vec<VaultEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT placeholder, kind, first_seen, last_seen, uses
+             FROM secrets ORDER BY last_seen DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let first_seen: String = row.get(2)?;
+            let last_seen: String = row.get(3)?;
+            Ok(VaultEntry {
+                placeholder: row.get(0)?,
+                kind: row.get(1)?,
+                first_seen: parse_timestamp(&first_seen),
+                last_seen: parse_timestamp(&last_seen),
+                uses: row.get::<_, i64>(4)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+/// Read a 32-byte key file, generating it (mode 0600) on first use
+pub fn load_or_create_key(path: &Path) -> io::Result<[u8; 32]> {
+    match std::fs::File::open(path) {
+        Ok(mut file) => {
+            let mut key = [0u8; 32];
+            file.read_exact(&mut key).map_err(|e| {
+                io::Error::new(e.kind(), format!("invalid key file {:?}: {}", path, e))
+            })?;
+            Ok(key)
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let key: [u8; 32] = ChaCha20Poly1305::generate_key(&mut OsRng).into();
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            options.open(path)?.write_all(&key)?;
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn siphon_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".siphon")
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn parse_timestamp(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn temp_dir() -> TempDir {
+        let dir = std::env::temp_dir().join(format!("siphon-vault-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    #[test]
+    fn test_store_and_reveal() {
+        let dir = temp_dir();
+        let vault = SecretVault::open(&dir.0.join("vault.db"), &dir.0.join("vault.key")).unwrap();
+
+        vault
+            .store("[SECRET:gh_3fa1b2]", "github_token", "ghp_example")
+            .unwrap();
+        vault
+            .store("[SECRET:gh_3fa1b2]", "github_token", "ghp_example")
+            .unwrap();
+
+        assert_eq!(
+            vault.reveal("[SECRET:gh_3fa1b2]").unwrap().as_deref(),
+            Some("ghp_example")
+        );
+        assert_eq!(vault.reveal("[SECRET:pw_000000]").unwrap(), None);
+
+        let entries = vault.list().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].uses, 2);
+        assert_eq!(entries[0].kind, "github_token");
+
+        // Ciphertext on disk, not the secret
+        let raw = std::fs::read(dir.0.join("vault.db")).unwrap();
+        assert!(!raw.windows(11).any(|w| w == b"ghp_example"));
+    }
+
+    #[test]
+    fn test_colliding_placeholder_is_refused() {
+        let dir = temp_dir();
+        let vault = SecretVault::open(&dir.0.join("vault.db"), &dir.0.join("vault.key")).unwrap();
+        vault
+            .store("[SECRET:pw_123456]", "password", "hunter2")
+            .unwrap();
+
+        let err = vault
+            .store("[SECRET:pw_123456]", "password", "correct horse")
+            .unwrap_err();
+        assert!(err.is::<PlaceholderCollision>());
+        assert_eq!(
+            vault.reveal("[SECRET:pw_123456]").unwrap().as_deref(),
+            Some("hunter2")
+        );
+        assert_eq!(vault.list().unwrap()[0].uses, 1);
+    }
+
+    #[test]
+    fn test_wrong_key_cannot_reveal() {
+        let dir = temp_dir();
+        let db = dir.0.join("vault.db");
+        SecretVault::open(&db, &dir.0.join("vault.key"))
+            .unwrap()
+            .store("[SECRET:pw_123456]", "password", "hunter2")
+            .unwrap();
+
+        let other = SecretVault::open(&db, &dir.0.join("other.key")).unwrap();
+        assert!(other.reveal("[SECRET:pw_123456]").is_err());
+    }
+
+    #[test]
+    fn test_key_is_created_once() {
+        let dir = temp_dir();
+        let path = dir.0.join("test.key");
+        let first = load_or_create_key(&path).unwrap();
+        let second = load_or_create_key(&path).unwrap();
+        assert_eq!(first, second);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
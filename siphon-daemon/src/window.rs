@@ -4,6 +4,12 @@
 //! - App name and window title
 //! - Browser URLs (for Safari, Chrome, etc.)
 //! - Time spent in each window
+//!
+//! On Linux, the focused window is read from Hyprland, sway/i3 or EWMH
+//! (see [`linux`]), falling back to `active-win-pos-rs`.
+
+#[cfg(target_os = "linux")]
+pub mod linux;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -95,12 +101,55 @@ pub struct WindowChangeEvent {
     pub timestamp: DateTime<Utc>,
 }
 
+/// A contiguous period during which one window had focus
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FocusSpan {
+    pub id: String,
+    pub app_name: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// None while the window is still focused
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl FocusSpan {
+    /// Start a new open span for a window
+    pub fn open(window: &WindowInfo, started_at: DateTime<Utc>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            app_name: window.app_name.clone(),
+            title: window.title.clone(),
+            url: window.url.clone(),
+            bundle_id: window.bundle_id.clone(),
+            started_at,
+            ended_at: None,
+        }
+    }
+
+    /// Seconds of this span that fall inside `[start, end]`, treating an open span as ending at `end`
+    pub fn seconds_within(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> u64 {
+        let span_end = self.ended_at.unwrap_or(end).min(end);
+        let span_start = self.started_at.max(start);
+        (span_end - span_start).num_seconds().max(0) as u64
+    }
+}
+
 /// Tracks the active window and emits events on changes
 pub struct WindowTracker {
     config: WindowConfig,
     last_window: Option<WindowInfo>,
     last_change_time: Instant,
     last_check_time: Instant,
+    /// Span for the focused window, closed on switch, idle or shutdown
+    current_span: Option<FocusSpan>,
+    /// Spans opened or closed since the last drain, waiting to be persisted
+    span_updates: Vec<FocusSpan>,
+    #[cfg(target_os = "linux")]
+    linux_backend: Option<linux::LinuxBackend>,
 }
 
 impl WindowTracker {
@@ -111,6 +160,10 @@ impl WindowTracker {
             last_window: None,
             last_change_time: Instant::now(),
             last_check_time: Instant::now(),
+            current_span: None,
+            span_updates: Vec::new(),
+            #[cfg(target_os = "linux")]
+            linux_backend: linux::LinuxBackend::detect(),
         }
     }
 
@@ -124,7 +177,11 @@ impl WindowTracker {
 
         // Get current active window
         let current = self.get_active_window()?;
+        self.apply_window(current)
+    }
 
+    /// Compare a freshly polled window with the last one and record any change
+    fn apply_window(&mut self, current: WindowInfo) -> Option<WindowChangeEvent> {
         // Check if window changed (compare app_name and title)
         let changed = match &self.last_window {
             Some(prev) => prev.app_name != current.app_name || prev.title != current.title,
@@ -146,6 +203,12 @@ impl WindowTracker {
                 timestamp: Utc::now(),
             };
 
+            // Close the previous focus span and open one for the new window
+            self.close_span(event.timestamp);
+            let span = FocusSpan::open(&current, event.timestamp);
+            self.span_updates.push(span.clone());
+            self.current_span = Some(span);
+
             self.last_window = Some(current);
             self.last_change_time = now;
 
@@ -169,7 +232,11 @@ impl WindowTracker {
 
         #[cfg(not(target_os = "macos"))]
         {
-            self.get_active_window_other()
+            let mut window = self.get_active_window_other()?;
+            if self.config.extract_urls && window.url.is_none() {
+                window.url = self.get_browser_url(&window.app_name, &window.title);
+            }
+            Some(window)
         }
     }
 
@@ -216,6 +283,12 @@ impl WindowTracker {
     fn get_active_window_other(&self) -> Option<WindowInfo> {
         use active_win_pos_rs::get_active_window;
 
+        // Prefer the compositor/window manager backend when one was detected
+        #[cfg(target_os = "linux")]
+        if let Some(ref backend) = self.linux_backend {
+            return backend.active_window();
+        }
+
         match get_active_window() {
             Ok(window) => Some(WindowInfo {
                 app_name: window.app_name,
@@ -312,10 +385,17 @@ impl WindowTracker {
         }
     }
 
-    #[cfg(not(target_os = "macos"))]
-    #[allow(dead_code)]
-    fn get_browser_url(&self, _app_name: &str) -> Option<String> {
-        // URL extraction not supported on non-macOS platforms yet
+    /// Get the current URL from a browser's window title (Linux)
+    ///
+    /// There's no AppleScript equivalent here, but the title carries the URL
+    /// for pages without a title of their own and with "URL in title" extensions.
+    #[cfg(target_os = "linux")]
+    fn get_browser_url(&self, app_name: &str, title: &str) -> Option<String> {
+        linux::browser_url_from_title(app_name, title)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn get_browser_url(&self, _app_name: &str, _title: &str) -> Option<String> {
         None
     }
 
@@ -328,6 +408,40 @@ impl WindowTracker {
     pub fn current_window_duration(&self) -> Duration {
         self.last_change_time.elapsed()
     }
+
+    /// Get the open focus span, if any
+    pub fn current_span(&self) -> Option<&FocusSpan> {
+        self.current_span.as_ref()
+    }
+
+    /// Close the open focus span at `at` (on idle or shutdown)
+    pub fn close_span(&mut self, at: DateTime<Utc>) -> Option<FocusSpan> {
+        let mut span = self.current_span.take()?;
+        span.ended_at = Some(at.max(span.started_at));
+        self.span_updates.push(span.clone());
+        Some(span)
+    }
+
+    /// Reopen a span for the still-focused window after the user returns from idle
+    pub fn resume_span(&mut self, at: DateTime<Utc>) -> Option<&FocusSpan> {
+        if self.current_span.is_none() {
+            let span = FocusSpan::open(self.last_window.as_ref()?, at);
+            self.span_updates.push(span.clone());
+            self.current_span = Some(span);
+        }
+        self.current_span.as_ref()
+    }
+
+    /// Restart the change timer after a sleep, which `Instant` doesn't see,
+    /// so the next change doesn't report pre-sleep time as time on the window
+    pub fn reset_change_timer(&mut self) {
+        self.last_change_time = Instant::now();
+    }
+
+    /// Take the spans opened or closed since the last call, for persisting
+    pub fn drain_span_updates(&mut self) -> Vec<FocusSpan> {
+        std::mem::take(&mut self.span_updates)
+    }
 }
 
 #[cfg(test)]
@@ -347,4 +461,73 @@ mod tests {
         assert!(tracker.last_window.is_none());
         assert!(tracker.current_window().is_none());
     }
+
+    fn window(app_name: &str, title: &str) -> WindowInfo {
+        WindowInfo {
+            app_name: app_name.to_string(),
+            title: title.to_string(),
+            process_id: 1,
+            bundle_id: None,
+            url: None,
+            bounds: None,
+        }
+    }
+
+    #[test]
+    fn test_window_switch_closes_span() {
+        let mut tracker = WindowTracker::new(WindowConfig::default());
+
+        tracker.apply_window(window("Code", "main.rs"));
+        assert!(tracker.apply_window(window("Code", "main.rs")).is_none());
+        tracker.apply_window(window("Slack", "general"));
+
+        let updates = tracker.drain_span_updates();
+        // open Code, close Code, open Slack
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[1].app_name, "Code");
+        assert!(updates[1].ended_at.is_some());
+        assert_eq!(updates[1].id, updates[0].id);
+        assert_eq!(tracker.current_span().unwrap().app_name, "Slack");
+        assert!(tracker.current_span().unwrap().ended_at.is_none());
+    }
+
+    #[test]
+    fn test_idle_closes_and_resume_reopens_span() {
+        let mut tracker = WindowTracker::new(WindowConfig::default());
+        tracker.apply_window(window("Code", "main.rs"));
+        let opened = tracker.current_span().unwrap().clone();
+
+        let closed = tracker.close_span(Utc::now()).unwrap();
+        assert_eq!(closed.id, opened.id);
+        assert!(tracker.current_span().is_none());
+        assert!(tracker.close_span(Utc::now()).is_none());
+
+        let resumed = tracker.resume_span(Utc::now()).unwrap();
+        assert_ne!(resumed.id, opened.id);
+        assert_eq!(resumed.app_name, "Code");
+    }
+
+    #[test]
+    fn test_span_seconds_within_range() {
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let mut span = FocusSpan::open(&window("Code", "main.rs"), start);
+        span.ended_at = Some(start + chrono::Duration::seconds(90));
+
+        // Fully inside
+        assert_eq!(
+            span.seconds_within(start - chrono::Duration::hours(1), Utc::now()),
+            90
+        );
+        // Clipped by the range start
+        assert_eq!(
+            span.seconds_within(start + chrono::Duration::seconds(30), Utc::now()),
+            60
+        );
+        // Open spans run to the end of the range
+        span.ended_at = None;
+        assert_eq!(
+            span.seconds_within(start, start + chrono::Duration::seconds(45)),
+            45
+        );
+    }
 }
@@ -0,0 +1,627 @@
+//! Linux window tracking backends
+//!
+//! `active-win-pos-rs` only understands X11 and returns nothing on most
+//! Wayland compositors, so on Linux we talk to the window manager directly:
+//! - Hyprland via its request socket (`j/activewindow`)
+//! - Sway and i3 via the i3 IPC protocol (`GET_TREE`)
+//! - Any EWMH-compliant X11 window manager via `_NET_ACTIVE_WINDOW`
+//!
+//! The backend is selected from the session environment at startup and can be
+//! forced with `SIPHON_WINDOW_BACKEND=hyprland|sway|x11`. Browser URLs come
+//! from the window title when it shows one ([`browser_url_from_title`]).
+
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::{WindowBounds, WindowInfo};
+
+/// Timeout for a single IPC round-trip so a wedged compositor can't stall the ticker
+const IPC_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// i3/sway IPC magic string
+const I3_IPC_MAGIC: &[u8] = b"i3-ipc";
+
+/// i3/sway IPC message type for GET_TREE
+const I3_IPC_GET_TREE: u32 = 4;
+
+/// A window manager backend that can report the focused window
+pub enum LinuxBackend {
+    /// Hyprland request socket
+    Hyprland(PathBuf),
+    /// Sway or i3 IPC socket
+    Sway(PathBuf),
+    /// X11 connection using EWMH hints
+    X11(Box<X11Backend>),
+}
+
+impl LinuxBackend {
+    /// Pick a backend from the session environment
+    pub fn detect() -> Option<Self> {
+        let forced = std::env::var("SIPHON_WINDOW_BACKEND")
+            .ok()
+            .map(|v| v.to_lowercase());
+
+        let backend = match forced.as_deref() {
+            Some("hyprland") => hyprland_socket_path().map(LinuxBackend::Hyprland),
+            Some("sway") | Some("i3") => sway_socket_path().map(LinuxBackend::Sway),
+            Some("x11") => X11Backend::connect().map(|x| LinuxBackend::X11(Box::new(x))),
+            Some(other) if other != "auto" => {
+                warn!("Unknown SIPHON_WINDOW_BACKEND '{}', detecting", other);
+                Self::detect_auto()
+            }
+            _ => Self::detect_auto(),
+        };
+
+        match &backend {
+            Some(b) => info!("Using {} window backend", b.name()),
+            None => debug!("No Linux window backend available, using active-win-pos-rs"),
+        }
+
+        backend
+    }
+
+    fn detect_auto() -> Option<Self> {
+        if let Some(path) = hyprland_socket_path() {
+            return Some(LinuxBackend::Hyprland(path));
+        }
+        if let Some(path) = sway_socket_path() {
+            return Some(LinuxBackend::Sway(path));
+        }
+        if std::env::var_os("DISPLAY").is_some() {
+            if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                debug!("No compositor socket found; X11 will only see XWayland windows");
+            }
+            return X11Backend::connect().map(|x| LinuxBackend::X11(Box::new(x)));
+        }
+        None
+    }
+
+    /// Backend name for logging
+    pub fn name(&self) -> &'static str {
+        match self {
+            LinuxBackend::Hyprland(_) => "hyprland",
+            LinuxBackend::Sway(_) => "sway",
+            LinuxBackend::X11(_) => "x11",
+        }
+    }
+
+    /// Query the currently focused window
+    pub fn active_window(&self) -> Option<WindowInfo> {
+        let result = match self {
+            LinuxBackend::Hyprland(path) => hyprland_active_window(path).map_err(|e| e.to_string()),
+            LinuxBackend::Sway(path) => sway_active_window(path).map_err(|e| e.to_string()),
+            LinuxBackend::X11(x11) => x11.active_window().map_err(|e| e.to_string()),
+        };
+
+        match result {
+            Ok(window) => window,
+            Err(e) => {
+                debug!("{} backend failed to get active window: {}", self.name(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Resolve the Hyprland request socket from `HYPRLAND_INSTANCE_SIGNATURE`
+fn hyprland_socket_path() -> Option<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+
+    // Hyprland >= 0.40 uses $XDG_RUNTIME_DIR/hypr, older versions /tmp/hypr
+    let mut candidates = Vec::new();
+    if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+        candidates.push(PathBuf::from(runtime_dir).join("hypr"));
+    }
+    candidates.push(PathBuf::from("/tmp/hypr"));
+
+    candidates
+        .into_iter()
+        .map(|dir| dir.join(&signature).join(".socket.sock"))
+        .find(|p| p.exists())
+}
+
+/// Resolve the sway/i3 IPC socket from `SWAYSOCK` or `I3SOCK`
+fn sway_socket_path() -> Option<PathBuf> {
+    ["SWAYSOCK", "I3SOCK"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(PathBuf::from)
+        .find(|p| p.exists())
+}
+
+fn connect_socket(path: &Path) -> std::io::Result<UnixStream> {
+    let stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(IPC_TIMEOUT))?;
+    stream.set_write_timeout(Some(IPC_TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Ask Hyprland for the active window
+pub fn hyprland_active_window(socket: &Path) -> std::io::Result<Option<WindowInfo>> {
+    let mut stream = connect_socket(socket)?;
+    stream.write_all(b"j/activewindow")?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    Ok(parse_hyprland_window(&response))
+}
+
+/// Parse the JSON reply of `hyprctl -j activewindow`
+fn parse_hyprland_window(response: &str) -> Option<WindowInfo> {
+    let data: Value = serde_json::from_str(response).ok()?;
+
+    let app_name = data
+        .get("class")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .or_else(|| data.get("initialClass").and_then(|v| v.as_str()))
+        .filter(|s| !s.is_empty())?
+        .to_string();
+
+    let pair = |key: &str| -> Option<(f64, f64)> {
+        let arr = data.get(key)?.as_array()?;
+        Some((arr.first()?.as_f64()?, arr.get(1)?.as_f64()?))
+    };
+    let bounds = match (pair("at"), pair("size")) {
+        (Some((x, y)), Some((width, height))) => Some(WindowBounds {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => None,
+    };
+
+    Some(WindowInfo {
+        app_name,
+        title: data
+            .get("title")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        process_id: data.get("pid").and_then(|v| v.as_u64()).unwrap_or(0),
+        bundle_id: None,
+        url: None,
+        bounds,
+    })
+}
+
+/// Ask sway/i3 for the layout tree and return the focused window
+pub fn sway_active_window(socket: &Path) -> std::io::Result<Option<WindowInfo>> {
+    let mut stream = connect_socket(socket)?;
+
+    let mut request = Vec::with_capacity(I3_IPC_MAGIC.len() + 8);
+    request.extend_from_slice(I3_IPC_MAGIC);
+    request.extend_from_slice(&0u32.to_ne_bytes());
+    request.extend_from_slice(&I3_IPC_GET_TREE.to_ne_bytes());
+    stream.write_all(&request)?;
+
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header)?;
+    if &header[..6] != I3_IPC_MAGIC {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "invalid i3-ipc reply header",
+        ));
+    }
+    let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload)?;
+
+    let tree: Value = serde_json::from_slice(&payload)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    Ok(find_focused_node(&tree).and_then(sway_node_to_window))
+}
+
+/// Depth-first search for the node with `"focused": true`
+fn find_focused_node(node: &Value) -> Option<&Value> {
+    if node.get("focused").and_then(|v| v.as_bool()) == Some(true) {
+        return Some(node);
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(*key).and_then(|v| v.as_array()))
+        .flatten()
+        .find_map(find_focused_node)
+}
+
+/// Convert a sway/i3 container into window info
+///
+/// Workspaces and outputs can also be focused; those have no app and are skipped.
+fn sway_node_to_window(node: &Value) -> Option<WindowInfo> {
+    // Native Wayland windows carry app_id, XWayland/i3 windows carry a WM_CLASS
+    let app_name = node
+        .get("app_id")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .or_else(|| {
+            node.get("window_properties")
+                .and_then(|p| p.get("class"))
+                .and_then(|v| v.as_str())
+        })?
+        .to_string();
+
+    let bounds = node.get("rect").and_then(|rect| {
+        Some(WindowBounds {
+            x: rect.get("x")?.as_f64()?,
+            y: rect.get("y")?.as_f64()?,
+            width: rect.get("width")?.as_f64()?,
+            height: rect.get("height")?.as_f64()?,
+        })
+    });
+
+    Some(WindowInfo {
+        app_name,
+        title: node
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        process_id: node.get("pid").and_then(|v| v.as_u64()).unwrap_or(0),
+        bundle_id: None,
+        url: None,
+        bounds,
+    })
+}
+
+/// X11 backend reading EWMH properties from the root window
+pub struct X11Backend {
+    conn: x11rb::rust_connection::RustConnection,
+    root: u32,
+    atoms: X11Atoms,
+}
+
+struct X11Atoms {
+    net_active_window: u32,
+    net_wm_name: u32,
+    net_wm_pid: u32,
+    utf8_string: u32,
+}
+
+impl X11Backend {
+    /// Connect to the X server named by `DISPLAY`
+    pub fn connect() -> Option<Self> {
+        use x11rb::connection::Connection;
+
+        let (conn, screen_num) = match x11rb::connect(None) {
+            Ok(c) => c,
+            Err(e) => {
+                debug!("Failed to connect to X server: {}", e);
+                return None;
+            }
+        };
+        let root = conn.setup().roots.get(screen_num)?.root;
+
+        let intern = |name: &[u8]| -> Option<u32> {
+            use x11rb::protocol::xproto::ConnectionExt;
+            Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom)
+        };
+        let atoms = X11Atoms {
+            net_active_window: intern(b"_NET_ACTIVE_WINDOW")?,
+            net_wm_name: intern(b"_NET_WM_NAME")?,
+            net_wm_pid: intern(b"_NET_WM_PID")?,
+            utf8_string: intern(b"UTF8_STRING")?,
+        };
+
+        Some(Self { conn, root, atoms })
+    }
+
+    /// Read `_NET_ACTIVE_WINDOW` and describe that window
+    pub fn active_window(&self) -> Result<Option<WindowInfo>, Box<dyn std::error::Error>> {
+        use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+        let active = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.atoms.net_active_window,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )?
+            .reply()?;
+        let window = match active.value32().and_then(|mut v| v.next()) {
+            Some(w) if w != 0 => w,
+            _ => return Ok(None),
+        };
+
+        // WM_CLASS is "instance\0class\0"; the class is the human-facing app name
+        let class = self
+            .conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)?
+            .reply()?;
+        let app_name = class
+            .value
+            .split(|b| *b == 0)
+            .filter(|s| !s.is_empty())
+            .nth(1)
+            .or_else(|| class.value.split(|b| *b == 0).find(|s| !s.is_empty()))
+            .map(|s| String::from_utf8_lossy(s).to_string());
+        let app_name = match app_name {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        let title = {
+            let net_name = self
+                .conn
+                .get_property(
+                    false,
+                    window,
+                    self.atoms.net_wm_name,
+                    self.atoms.utf8_string,
+                    0,
+                    4096,
+                )?
+                .reply()?;
+            if !net_name.value.is_empty() {
+                String::from_utf8_lossy(&net_name.value).to_string()
+            } else {
+                let wm_name = self
+                    .conn
+                    .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::ANY, 0, 4096)?
+                    .reply()?;
+                String::from_utf8_lossy(&wm_name.value).to_string()
+            }
+        };
+
+        let process_id = self
+            .conn
+            .get_property(
+                false,
+                window,
+                self.atoms.net_wm_pid,
+                AtomEnum::CARDINAL,
+                0,
+                1,
+            )?
+            .reply()?
+            .value32()
+            .and_then(|mut v| v.next())
+            .unwrap_or(0) as u64;
+
+        let bounds = match (
+            self.conn.get_geometry(window)?.reply(),
+            self.conn
+                .translate_coordinates(window, self.root, 0, 0)?
+                .reply(),
+        ) {
+            (Ok(geometry), Ok(origin)) => Some(WindowBounds {
+                x: origin.dst_x as f64,
+                y: origin.dst_y as f64,
+                width: geometry.width as f64,
+                height: geometry.height as f64,
+            }),
+            _ => None,
+        };
+
+        Ok(Some(WindowInfo {
+            app_name,
+            title,
+            process_id,
+            bundle_id: None,
+            url: None,
+            bounds,
+        }))
+    }
+}
+
+/// App names (X11 classes, Wayland app ids) of browsers, lowercased
+const BROWSER_APPS: &[&str] = &[
+    "firefox",
+    "librewolf",
+    "chrom",
+    "brave",
+    "vivaldi",
+    "microsoft-edge",
+    "opera",
+];
+
+/// The page's URL, when a browser's window title shows one
+///
+/// Titles end with the browser's name (`... - Mozilla Firefox`); a page
+/// without a `<title>`, or with a "URL in title" extension, shows its URL or
+/// a bare `host/path` there instead.
+pub fn browser_url_from_title(app_name: &str, title: &str) -> Option<String> {
+    let app_name = app_name.to_lowercase();
+    if !BROWSER_APPS.iter().any(|b| app_name.contains(b)) {
+        return None;
+    }
+    title
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| {
+                matches!(
+                    c,
+                    '(' | ')' | '[' | ']' | '<' | '>' | '"' | '\'' | ',' | '|'
+                )
+            })
+        })
+        .find_map(|word| {
+            if word.starts_with("https://") || word.starts_with("http://") {
+                return Some(word.to_string());
+            }
+            // A bare host and path, such as `meet.google.com/abc-defg-hij`
+            let (host, _) = word.split_once('/')?;
+            let labels: Vec<&str> = host.split('.').collect();
+            let is_host = labels.len() >= 2
+                && labels.iter().all(|l| {
+                    !l.is_empty() && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                })
+                && labels[labels.len() - 1]
+                    .chars()
+                    .all(|c| c.is_ascii_alphabetic());
+            is_host.then(|| format!("https://{}", word))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    /// Bind a throwaway socket and answer one request with `reply`
+    fn mock_socket(reply: impl FnOnce(&mut UnixStream) + Send + 'static) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("siphon-ipc-{}.sock", uuid::Uuid::new_v4()));
+        let listener = UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                reply(&mut stream);
+            }
+        });
+        path
+    }
+
+    const SWAY_TREE: &str = r#"{
+        "type": "root", "focused": false,
+        "nodes": [{
+            "type": "output", "focused": false,
+            "nodes": [{
+                "type": "workspace", "focused": false,
+                "nodes": [
+                    {"type": "con", "focused": false, "app_id": "kitty", "name": "zsh", "pid": 10},
+                    {"type": "con", "focused": true, "app_id": null, "pid": 42,
+                     "name": "main.rs - siphon - Visual Studio Code",
+                     "window_properties": {"class": "Code", "instance": "code"},
+                     "rect": {"x": 0, "y": 30, "width": 1920, "height": 1050}}
+                ],
+                "floating_nodes": []
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn test_sway_ipc_focused_window() {
+        let path = mock_socket(|stream| {
+            let mut header = [0u8; 14];
+            stream.read_exact(&mut header).unwrap();
+            assert_eq!(&header[..6], I3_IPC_MAGIC);
+            assert_eq!(
+                u32::from_ne_bytes([header[10], header[11], header[12], header[13]]),
+                I3_IPC_GET_TREE
+            );
+
+            let mut reply = Vec::new();
+            reply.extend_from_slice(I3_IPC_MAGIC);
+            reply.extend_from_slice(&(SWAY_TREE.len() as u32).to_ne_bytes());
+            reply.extend_from_slice(&I3_IPC_GET_TREE.to_ne_bytes());
+            reply.extend_from_slice(SWAY_TREE.as_bytes());
+            stream.write_all(&reply).unwrap();
+        });
+
+        let window = sway_active_window(&path).unwrap().unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(window.app_name, "Code");
+        assert_eq!(window.title, "main.rs - siphon - Visual Studio Code");
+        assert_eq!(window.process_id, 42);
+        assert_eq!(window.bounds.unwrap().height, 1050.0);
+    }
+
+    #[test]
+    fn test_sway_focused_workspace_is_not_a_window() {
+        let tree: Value = serde_json::from_str(
+            r#"{"nodes": [{"type": "workspace", "focused": true, "name": "1", "nodes": []}]}"#,
+        )
+        .unwrap();
+        let focused = find_focused_node(&tree).unwrap();
+        assert!(sway_node_to_window(focused).is_none());
+    }
+
+    #[test]
+    fn test_hyprland_socket_active_window() {
+        let path = mock_socket(|stream| {
+            let mut request = [0u8; 14];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(&request, b"j/activewindow");
+            stream
+                .write_all(
+                    br#"{"address": "0x1", "at": [10, 20], "size": [800, 600],
+                        "class": "firefox", "title": "Meet - abc-defg-hij", "pid": 777}"#,
+                )
+                .unwrap();
+        });
+
+        let window = hyprland_active_window(&path).unwrap().unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(window.app_name, "firefox");
+        assert_eq!(window.title, "Meet - abc-defg-hij");
+        assert_eq!(window.process_id, 777);
+        assert_eq!(
+            window.bounds,
+            Some(WindowBounds {
+                x: 10.0,
+                y: 20.0,
+                width: 800.0,
+                height: 600.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_hyprland_no_active_window() {
+        assert!(parse_hyprland_window("{}").is_none());
+        assert!(parse_hyprland_window("Invalid").is_none());
+    }
+
+    /// Run with `xvfb-run cargo test -- --ignored` to exercise the EWMH path
+    #[test]
+    #[ignore]
+    fn test_x11_backend_connects() {
+        let backend = X11Backend::connect().expect("DISPLAY must point at an X server");
+        // A bare Xvfb has no window manager, so there may be no active window
+        assert!(backend.active_window().is_ok());
+    }
+
+    #[test]
+    fn test_browser_url_from_title() {
+        assert_eq!(
+            browser_url_from_title(
+                "firefox",
+                "https://meet.google.com/abc-defg-hij - Mozilla Firefox"
+            )
+            .as_deref(),
+            Some("https://meet.google.com/abc-defg-hij")
+        );
+        assert_eq!(
+            browser_url_from_title("Google-chrome", "zoom.us/j/123456 - Google Chrome").as_deref(),
+            Some("https://zoom.us/j/123456")
+        );
+        assert_eq!(
+            browser_url_from_title(
+                "brave-browser",
+                "Weekly sync (https://teams.microsoft.com/l/meetup-join/x) - Brave"
+            )
+            .as_deref(),
+            Some("https://teams.microsoft.com/l/meetup-join/x")
+        );
+
+        // Ordinary titles, file paths and non-browsers
+        assert_eq!(
+            browser_url_from_title("firefox", "Rust Blog - Mozilla Firefox"),
+            None
+        );
+        assert_eq!(
+            browser_url_from_title("firefox", "src/main.rs - Mozilla Firefox"),
+            None
+        );
+        assert_eq!(
+            browser_url_from_title("firefox", "v1.2/notes - Mozilla Firefox"),
+            None
+        );
+        assert_eq!(
+            browser_url_from_title("code", "https://example.com/x - Visual Studio Code"),
+            None
+        );
+    }
+}